@@ -10,15 +10,24 @@ blueprint! {
         
         // Candy Hashmap with name, symbol, price.              
         candy_map: HashMap<Address, (String, String, Decimal)>,        
-        // metaBadge Hashmap with entry fee level, metaCandy amount & Candy address.  
-        badge_map: HashMap<Address, (Decimal, Decimal, Address)>,         
+        // Position Hashmaps by candy address, with price range, metaCandy amount & accrued fee by 
+        // position NFT key.  
+        positions: HashMap<Address, HashMap<NonFungibleKey, Position>>,         
         // Candy Hashmap with accrued fee, metaCandy amount & address.
         meta_map: HashMap<Address, (Decimal, Decimal, Address)>,         
         // metaCandy Hashmap with MetaToken resource adresses. 
         meta: HashMap<Address, MetaToken>,  
         
-        // Badge to mint and burn metaCandies.                      
+        // Badge to mint and burn metaCandies and position NFTs.                      
         minter_badge: Vault,         
+        // Position NFT resource recording liquidity provider's price range.
+        position_def: ResourceDef,
+        // Number of position NFTs minted so far, used as key of the next one.
+        position_count: u128,
+        // Minimum metaCandy amount of a position, so that dust positions can't bloat swaps.
+        min_position: Decimal,
+        // Price range bounds must be multiples of tick spacing, so that swaps cross few boundaries.
+        tick_spacing: Decimal,
         // Owner badge to determine protocol fee and collect accrued XRD fee.                                     
         owner_badge: ResourceDef,      
         
@@ -36,6 +45,15 @@ blueprint! {
                 .metadata("name", " MinterBadge ")
                 .initial_supply_fungible(1);
 
+            let position_def: ResourceDef = ResourceBuilder::new_non_fungible()
+                .metadata("name", " CandyPosition ")
+                .flags(MINTABLE | BURNABLE | INDIVIDUAL_METADATA_MUTABLE)
+                .badge(
+                    minter_badge.resource_def(), 
+                    MAY_MINT | MAY_BURN | MAY_CHANGE_INDIVIDUAL_METADATA
+                )
+                .no_initial_supply();
+
             let badge_bucket = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
                 .metadata("name", " OwnerBadge ")
                 .initial_supply_fungible(1);
@@ -44,10 +62,14 @@ blueprint! {
                 collected_xrd: Vault::new(RADIX_TOKEN),
                 candy_vaults: HashMap::new(),
                 candy_map: HashMap::new(),
-                badge_map: HashMap::new(),
+                positions: HashMap::new(),
                 meta_map: HashMap::new(),
                 meta: HashMap::new(),
                 minter_badge: Vault::with_bucket(minter_badge),
+                position_def,
+                position_count: 0,
+                min_position: dec!(1),
+                tick_spacing: dec!("0.01"),
                 owner_badge: badge_bucket.resource_def(),
                 xrd_fee: dec!(0),
                 xrd_claimed: dec!(0),
@@ -57,62 +79,92 @@ blueprint! {
             (component,badge_bucket)
         }
 
-            // Create a metaBadge and populate a hashmap to associate a determinated entry fee level
-            // to a candy stoke event.
-            fn add_meta_badge(
+            // Mint a position NFT recording the price range chosen by liquidity provider and 
+            // populate positions hashmap to track relative metaCandy amount and accrued fee.
+            fn add_position(
                 &mut self, 
-                symbol: String, 
                 candy_addr: Address, 
-                entry_fee: Decimal, 
+                price_low: Decimal, 
+                price_high: Decimal, 
                 meta_amnt: Decimal
             ) -> Bucket {
-                let mut meta_badge_res_def: ResourceDef = 
-                    ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
-                        .metadata("symbol", format!(" mBadge{}", symbol.clone()))
-                        .flags(MINTABLE | BURNABLE)
-                        .badge(self.minter_badge.resource_def(), MAY_MINT | MAY_BURN)
-                        .no_initial_supply();
-                
-                let badge_address = meta_badge_res_def.address();               
-                self.badge_map.insert(badge_address.clone(),(entry_fee, meta_amnt, candy_addr));
-
-                self.minter_badge.authorize(|auth| {meta_badge_res_def.mint(1, auth)})
+                assert!(meta_amnt > dec!(0)," Let's stock a positive candy amount ");
+                assert!(meta_amnt >= self.min_position," Position below minimum size of {} candy ", self.min_position);
+                assert!(price_low >= dec!(0)," Price range lower bound can't be negative ");
+                assert!(
+                    price_high == dec!(0) || price_high > price_low,
+                    " Price range upper bound must exceed lower one, or be zero for no bound "
+                );
+                assert!(
+                    CandyDex::on_tick(self, price_low) && CandyDex::on_tick(self, price_high),
+                    " Price range bounds must be multiples of {} $XRD tick spacing ", self.tick_spacing
+                );
+
+                self.position_count += 1;
+                let key = NonFungibleKey::from(self.position_count);
+                let position = Position::new(candy_addr, price_low, price_high, meta_amnt);
+
+                self.positions.entry(candy_addr).or_insert_with(HashMap::new)
+                    .insert(key.clone(), position.clone());
+
+                self.minter_badge.authorize(|auth| {
+                    self.position_def.mint_non_fungible(&key, position.to_data(), auth)
+                })
             }
 
-            // Populate a hashmap relating a determinated metaBadge address to a metaCandy amount
-            // with the purpose of guarantee a fair Protocol's accrued fee distribution.
-            fn meta_amounts(
+            // Update position metaCandy amount and accrued fee once liquidity is withdrawn via 
+            // "unstock_candy" function, burn position NFT if all liquidity is removed.
+            fn position_withdraw(
                 &mut self, 
-                meta_badge: Bucket, 
-                meta_amount: Decimal, 
-                flag: Decimal
+                position_nft: Bucket, 
+                candy_addr: Address, 
+                meta_amnt: Decimal, 
+                fee_amnt: Decimal
             ) -> Bucket {
-                let meta_badge_addr: Address = meta_badge.resource_address().clone();
-                
-                let (a,mut m_candy_amnt,c) = *self.badge_map.get(&meta_badge_addr).unwrap();
-                let entry_fee = a;
-                let candy_addr = c;               
-                
-                // Determine if caller is a "stock/restock" method or an "unstock" one and udate
-                // consequentely metaCandy amount, burn metaBadge if all liquidity is removed. 
-                if flag == dec!(0) {
-                    m_candy_amnt = m_candy_amnt+meta_amount;
-                    self.badge_map.insert(meta_badge_addr,(entry_fee,m_candy_amnt,candy_addr));
-                    meta_badge
-                } else if flag == dec!(1) {
-                    assert!( meta_amount <= m_candy_amnt," Let's check passed amount ");
-                    m_candy_amnt = m_candy_amnt-meta_amount;
-                  
-                    if m_candy_amnt == dec!(0) {
-                        self.badge_map.remove(&meta_badge_addr);
-                        CandyDex::badge_burn(self, meta_badge);
-                        Bucket::new(RADIX_TOKEN)
-                    } else { 
-                        self.badge_map.insert(meta_badge_addr,(entry_fee,m_candy_amnt,candy_addr));
-                        meta_badge 
+                let key = position_nft.get_non_fungible_key();
+                let candy_positions = self.positions.get_mut(&candy_addr).unwrap();
+                let mut position = candy_positions.get(&key).unwrap().clone();
+
+                position.meta_amount = position.meta_amount-meta_amnt;
+                position.accrued_fee = position.accrued_fee-fee_amnt;
+
+                if position.meta_amount == dec!(0) {
+                    candy_positions.remove(&key);
+                } else {
+                    candy_positions.insert(key.clone(), position.clone());
+                }
+
+                if position.meta_amount == dec!(0) {
+                    self.minter_badge.authorize(|auth| {position_nft.burn_with_auth(auth)});
+                    Bucket::new(self.position_def.clone())
+                } else {
+                    self.minter_badge.authorize(|auth| {
+                        self.position_def.update_non_fungible_data(&key, position.to_data(), auth)
+                    });
+                    position_nft
+                }
+            }
+
+            // Verify a price range bound is a multiple of tick spacing.
+            fn on_tick(&self, price: Decimal) -> bool {
+                price.0 % self.tick_spacing.0 == 0
+            }
+
+            // Retrieve position relative to a position NFT, verifying its resource address.
+            fn position_get(
+                &self, 
+                position_def: ResourceDef, 
+                candy_addr: Address, 
+                key: &NonFungibleKey
+            ) -> Position {
+                assert!(position_def == self.position_def," Please supply a candy position NFT ");
+
+                match self.positions.get(&candy_addr).and_then(|candy_positions| candy_positions.get(key)) {
+                    Some(position) => position.clone(),
+                    None => {
+                        info!(" No position's correspondence! ");
+                        std::process::abort()
                     }
-                } else { 
-                    std::process::abort() 
                 }
             }
 
@@ -160,15 +212,14 @@ blueprint! {
             }
 
             // Burn a metacandy amount relative to amount of candy claimed by end user 
-            // via "unstock_candy" function.
-            fn meta_burn(&mut self, meta_candy: Bucket) {                
-                self.minter_badge.authorize(|auth| {meta_candy.burn_with_auth(auth)});
-            }
+            // via "unstock_candy" function and update total metaCandy minted amount.
+            fn meta_burn(&mut self, meta_candy: Bucket, candy_address: Address) {                
+                match self.meta_map.get_mut(&candy_address.clone()) {
+                    Some((_a,minted_amnt,_c)) => *minted_amnt = *minted_amnt-meta_candy.amount(),
+                    None => std::process::abort()                  
+                };
 
-            // Burn metaBadge once all relative metaCandy has been claimed by end user 
-            // via "unstock_candy" function.
-            fn badge_burn(&mut self, meta_badge: Bucket) {
-                self.minter_badge.authorize(|auth| {meta_badge.burn_with_auth(auth)});
+                self.minter_badge.authorize(|auth| {meta_candy.burn_with_auth(auth)});
             }
 
             // Retrieve price of specific candy type.
@@ -248,22 +299,99 @@ blueprint! {
                 candy
             }
 
-            // Take buyed candy from candy vault and increment total accrued fee in relative hashmap.
+            // Calculate candy reserve amount backing current price while it moves up or down, and 
+            // the nearest price range boundary where in range positions change. Each in range 
+            // position is weighted by its range amplification factor, so that narrow ranges provide 
+            // deeper liquidity than full range ones with same candy amount, but never more candy than
+            // they hold before price reaches their boundary.
+            fn liquidity_segment(
+                &self, 
+                candy_addr: Address, 
+                price: Decimal, 
+                up: bool
+            ) -> (Decimal,Option<Decimal>) {
+                let total_candy = self.candy_vaults.get(&candy_addr).unwrap().amount();
+                let (_a,total_minted,_c) = *self.meta_map.get(&candy_addr).unwrap();
+
+                let mut active_meta: Decimal = dec!(0);
+                let mut boundary: Option<Decimal> = None;
+                for position in self.positions.get(&candy_addr).into_iter().flat_map(|p| p.values()) {
+                    if position.active(price, up) {
+                        active_meta = active_meta+position.meta_amount*position.amplification();
+                    }
+                    for bound in [position.price_low, position.price_high] {
+                        let ahead = if up { bound > price } else { bound < price && bound > dec!(0) };
+                        if ahead && boundary.map_or(true, |b| if up { bound < b } else { bound > b }) {
+                            boundary = Some(bound);
+                        }
+                    }
+                }
+
+                if total_minted == dec!(0) {
+                    return (dec!(0),boundary);
+                }
+                (total_candy*active_meta/total_minted,boundary)
+            }
+
+            // Credit accrued fee to in range positions pro rata to their metaCandy amount. 
+            // Fee is shared among all candy positions if none of them is in range.
+            fn credit_fee(&mut self, fee_amnt: Decimal, candy_addr: Address) {
+                let price = CandyDex::candyprice(self, candy_addr);
+                let candy_positions = match self.positions.get_mut(&candy_addr) {
+                    Some(candy_positions) => candy_positions,
+                    None => return
+                };
+
+                let mut in_range_meta: Decimal = dec!(0);
+                let mut total_meta: Decimal = dec!(0);
+                for position in candy_positions.values() {
+                    total_meta = total_meta+position.meta_amount;
+                    if position.in_range(price) {
+                        in_range_meta = in_range_meta+position.meta_amount;
+                    }
+                }
+
+                let range_only = in_range_meta > dec!(0);
+                let share_meta = if range_only { in_range_meta } else { total_meta };
+                if share_meta == dec!(0) {
+                    return;
+                }
+
+                for position in candy_positions.values_mut() {
+                    if !range_only || position.in_range(price) {
+                        position.accrued_fee = 
+                            position.accrued_fee+fee_amnt*position.meta_amount/share_meta;
+                    }
+                }
+            }
+
+            // Take buyed candy from candy vault, increment total accrued fee in relative hashmap
+            // and credit it to liquidity providers positions.
             fn candytake(&mut self, candy_out_nbr: Decimal, candy_out_addr: Address) -> Bucket {
+                let fee_amnt = candy_out_nbr*self.fee/100;
+
                 match self.meta_map.get_mut(&candy_out_addr.clone()) {
-                    Some((amnt_fee,_b,_c)) => *amnt_fee = *amnt_fee+candy_out_nbr*self.fee/100,
+                    Some((amnt_fee,_b,_c)) => *amnt_fee = *amnt_fee+fee_amnt,
                     None => std::process::abort()                  
                 };
 
+                CandyDex::credit_fee(self, fee_amnt, candy_out_addr);
+
                 match self.candy_vaults.get_mut(&candy_out_addr) {
-                    Some(vault) => vault.take(candy_out_nbr-(candy_out_nbr*self.fee/100)),
+                    Some(vault) => {
+                        let candy_out = candy_out_nbr-(candy_out_nbr*self.fee/100);
+                        assert!(candy_out <= vault.amount()," Not enough candy liquidity ");
+                        vault.take(candy_out)
+                    },
                     None => { info!("Candy not in stock! ");
                               std::process::abort()
                     }
                 }
             }
 
-            // Calculate new candy price.
+            // Calculate new candy price. Price moves within a liquidity segment until it reaches a 
+            // price range boundary, then goes on with the in range positions beyond it. A segment 
+            // without liquidity is crossed for free.
             fn price_mod(
                 &mut self, 
                 amount: Decimal, 
@@ -271,16 +399,47 @@ blueprint! {
                 price: Decimal, 
                 flag: i32
             ) -> Decimal {
+                let up = flag == 1 || flag == 2;
+                let mut amount = amount;
+                let mut price = price;
 
-                // Retrieve candy reserve amount.
-                let total_candy = self.candy_vaults.get(&address).unwrap().amount();
-            
-                // Determine relative math formula once identified calling method. 
-                match flag {
-                    1 => total_candy*price/(total_candy-amount),         
-                    2 => (total_candy*price + amount*price)/total_candy, 
-                    3 => (total_candy*price - amount*price)/total_candy,
-                    _ => total_candy*price/(total_candy+amount)
+                loop {
+                    if amount == dec!(0) {
+                        return price;
+                    }
+
+                    // Retrieve in range candy reserve amount and next range boundary.
+                    let (total_candy,boundary) = CandyDex::liquidity_segment(self, address, price, up);
+
+                    // Determine candy amount moving price up to boundary once identified calling method.
+                    let to_boundary = match boundary {
+                        Some(bound) => match flag {
+                            1 => total_candy-total_candy*price/bound,
+                            2 => total_candy*bound/price-total_candy,
+                            3 => total_candy-total_candy*bound/price,
+                            _ => total_candy*price/bound-total_candy
+                        },
+                        None => {
+                            assert!(
+                                total_candy > amount || (total_candy > dec!(0) && (flag == 0 || flag == 2)),
+                                " Not enough candy liquidity in range @{} $XRD price ", price
+                            );
+                            amount
+                        }
+                    };
+
+                    if amount <= to_boundary {
+                        // Determine relative math formula once identified calling method. 
+                        return match flag {
+                            1 => total_candy*price/(total_candy-amount),         
+                            2 => (total_candy*price + amount*price)/total_candy, 
+                            3 => (total_candy*price - amount*price)/total_candy,
+                            _ => total_candy*price/(total_candy+amount)
+                        };
+                    }
+
+                    amount = amount-to_boundary;
+                    price = boundary.unwrap();
                 }
             }
 
@@ -303,13 +462,12 @@ blueprint! {
                 // Determine relative math formula once identified calling method.
                 match flag {
                     1 => {
-                        let tot_amnt_out = self.candy_vaults.get(&addr_sec).unwrap().amount();
-                        let price_new = price_out*tot_amnt_out/(tot_amnt_out+candy_out_amnt);
+                        let price_new = CandyDex::price_mod(self, candy_out_amnt, addr_sec, price_out, 0);
                         price_new*candy_out_amnt/price_out
                     },
                     2 => {
-                        let tot_amnt_out = self.candy_vaults.get(&addr_pri).unwrap().amount();
-                        tot_amnt_out*candy_out_amnt/(tot_amnt_out-amnt_pri)
+                        let price_new = CandyDex::price_mod(self, amnt_pri, addr_pri, price_in, 1);
+                        price_new*candy_out_amnt/price_in
                     },
                     _ => {
                         let mut price_new: Decimal = 1.into();
@@ -344,6 +502,18 @@ blueprint! {
             info!(" Protocol fee set to {}% ", self.fee);
         }
 
+            // Set minimum position size and price range tick spacing function whom only protocol 
+            // owner can succesfully call. Existing positions are left untouched.
+        #[auth(owner_badge)]
+        pub fn set_position_limits(&mut self, min_position: Decimal, tick_spacing: Decimal) {
+            assert!(min_position >= dec!(0)," Minimum position size can't be negative ");
+            assert!(tick_spacing > dec!(0)," Tick spacing must be a positive value ");
+
+            self.min_position = min_position;
+            self.tick_spacing = tick_spacing;
+            info!(" Minimum position {} candy, tick spacing {} $XRD ", self.min_position, self.tick_spacing);
+        }
+
             // Claim accrued XRD fee function whom only protocol owner can succesfully call.
         #[auth(owner_badge)]
         pub fn claim_xrd_fee(&mut self) -> Bucket {
@@ -360,8 +530,14 @@ blueprint! {
         }
 
             // Stock candy function callable by an end user wishing to supply unpresent liquidity to 
-            // protocol.
-        pub fn stock_candy(&mut self, candy: Bucket, new_price: Decimal) -> (Bucket,Bucket) {
+            // protocol within a price range. A zero upper bound stands for an unbounded range.
+        pub fn stock_candy(
+            &mut self, 
+            candy: Bucket, 
+            new_price: Decimal, 
+            price_low: Decimal, 
+            price_high: Decimal
+        ) -> (Bucket,Bucket) {
             let candy_addr = candy.resource_address();
             let candy_amnt = candy.amount();
             let candy_res_def = candy.resource_def();
@@ -373,6 +549,10 @@ blueprint! {
             // been stocked yet.
             assert!( candy_addr != RADIX_TOKEN," Cannot stock XRD as candy ");
             assert!(new_price > dec!(0), "new price must be a positive value");
+            assert!(
+                new_price >= price_low && (price_high == dec!(0) || new_price <= price_high),
+                " New price must be within position price range "
+            );
             
             if self.candy_map.contains_key(&candy_addr) {
                 info!(" Candy already in Vault. Please use restock_candy function ");
@@ -391,25 +571,26 @@ blueprint! {
             self.candy_map.insert(candy_addr,(name.clone(),symbol.clone(),new_price));
             self.meta_map.insert(candy_addr,(none,none,candy_addr));
             
-            // Mint metaCandy, metaBadge and insert relative hashmaps values. 
+            // Mint metaCandy, position NFT and insert relative hashmaps values. 
             CandyDex::add_meta_candy(self, name.clone(), symbol.clone(), candy_addr);
             
             let meta_candy: Bucket = CandyDex::meta_mint(self, candy_amnt, candy_addr);           
             
             let meta_amount = meta_candy.amount();
-            let meta_badge: Bucket = 
-                CandyDex::add_meta_badge(self, symbol, candy_addr, none, meta_amount);
-            
-            self.badge_map.insert(meta_badge.resource_address().clone(),(none,none,candy_addr));
-            
-            let output_badge: Bucket = CandyDex::meta_amounts(self, meta_badge, meta_amount, none);
+            let position: Bucket = 
+                CandyDex::add_position(self, candy_addr, price_low, price_high, meta_amount);
             
-            (meta_candy,output_badge)
+            (meta_candy,position)
         }
 
             // Restock candy function callable by an end user wishing to supply present liquidity to 
-            // protocol.
-        pub fn restock_candy(&mut self, candy: Bucket) -> (Bucket,Bucket) {
+            // protocol within a price range. A zero upper bound stands for an unbounded range.
+        pub fn restock_candy(
+            &mut self, 
+            candy: Bucket, 
+            price_low: Decimal, 
+            price_high: Decimal
+        ) -> (Bucket,Bucket) {
             let candy_addr = candy.resource_address();            
             assert!( candy_addr != RADIX_TOKEN," Cannot stock XRD as candy ");
             
@@ -433,25 +614,12 @@ blueprint! {
             // Mint relative metaCandy tokens.    
             let meta_candy: Bucket = CandyDex::meta_mint(self, amnt, candy_addr);
 
-            // Retrieve entry fee level.
-            let (accrued_fee,_b,_c) = self.meta_map.get(&candy_addr).unwrap();
-            info!(" entry_fee {} ",accrued_fee);
-            
-            // Mint a metaBadge and update relative hashmaps values.
-            let entry_fee = *accrued_fee;            
-            let (_name,symbol,_price) = self.candy_map.get(&candy_addr).unwrap();
-            let symbol_str = symbol.to_string();
+            // Mint a position NFT and update relative hashmaps values.
             let meta_amount = meta_candy.amount();
-            let meta_badge: Bucket = 
-                CandyDex::add_meta_badge(self, symbol_str, candy_addr, entry_fee, meta_amount);
-            
-            self.badge_map.insert(meta_badge.resource_address()
-                .clone(),(entry_fee,dec!(0),candy_addr));
-            
-            let output_badge: Bucket = 
-                CandyDex::meta_amounts(self, meta_badge, meta_amount, dec!(0));
+            let position: Bucket = 
+                CandyDex::add_position(self, candy_addr, price_low, price_high, meta_amount);
             
-            (meta_candy,output_badge)
+            (meta_candy,position)
         }
 
             // Unstock candy function callable by an end user wishing to withdraw owned candy 
@@ -460,33 +628,37 @@ blueprint! {
             &mut self, 
             candy_addr: Address, 
             meta_candy: Bucket, 
-            meta_badge: Bucket
+            position_nft: Bucket
         ) -> (Bucket,Bucket,Bucket) {
 
             // Verify supplied resources correspondances. 
-            let badge_amnt: Decimal = meta_badge.amount();
-            assert!( badge_amnt >= dec!(1), " Please supply meta badge ");
+            assert!( position_nft.amount() == dec!(1), " Please supply a single position NFT ");
             
-            let (accrued_fee,total_minted,meta_address) = self.meta_map.get(&candy_addr).unwrap();
+            let (_a,_b,meta_address) = self.meta_map.get(&candy_addr).unwrap();
             assert!(meta_address == &meta_candy.resource_address()," Mismatch between Candy & metaCandy! ");
             
-            let (entry_fee,_b,candy_address) = 
-                self.badge_map.get(&meta_badge.resource_address()).unwrap();
-            assert!(candy_address == &candy_addr," MetaBadge address unrecognized! ");
+            let key = position_nft.get_non_fungible_key();
+            let position: Position = 
+                CandyDex::position_get(self, position_nft.resource_def(), candy_addr, &key);
             
             let meta_candy_amnt: Decimal = meta_candy.amount();
+            assert!( meta_candy_amnt <= position.meta_amount," Let's check passed amount ");
+            assert!(
+                meta_candy_amnt == position.meta_amount || position.meta_amount-meta_candy_amnt >= self.min_position,
+                " Remaining position below minimum size of {} candy ", self.min_position
+            );
+            
             let candy_out_nbr: Decimal;
             let candy_bucket: Bucket;
             let xrd_out: Bucket;
-            let delta_fee: Decimal = *accrued_fee-(*entry_fee);
+            let fee_out: Decimal = position.accrued_fee*meta_candy_amnt/position.meta_amount;
             
             // Determine output Candy amount.
-            candy_out_nbr = meta_candy_amnt+delta_fee*meta_candy_amnt/(*total_minted);
+            candy_out_nbr = meta_candy_amnt+fee_out;
             
-            info!(" total_minted {} ",total_minted);
-            info!(" accrued_fee {} ",accrued_fee);
-            info!(" entry_fee {} ",entry_fee);
-            info!(" delta_fee {} ",delta_fee);
+            info!(" position_meta {} ",position.meta_amount);
+            info!(" accrued_fee {} ",position.accrued_fee);
+            info!(" fee_out {} ",fee_out);
             info!(" candy_out_nbr {} ",candy_out_nbr);
             
             // Retrieve Candy reserve amount.
@@ -519,38 +691,40 @@ blueprint! {
             }
 
             // Burn relative metaCandy, verify user's remaining Candy liquidity is greater then zero
-            // otherwise burn metaBadge, update relative hashmaps values. 
-            let meta_candy_amnt: Decimal = meta_candy.amount().clone();
-            
-            CandyDex::meta_burn(self, meta_candy);
+            // otherwise burn position NFT, update relative hashmaps values. 
+            CandyDex::meta_burn(self, meta_candy, candy_addr);
             
-            let output_badge: Bucket = 
-                CandyDex::meta_amounts(self, meta_badge, meta_candy_amnt, dec!(1));
+            let output_position: Bucket = 
+                CandyDex::position_withdraw(self, position_nft, candy_addr, meta_candy_amnt, fee_out);
             
-            (candy_bucket,xrd_out,output_badge)
+            (candy_bucket,xrd_out,output_position)
         }
 
-            // Retrieve liquidity provider position providing a relative metaBadge as reference.
-        pub fn stock_position(&mut self, meta_badge: BucketRef) {
-            let badge_amnt: Decimal = meta_badge.amount();
-            assert!( badge_amnt >= dec!(1), " Please provide your own metaBadge as reference ");
-            
-            match self.badge_map.get(&meta_badge.resource_address()) {
-                Some((a,b,c)) => { 
-                    let (accrued_fee,total_minted,_meta_address) = self.meta_map.get(&c).unwrap();
-                    let delta_fee: Decimal = *accrued_fee-(*a);
-                    let candy_out_nbr = *b+delta_fee*(*b)/(*total_minted);
-                    info!(" accrued_fee {} ",accrued_fee);
-                    info!(" total_minted {} ",total_minted);
-                    info!(" entry_fee {} ",a);
-                    info!(" delta_fee {} ",delta_fee);
-                    info!(" candy_out_nbr {} ",candy_out_nbr);
-                },
-                None => {
-                    info!(" No badge's correspondence! ");
-                    std::process::abort()
-                }
-            }
+            // Retrieve liquidity provider position providing a relative position NFT as reference
+            // and refresh its metaCandy amount and accrued fee data.
+        pub fn stock_position(&mut self, position_nft: BucketRef) {
+            assert!( position_nft.amount() == dec!(1), " Please provide your own position NFT as reference ");
+            
+            assert!(position_nft.resource_def() == self.position_def," Please supply a candy position NFT ");
+            
+            let key = position_nft.get_non_fungible_key();
+            let data: PositionData = self.position_def.get_non_fungible_data(&key);
+            let position: Position = 
+                CandyDex::position_get(self, position_nft.resource_def(), data.candy_address, &key);
+            let price = CandyDex::candyprice(self, position.candy_address);
+            let candy_out_nbr = position.meta_amount+position.accrued_fee;
+            
+            info!(" candy_address {} ",position.candy_address);
+            info!(" price_low {} ",position.price_low);
+            info!(" price_high {} ",position.price_high);
+            info!(" in_range {} @{} $XRD price ",position.in_range(price),price);
+            info!(" meta_amount {} ",position.meta_amount);
+            info!(" accrued_fee {} ",position.accrued_fee);
+            info!(" candy_out_nbr {} ",candy_out_nbr);
+            
+            self.minter_badge.authorize(|auth| {
+                self.position_def.update_non_fungible_data(&key, position.to_data(), auth)
+            });
         }
 
             // Get price, name, symbol of a determinated candy giving his resource address.
@@ -910,3 +1084,80 @@ impl MetaToken {
         }
     }
 }
+
+// Build a structure and implement it to track a liquidity provider position within a chosen 
+// candy price range, along with its metaCandy amount and accrued fee.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct Position {
+    candy_address: Address,
+    price_low: Decimal,
+    price_high: Decimal,
+    meta_amount: Decimal,
+    accrued_fee: Decimal,
+}
+
+impl Position {
+    pub fn new(
+        candy_address: Address,
+        price_low: Decimal,
+        price_high: Decimal,
+        meta_amount: Decimal,
+    ) -> Self {
+        Self {
+            candy_address,
+            price_low,
+            price_high,
+            meta_amount,
+            accrued_fee: dec!(0),
+        }
+    }
+
+    // Verify position price range includes a candy price. A zero upper bound stands for an 
+    // unbounded range.
+    pub fn in_range(&self, price: Decimal) -> bool {
+        price >= self.price_low && (self.price_high == dec!(0) || price <= self.price_high)
+    }
+
+    // Whether position provides liquidity while price moves up or down from given price, so
+    // that a position sitting at its own boundary doesn't back a move out of its range.
+    pub fn active(&self, price: Decimal, up: bool) -> bool {
+        if up {
+            price >= self.price_low && (self.price_high == dec!(0) || price < self.price_high)
+        } else {
+            price > self.price_low && (self.price_high == dec!(0) || price <= self.price_high)
+        }
+    }
+
+    // Liquidity amplification factor of position price range, equal to one for unbounded
+    // ranges and growing as range narrows.
+    pub fn amplification(&self) -> Decimal {
+        if self.price_high == dec!(0) {
+            dec!(1)
+        } else {
+            self.price_high/(self.price_high-self.price_low)
+        }
+    }
+
+    pub fn to_data(&self) -> PositionData {
+        PositionData {
+            candy_address: self.candy_address,
+            price_low: self.price_low,
+            price_high: self.price_high,
+            meta_amount: self.meta_amount,
+            accrued_fee: self.accrued_fee,
+        }
+    }
+}
+
+// Position NFT data. Price range is immutable while metaCandy amount and accrued fee are 
+// refreshed whenever position owner calls "unstock_candy" or "stock_position" functions.
+#[derive(NonFungibleData)]
+pub struct PositionData {
+    candy_address: Address,
+    price_low: Decimal,
+    price_high: Decimal,
+    #[scrypto(mutable)]
+    meta_amount: Decimal,
+    #[scrypto(mutable)]
+    accrued_fee: Decimal,
+}
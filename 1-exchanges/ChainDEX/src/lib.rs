@@ -17,6 +17,7 @@ use scrypto::prelude::*;
 #[derive(NonFungibleData)]
 pub struct Order {
     is_buy: bool,           // is b for a
+    #[scrypto(mutable)]
     price: Decimal,         // a per b
     #[scrypto(mutable)]
     amount: Decimal,        // a to buy/sell
@@ -65,6 +66,175 @@ blueprint! {
             .instantiate()
         }
 
+        // fills orders of the opposite chain with a better price than asking
+        // returns filled tokens, remaining tokens are left in the given bucket
+        fn match_order(&mut self, tokens: &mut Bucket, price: Decimal, is_buy: bool) -> Bucket {
+            if is_buy { // b for a (buy)
+                let mut filled: Bucket = Bucket::new(self.a_pool.resource_def());
+                let mut sell_pointer: NonFungibleKey = self.sell_head.clone();
+
+                // market buy till price too high
+                while tokens.amount() != 0.into() && sell_pointer != 0.into() && price >= self.order_def.get_non_fungible_data::<Order>(&sell_pointer).price {
+                    let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&sell_pointer);
+                    
+                    // min(can_buy, in_order)
+                    let to_fill: Decimal = if tokens.amount() / data.price >= data.amount {
+                        self.sell_head = data.next.clone();
+                        data.amount
+                    } else {
+                        tokens.amount() / data.price
+                    };
+
+                    // update state
+                    self.b_pool.put(tokens.take(to_fill * data.price));
+                    data.amount -= to_fill;
+                    data.filled += to_fill;
+                    filled.put(self.a_pool.take(to_fill));
+                    
+                    self.admin_badge.authorize(|auth| {
+                        self.order_def.update_non_fungible_data(&sell_pointer, data, auth);
+                    });
+
+                    // move to next order
+                    sell_pointer = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
+                }
+                filled
+            } else { // a for b (sell)
+                let mut filled: Bucket = Bucket::new(self.b_pool.resource_def());
+                let mut buy_pointer: NonFungibleKey = self.buy_head.clone();
+
+                // market sell till price too low
+                while tokens.amount() != 0.into() && buy_pointer != 0.into() && price <= self.order_def.get_non_fungible_data::<Order>(&buy_pointer).price {
+                    let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&buy_pointer);
+                    
+                    // min(can_sell, in_order)
+                    let to_fill: Decimal = if tokens.amount() >= data.amount {
+                        self.buy_head = data.next.clone();
+                        data.amount
+                    } else {
+                        tokens.amount()
+                    };
+
+                    // update state
+                    self.a_pool.put(tokens.take(to_fill));
+                    data.amount -= to_fill;
+                    data.filled += to_fill;
+                    filled.put(self.b_pool.take(to_fill * data.price));
+
+                    self.admin_badge.authorize(|auth| {
+                        self.order_def.update_non_fungible_data(&buy_pointer, data, auth);
+                    });
+                    
+                    // move to next order
+                    buy_pointer = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
+                }
+                filled
+            }
+        }
+
+        // links order in buy/sell chain after the orders with a better or equal price
+        // returns key of next order in chain
+        fn insert_order(&mut self, order_key: &NonFungibleKey, price: Decimal, is_buy: bool) -> NonFungibleKey {
+            if is_buy {
+                if self.buy_head == 0.into() {
+                    // if no other orders, point head to new order
+                    self.buy_head = order_key.clone();
+                    0.into()
+                } else if price > self.order_def.get_non_fungible_data::<Order>(&self.buy_head).price {
+                    // if best price put at front and point head to new order
+                    let buy_pointer: NonFungibleKey = self.buy_head.clone();
+                    self.buy_head = order_key.clone();
+                    buy_pointer
+                } else {
+                    let mut buy_pointer: NonFungibleKey = self.buy_head.clone();
+                    let mut next : NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
+
+                    // find orders to place new order between
+                    while next != 0.into() && price <= self.order_def.get_non_fungible_data::<Order>(&next).price {
+                        buy_pointer = next;
+                        next = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
+                    }
+
+                    // update prev order to point to new order
+                    let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&buy_pointer);
+                    data.next = order_key.clone();
+
+                    self.admin_badge.authorize(|auth| {
+                        self.order_def.update_non_fungible_data(&buy_pointer, data, auth);
+                    });
+
+                    next
+                }
+            } else {
+                if self.sell_head == 0.into() {
+                    // if no other orders, point head to new order
+                    self.sell_head = order_key.clone();
+                    0.into()
+                } else if price < self.order_def.get_non_fungible_data::<Order>(&self.sell_head).price {
+                    // if best price put at front and point head to new order
+                    let sell_pointer: NonFungibleKey = self.sell_head.clone();
+                    self.sell_head = order_key.clone();
+                    sell_pointer
+                } else {
+                    let mut sell_pointer: NonFungibleKey = self.sell_head.clone();
+                    let mut next : NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
+
+                    // find orders to place new order between
+                    while next != 0.into() && price >= self.order_def.get_non_fungible_data::<Order>(&next).price {
+                        sell_pointer = next;
+                        next = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
+                    }
+
+                    // update prev order to point to new order
+                    let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&sell_pointer);
+                    let next: NonFungibleKey = data.next.clone();
+                    data.next = order_key.clone();
+                    
+                    self.admin_badge.authorize(|auth| {
+                        self.order_def.update_non_fungible_data(&sell_pointer, data, auth);
+                    });
+
+                    next
+                }
+            }
+        }
+
+        // unlinks order from buy/sell chain
+        fn remove_order(&mut self, order_key: &NonFungibleKey, data: &Order) {
+            let head: NonFungibleKey = if data.is_buy {
+                self.buy_head.clone()
+            } else {
+                self.sell_head.clone()
+            };
+
+            // if first in chain point head to next order
+            if head == *order_key {
+                if data.is_buy {
+                    self.buy_head = data.next.clone();
+                } else {
+                    self.sell_head = data.next.clone();
+                }
+                return;
+            }
+
+            let mut curr: NonFungibleKey = head;
+            let mut next: NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&curr).next;
+
+            // find order that points to order to remove
+            while next != *order_key {
+                curr = next;
+                next = self.order_def.get_non_fungible_data::<Order>(&curr).next;
+            }
+
+            // update order that points to order to remove
+            let mut curr_data: Order = self.order_def.get_non_fungible_data::<Order>(&curr);
+            curr_data.next = data.next.clone();
+
+            self.admin_badge.authorize(|auth| {
+                self.order_def.update_non_fungible_data(&curr, curr_data, auth);
+            });
+        }
+
         // fills orders with a better price than asking then creates a limit order with the remaining tokens
         // returns (tokens, ?order)
         pub fn create_order(&mut self, mut tokens: Bucket, price: Decimal) -> (Bucket, Bucket) {
@@ -72,185 +242,164 @@ blueprint! {
                 tokens.resource_def() == self.a_pool.resource_def() || tokens.resource_def() == self.b_pool.resource_def(),
                 "Tokens not in this pair"
             );
+            assert!(price > 0.into(), "Price must be positive");
 
             self.count += 1;
             let order_key: NonFungibleKey = self.count.into();
             let is_buy: bool = tokens.resource_def() == self.b_pool.resource_def();
-            
-            if is_buy { // b for a (buy)
-                let mut filled: Bucket = Bucket::new(self.a_pool.resource_def());
-                // fill colisions (market buy)
-                {
-                    let mut sell_pointer: NonFungibleKey = self.sell_head.clone();
 
-                    // market buy till price too high
-                    while tokens.amount() != 0.into() && sell_pointer != 0.into() && price >= self.order_def.get_non_fungible_data::<Order>(&sell_pointer).price {
-                        let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&sell_pointer);
-                        
-                        // min(can_buy, in_order)
-                        let to_fill: Decimal = if tokens.amount() / data.price >= data.amount {
-                            self.sell_head = data.next.clone();
-                            data.amount
-                        } else {
-                            tokens.amount() / data.price
-                        };
-
-                        // update state
-                        self.b_pool.put(tokens.take(to_fill * data.price));
-                        data.amount -= to_fill;
-                        data.filled += to_fill;
-                        filled.put(self.a_pool.take(to_fill));
-                        
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.update_non_fungible_data(&sell_pointer, data, auth);
-                        });
-
-                        // move to next order
-                        sell_pointer = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
-                    }
-                }
+            // fill colisions (market buy/sell)
+            let filled: Bucket = self.match_order(&mut tokens, price, is_buy);
 
-                // make order (limit buy)
+            // make order (limit buy/sell)
+            let to_order: Decimal = if is_buy {
                 let to_order: Decimal = tokens.amount() / price;
                 self.b_pool.put(tokens);
+                to_order
+            } else {
+                let to_order: Decimal = tokens.amount();
+                self.a_pool.put(tokens);
+                to_order
+            };
 
-                if to_order > 0.into() {
-                    let next: NonFungibleKey = if self.buy_head == 0.into() {
-                        // if no other orders, point head to new order
-                        self.buy_head = order_key.clone();
-                        0.into()
-                    } else if price > self.order_def.get_non_fungible_data::<Order>(&self.buy_head).price {
-                        // if best price put at front and point head to new order
-                        let buy_pointer: NonFungibleKey = self.buy_head.clone();
-                        self.buy_head = order_key.clone();
-                        buy_pointer
-                    } else {
-                        let mut buy_pointer: NonFungibleKey = self.buy_head.clone();
-                        let mut next : NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
+            if to_order > 0.into() {
+                let next: NonFungibleKey = self.insert_order(&order_key, price, is_buy);
+                // return (filled, order)
+                (
+                    filled,
+                    self.admin_badge.authorize(|auth| {
+                        self.order_def.mint_non_fungible(&order_key,
+                            Order {
+                                is_buy: is_buy,
+                                price: price,
+                                amount: to_order,
+                                filled: 0.into(),
+                                next: next,
+                            },
+                            auth)
+                    })
+                )
+            } else {
+                // return (filled, empty bucket)
+                (filled, Bucket::new(self.order_def.clone()))
+            }
+        }
 
-                        // find orders to place new order between
-                        while next != 0.into() && price <= self.order_def.get_non_fungible_data::<Order>(&next).price {
-                            buy_pointer = next;
-                            next = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
-                        }
+        // walks buy/sell chain to its last order
+        // returns price of last order (worst price of chain), None if chain is empty
+        fn last_price_in_chain(&self, is_buy: bool) -> Option<Decimal> {
+            let mut pointer: NonFungibleKey = if is_buy {
+                self.buy_head.clone()
+            } else {
+                self.sell_head.clone()
+            };
+            let mut price: Option<Decimal> = None;
+
+            while pointer != 0.into() {
+                let data: Order = self.order_def.get_non_fungible_data::<Order>(&pointer);
+                price = Some(data.price);
+                pointer = data.next;
+            }
+            price
+        }
 
-                        // update prev order to point to new order
-                        let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&buy_pointer);
-                        data.next = order_key.clone();
+        // fills orders of the opposite chain whatever their price, no limit order is created
+        // returns (filled_tokens, remaining_tokens)
+        pub fn market_order(&mut self, mut tokens: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                tokens.resource_def() == self.a_pool.resource_def() || tokens.resource_def() == self.b_pool.resource_def(),
+                "Tokens not in this pair"
+            );
 
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.update_non_fungible_data(&buy_pointer, data, auth);
-                        });
+            let is_buy: bool = tokens.resource_def() == self.b_pool.resource_def();
 
-                        next
-                    };
-                    // return (filled, order)
-                    (
-                        filled,
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.mint_non_fungible(&order_key,
-                                Order {
-                                    is_buy: is_buy,
-                                    price: price,
-                                    amount: to_order,
-                                    filled: 0.into(),
-                                    next: next,
-                                },
-                                auth)
-                        })
-                    )
+            // worst price of opposite chain lets every order of it match
+            let filled: Bucket = match self.last_price_in_chain(!is_buy) {
+                Some(price) => self.match_order(&mut tokens, price, is_buy),
+                None => if is_buy {
+                    Bucket::new(self.a_pool.resource_def())
                 } else {
-                    // return (filled, empty bucket)
-                    (filled, Bucket::new(self.order_def.clone()))
+                    Bucket::new(self.b_pool.resource_def())
                 }
-            } else { // a for b (sell)
-                let mut filled: Bucket = Bucket::new(self.b_pool.resource_def());
-                // fill colisions (market sell)
-                {
-                    let mut buy_pointer: NonFungibleKey = self.buy_head.clone();
+            };
 
-                    // market sell till price too low
-                    while tokens.amount() != 0.into() && buy_pointer != 0.into() && price <= self.order_def.get_non_fungible_data::<Order>(&buy_pointer).price {
-                        let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&buy_pointer);
-                        
-                        // min(can_sell, in_order)
-                        let to_fill: Decimal = if tokens.amount() >= data.amount {
-                            self.buy_head = data.next.clone();
-                            data.amount
-                        } else {
-                            tokens.amount()
-                        };
-
-                        // update state
-                        self.a_pool.put(tokens.take(to_fill));
-                        data.amount -= to_fill;
-                        data.filled += to_fill;
-                        filled.put(self.b_pool.take(to_fill * data.price));
-
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.update_non_fungible_data(&buy_pointer, data, auth);
-                        });
-                        
-                        // move to next order
-                        buy_pointer = self.order_def.get_non_fungible_data::<Order>(&buy_pointer).next;
-                    }
-                }
+            (filled, tokens)
+        }
 
-                // make order (limit sell)
-                let to_order: Decimal = tokens.amount();
-                self.a_pool.put(tokens);
+        // atomically changes price and amount of order keeping the same order NFT
+        // claims filled part of order, refunds the remaining part and uses it with the given tokens to fill orders
+        //  with a better price than asking then relinks order with the remaining amount
+        // burns order if completed by the amend
+        // returns (?order, filled_tokens, refunded_tokens)
+        pub fn amend_order(&mut self, order: Bucket, price: Decimal, amount: Decimal, mut tokens: Bucket) -> (Bucket, Bucket, Bucket) {
+            assert!(
+                order.resource_def() == self.order_def,
+                "Order not for this pair"
+            );
+            assert!(amount > 0.into(), "Amount must be positive");
+            assert!(price > 0.into(), "Price must be positive");
 
-                if to_order > 0.into() {
-                    let next: NonFungibleKey = if self.sell_head == 0.into() {
-                        // if no other orders, point head to new order
-                        self.sell_head = order_key.clone();
-                        0.into()
-                    } else if price < self.order_def.get_non_fungible_data::<Order>(&self.sell_head).price {
-                        // if best price put at front and point head to new order
-                        let sell_pointer: NonFungibleKey = self.sell_head.clone();
-                        self.sell_head = order_key.clone();
-                        sell_pointer
-                    } else {
-                        let mut sell_pointer: NonFungibleKey = self.sell_head.clone();
-                        let mut next : NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
+            let order_key: NonFungibleKey = order.get_non_fungible_key();
+            let data: Order = self.order_def.get_non_fungible_data::<Order>(&order_key);
+            assert!(data.amount > 0.into(), "Order already completed");
+            let is_buy: bool = data.is_buy;
 
-                        // find orders to place new order between
-                        while next != 0.into() && price >= self.order_def.get_non_fungible_data::<Order>(&next).price {
-                            sell_pointer = next;
-                            next = self.order_def.get_non_fungible_data::<Order>(&sell_pointer).next;
-                        }
+            // claim filled part of order if any
+            let (mut filled_tokens, mut order): (Bucket, Bucket) = self.claim_tokens(order);
 
-                        // update prev order to point to new order
-                        let mut data: Order = self.order_def.get_non_fungible_data::<Order>(&sell_pointer);
-                        let next: NonFungibleKey = data.next.clone();
-                        data.next = order_key.clone();
-                        
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.update_non_fungible_data(&sell_pointer, data, auth);
-                        });
+            // remove order from order chain and get remaining part of order
+            self.remove_order(&order_key, &data);
+            let mut order_tokens: Bucket = if is_buy {
+                self.b_pool.take(data.amount * data.price)
+            } else {
+                self.a_pool.take(data.amount)
+            };
+            assert!(
+                tokens.resource_def() == order_tokens.resource_def(),
+                "Tokens not for this order"
+            );
+            order_tokens.put(tokens.take(tokens.amount()));
 
-                        next
-                    };
-                    // return (filled, order)
-                    (   
-                        filled,
-                        self.admin_badge.authorize(|auth| {
-                            self.order_def.mint_non_fungible(&order_key,
-                                Order {
-                                    is_buy: is_buy,
-                                    price: price,
-                                    amount: to_order,
-                                    filled: 0.into(),
-                                    next: next,
-                                },
-                                auth)
-                        })
-                    )
-                } else {
-                    // return (filled, empty bucket)
-                    (filled, Bucket::new(self.order_def.clone()))
-                }
+            // take tokens needed by amended order, the rest is refunded
+            let needed: Decimal = if is_buy { amount * price } else { amount };
+            assert!(order_tokens.amount() >= needed, "Not enough tokens to amend order");
+            let mut tokens_to_order: Bucket = order_tokens.take(needed);
+
+            // fill colisions (market buy/sell)
+            filled_tokens.put(self.match_order(&mut tokens_to_order, price, is_buy));
+
+            // relink order with remaining amount (limit buy/sell)
+            let to_order: Decimal = if is_buy {
+                let to_order: Decimal = tokens_to_order.amount() / price;
+                self.b_pool.put(tokens_to_order);
+                to_order
+            } else {
+                let to_order: Decimal = tokens_to_order.amount();
+                self.a_pool.put(tokens_to_order);
+                to_order
+            };
+
+            if to_order > 0.into() {
+                let next: NonFungibleKey = self.insert_order(&order_key, price, is_buy);
+                self.admin_badge.authorize(|auth| {
+                    self.order_def.update_non_fungible_data(&order_key,
+                        Order {
+                            is_buy: is_buy,
+                            price: price,
+                            amount: to_order,
+                            filled: 0.into(),
+                            next: next,
+                        },
+                        auth);
+                });
+            } else {
+                // burn order if completed
+                self.admin_badge.authorize(|auth| {
+                    order.take_non_fungible(&order_key).burn_with_auth(auth);
+                });
             }
+
+            (order, filled_tokens, order_tokens)
         }
 
         // gives tokens for filled part of order, burns order if completed else returns updated order
@@ -314,27 +463,8 @@ blueprint! {
             
             // remove order from order chain and get remaining part of order if any
             let remaining_tokens: Bucket = if data.amount > 0.into() {
-                let mut curr: NonFungibleKey = if data.is_buy {
-                    self.buy_head.clone()
-                } else {
-                    self.sell_head.clone()
-                };
-                let mut next: NonFungibleKey = self.order_def.get_non_fungible_data::<Order>(&curr).next;
+                self.remove_order(&order_key, &data);
 
-                // find order that points to order to cancel
-                while next != order_key {
-                    curr = next;
-                    next = self.order_def.get_non_fungible_data::<Order>(&curr).next;
-                }
-
-                // update order that points to order to cancel
-                let mut curr_data: Order = self.order_def.get_non_fungible_data::<Order>(&curr);
-                curr_data.next = data.next;
-
-                self.admin_badge.authorize(|auth| {
-                    self.order_def.update_non_fungible_data(&curr, curr_data, auth);
-                });
-                
                 // return remaining tokens
                 if data.is_buy {
                     self.b_pool.take(data.amount * data.price)
@@ -357,5 +487,54 @@ blueprint! {
 
             (remaining_tokens, filled_tokens)
         }
+
+        // aggregates orders of buy/sell chain by price, starting from the best one
+        // returns (price, amount, order count) of the best price levels
+        fn chain_depth(&self, is_buy: bool, levels: usize) -> Vec<(Decimal, Decimal, u32)> {
+            let mut depth: Vec<(Decimal, Decimal, u32)> = Vec::new();
+            let mut pointer: NonFungibleKey = if is_buy {
+                self.buy_head.clone()
+            } else {
+                self.sell_head.clone()
+            };
+
+            while pointer != 0.into() {
+                let data: Order = self.order_def.get_non_fungible_data::<Order>(&pointer);
+
+                match depth.last_mut() {
+                    Some(level) if level.0 == data.price => {
+                        level.1 += data.amount;
+                        level.2 += 1;
+                    }
+                    _ => {
+                        if depth.len() == levels {
+                            break;
+                        }
+                        depth.push((data.price, data.amount, 1));
+                    }
+                }
+                pointer = data.next;
+            }
+            depth
+        }
+
+        // returns (best buy price, best sell price), None if chain is empty
+        pub fn get_best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            let best = |head: &NonFungibleKey| -> Option<Decimal> {
+                if *head == 0.into() {
+                    None
+                } else {
+                    Some(self.order_def.get_non_fungible_data::<Order>(head).price)
+                }
+            };
+
+            (best(&self.buy_head), best(&self.sell_head))
+        }
+
+        // returns (buy levels, sell levels) of the given number of best prices
+        // each level is (price, amount of a, order count)
+        pub fn get_depth(&self, levels: u32) -> (Vec<(Decimal, Decimal, u32)>, Vec<(Decimal, Decimal, u32)>) {
+            (self.chain_depth(true, levels as usize), self.chain_depth(false, levels as usize))
+        }
     }
 }
@@ -54,5 +54,8 @@ resim call-method $chain_book create_order 150000,$tether 40000
 # cancel order
 resim call-method $chain_book cancel_order `#00000000000000000000000000000003,$order
 
+# amend order
+resim call-method $chain_book amend_order `#0000000000000000000000000000000e,$order 33500 0.2 0,$tether
+
 resim show $chain_book
 resim show $account
\ No newline at end of file
@@ -88,6 +88,8 @@ resim run "$SCRIPT_DIR/transactions/swap_BTC_for_USDT.rtm"
 resim set-default-account $ACC_ADDRESS3 $PUB_KEY3 $PRIV_KEY3
 sed "$REPLACEMENT_LOOKUP" $SCRIPT_DIR/raw_transactions/swap_ADA_for_DOGE.rtm > $SCRIPT_DIR/transactions/swap_ADA_for_DOGE.rtm
 resim run "$SCRIPT_DIR/transactions/swap_ADA_for_DOGE.rtm"
+sed "$REPLACEMENT_LOOKUP" $SCRIPT_DIR/raw_transactions/swap_ADA_for_DOGE_routed.rtm > $SCRIPT_DIR/transactions/swap_ADA_for_DOGE_routed.rtm
+resim run "$SCRIPT_DIR/transactions/swap_ADA_for_DOGE_routed.rtm"
 
 resim set-default-account $ACC_ADDRESS4 $PUB_KEY4 $PRIV_KEY4
 sed "$REPLACEMENT_LOOKUP" $SCRIPT_DIR/raw_transactions/swap_BTC_for_USDT_and_add_liquidity.rtm > $SCRIPT_DIR/transactions/swap_BTC_for_USDT_and_add_liquidity.rtm
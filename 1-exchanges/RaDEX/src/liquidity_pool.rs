@@ -1,6 +1,41 @@
 use scrypto::prelude::*;
 use crate::utils::*;
 
+/// The maximum number of price observations that a liquidity pool keeps in its ring buffer. Once this number is 
+/// reached, each new observation overwrites the oldest one.
+const MAX_PRICE_OBSERVATIONS: usize = 64;
+
+/// A snapshot of the cumulative prices of a liquidity pool taken at a given epoch. The time-weighted average price over
+/// some window is computed from the difference between two of these snapshots.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct PriceObservation {
+    /// The epoch at which this observation was recorded.
+    pub epoch: u64,
+
+    /// The cumulative prices of the two tokens at the time of the observation. The key is the resource address of the
+    /// token and the value is the cumulative price of this token in terms of the other token.
+    pub price_cumulative: HashMap<ResourceAddress, Decimal>,
+}
+
+/// The data of the transient receipt NFT which is given out when a flash swap is made. This NFT can not be deposited 
+/// into any vault; therefore, the only way to get rid of it (and for the transaction to succeed) is to give it back to
+/// the liquidity pool along with the repayment of the flash swap.
+#[derive(NonFungibleData)]
+pub struct FlashSwapReceipt {
+    /// The resource address of the tokens which were taken out of the pool in the flash swap.
+    pub resource_address: ResourceAddress,
+
+    /// The amount of tokens which were taken out of the pool in the flash swap.
+    pub amount: Decimal,
+
+    /// The fee owed on the flash swap, in the tokens which were taken out of the pool.
+    pub fee: Decimal,
+
+    /// The total amount owed if the flash swap is repaid in the tokens which were taken out of the pool. This is the 
+    /// amount taken out plus the fee.
+    pub amount_due: Decimal,
+}
+
 blueprint!{
     /// This is a struct used to define a liquidity pool in the RaDEX decentralized exchange. A typical liquidity pool 
     /// is made up of two vaults which are used to store the two tokens being traded against one another. In addition to 
@@ -27,8 +62,46 @@ blueprint!{
         tracking_token_admin_badge: Vault,
 
         /// This is a decimal value between 0 and 100 which defines the amount of fees paid to the liquidity pool (and
-        /// in turn the liquidity providers) when a swap is made through this liquidity pool.
+        /// in turn the liquidity providers) when a swap is made through this liquidity pool. When the protocol fee is
+        /// switched on, a share of this fee is paid to the protocol instead of the liquidity providers.
         fee_to_pool: Decimal,
+
+        /// This is a decimal value between 0 and 100 which defines the percentage of the swap fee that is paid to the
+        /// protocol instead of the liquidity providers. A value of zero means that the protocol fee is switched off.
+        protocol_fee_share: Decimal,
+
+        /// These are the vaults where the protocol's share of the swap fees is stored until it is withdrawn by the 
+        /// RaDEX component. The tokens in these vaults are not a part of the reserves of the liquidity pool and they
+        /// do not affect the price or the k value of the pool.
+        protocol_fee_vaults: HashMap<ResourceAddress, Vault>,
+
+        /// These are the cumulative prices of the two tokens in the liquidity pool, in the same spirit as the price 
+        /// accumulators of Uniswap V2. The key is the resource address of the token and the value is the sum of the 
+        /// price of this token (in terms of the other token) multiplied by the number of epochs that this price has 
+        /// held for. These accumulators are updated before every swap and every addition or removal of liquidity.
+        price_cumulative: HashMap<ResourceAddress, Decimal>,
+
+        /// The epoch at which the price accumulators were last updated.
+        last_update_epoch: u64,
+
+        /// A ring buffer of the snapshots of the price accumulators. At most one observation is recorded per epoch and
+        /// at most `MAX_PRICE_OBSERVATIONS` are kept.
+        price_observations: Vec<PriceObservation>,
+
+        /// The index in the ring buffer where the next observation is recorded. Once the buffer is full, this is also
+        /// the index of the oldest observation.
+        next_price_observation: usize,
+
+        /// The badge which has the authority to mint and burn the flash swap receipts.
+        flash_swap_admin_badge: Vault,
+
+        /// The resource address of the transient flash swap receipt NFTs issued by this pool.
+        flash_swap_receipt_address: ResourceAddress,
+
+        /// The k value of the pool before the currently outstanding flash swap was made. This is `None` when there is
+        /// no outstanding flash swap. While a flash swap is outstanding, all of the other methods which change the 
+        /// reserves of the pool are locked.
+        flash_swap_k_before: Option<Decimal>,
     }
 
     impl LiquidityPool {
@@ -44,6 +117,7 @@ blueprint!{
         /// * **Check 2:** Checks that both `token1` and `token2` are fungible tokens.
         /// * **Check 3:** Checks that neither of the buckets are empty.
         /// * **Check 4:** Checks that the fee is between 0 and 100.
+        /// * **Check 5:** Checks that the protocol fee share is between 0 and 100.
         /// 
         /// If these checks are successful, then a new liquidity pool is created from the two buckets passed to this 
         /// function and tracking tokens are minted for the creator of this liquidity pool. Keep in mind that this 
@@ -57,6 +131,10 @@ blueprint!{
         /// * `token2` (Bucket) - A bucket containing the amount of the second token used to initialize the pool.
         /// * `fee` (Decimal) - A decimal value of the fee imposed on all swaps from this liquidity pool. This should be
         /// a value between 0 and 100.
+        /// * `protocol_fee_share` (Decimal) - A decimal value of the percentage of the swap fee that is paid to the 
+        /// protocol. This should be a value between 0 and 100.
+        /// * `pool_admin_badge` (ResourceAddress) - The resource address of the badge which is allowed to change the
+        /// protocol fee share and to withdraw the protocol fees from this pool.
         /// 
         /// # Returns:
         /// 
@@ -65,7 +143,9 @@ blueprint!{
         pub fn new(
             token1: Bucket,
             token2: Bucket,
-            fee_to_pool: Decimal
+            fee_to_pool: Decimal,
+            protocol_fee_share: Decimal,
+            pool_admin_badge: ResourceAddress
         ) -> (ComponentAddress, Bucket) {
             // Performing the checks to see if this liquidity pool may be created or not.
             assert_ne!(
@@ -92,13 +172,18 @@ blueprint!{
                 "[Pool Creation]: Fee must be between 0 and 100"
             );
 
+            assert!(
+                (protocol_fee_share >= Decimal::zero()) & (protocol_fee_share <= dec!("100")), 
+                "[Pool Creation]: Protocol fee share must be between 0 and 100"
+            );
+
             // At this point, we know that the pool creation can indeed go through. 
             
             // Sorting the buckets and then creating the hashmap of the vaults from the sorted buckets
             let (bucket1, bucket2): (Bucket, Bucket) = sort_buckets(token1, token2);
             let addresses: (ResourceAddress, ResourceAddress) = (bucket1.resource_address(), bucket2.resource_address());
             
-            let lp_id: String = format!("{}-{}", addresses.0, addresses.1);
+            let lp_id: String = format!("{}-{}-{}", addresses.0, addresses.1, fee_to_pool);
             let pair_name: String = address_pair_symbol(addresses.0, addresses.1);
 
             info!(
@@ -106,6 +191,16 @@ blueprint!{
                 lp_id, pair_name, bucket1.amount(), bucket2.amount()
             );
             
+            // The price accumulators of both tokens start at zero from the epoch in which the pool is created.
+            let mut price_cumulative: HashMap<ResourceAddress, Decimal> = HashMap::new();
+            price_cumulative.insert(addresses.0, Decimal::zero());
+            price_cumulative.insert(addresses.1, Decimal::zero());
+            let current_epoch: u64 = Runtime::current_epoch();
+
+            let mut protocol_fee_vaults: HashMap<ResourceAddress, Vault> = HashMap::new();
+            protocol_fee_vaults.insert(addresses.0, Vault::new(addresses.0));
+            protocol_fee_vaults.insert(addresses.1, Vault::new(addresses.1));
+
             let mut vaults: HashMap<ResourceAddress, Vault> = HashMap::new();
             vaults.insert(bucket1.resource_address(), Vault::with_bucket(bucket1));
             vaults.insert(bucket2.resource_address(), Vault::with_bucket(bucket2));
@@ -123,7 +218,7 @@ blueprint!{
             // Creating the tracking tokens and minting the amount owed to the initial liquidity provider
             let tracking_tokens: Bucket = ResourceBuilder::new_fungible()
                 .divisibility(DIVISIBILITY_MAXIMUM)
-                .metadata("name", format!("{} LP Tracking Token", pair_name))
+                .metadata("name", format!("{} {}% LP Tracking Token", pair_name, fee_to_pool))
                 .metadata("symbol", "TT")
                 .metadata("description", "A tracking token used to track the percentage ownership of liquidity providers over the liquidity pool")
                 .metadata("lp_id", format!("{}", lp_id))
@@ -131,14 +226,54 @@ blueprint!{
                 .burnable(rule!(require(tracking_token_admin_badge.resource_address())), LOCKED)
                 .initial_supply(100);
 
+            // Creating the badge which has the authority to mint and burn the flash swap receipts and the transient 
+            // receipt resource itself. The receipts can never be deposited, so they must be burned within the same 
+            // transaction which they were minted in.
+            let flash_swap_admin_badge: Bucket = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", "Flash Swap Admin Badge")
+                .metadata("symbol", "FSAB")
+                .metadata("description", "This is an admin badge that has the authority to mint and burn flash swap receipts")
+                .metadata("lp_id", format!("{}", lp_id))
+                .initial_supply(1);
+
+            let flash_swap_receipt_address: ResourceAddress = ResourceBuilder::new_non_fungible()
+                .metadata("name", format!("{} Flash Swap Receipt", pair_name))
+                .metadata("description", "A transient receipt of a flash swap which must be returned with the repayment to be burned")
+                .metadata("lp_id", format!("{}", lp_id))
+                .mintable(rule!(require(flash_swap_admin_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(flash_swap_admin_badge.resource_address())), LOCKED)
+                .restrict_deposit(rule!(deny_all), LOCKED)
+                .no_initial_supply();
+
+            // Only the pool admin badge (held by the RaDEX component) may change the protocol fee share and withdraw
+            // the protocol fees. All other methods of the liquidity pool may be called by anybody.
+            let access_rules: AccessRules = AccessRules::new()
+                .method("set_protocol_fee_share", rule!(require(pool_admin_badge)))
+                .method("withdraw_protocol_fees", rule!(require(pool_admin_badge)))
+                .default(rule!(allow_all));
+
             // Creating the liquidity pool component and instantiating it
             let liquidity_pool: ComponentAddress = Self { 
                 vaults: vaults,
                 tracking_token_address: tracking_tokens.resource_address(),
                 tracking_token_admin_badge: Vault::with_bucket(tracking_token_admin_badge),
                 fee_to_pool: fee_to_pool,
+                protocol_fee_share: protocol_fee_share,
+                protocol_fee_vaults: protocol_fee_vaults,
+                price_cumulative: price_cumulative.clone(),
+                last_update_epoch: current_epoch,
+                price_observations: vec![PriceObservation {
+                    epoch: current_epoch,
+                    price_cumulative: price_cumulative
+                }],
+                next_price_observation: 1,
+                flash_swap_admin_badge: Vault::with_bucket(flash_swap_admin_badge),
+                flash_swap_receipt_address: flash_swap_receipt_address,
+                flash_swap_k_before: None,
             }
             .instantiate()
+            .add_access_check(access_rules)
             .globalize();
 
             return (liquidity_pool, tracking_tokens);
@@ -186,6 +321,34 @@ blueprint!{
             );
         }
 
+        /// Asserts that there is no outstanding flash swap on the pool.
+        /// 
+        /// While a flash swap is outstanding the reserves of the pool are lower than they should be, so all of the 
+        /// methods which change the reserves are locked until the flash swap is repaid.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `label` (String) - The label of the method that called this assert method.
+        pub fn assert_no_flash_swap(
+            &self,
+            label: String
+        ) {
+            assert!(
+                self.flash_swap_k_before.is_none(),
+                "[{}]: The pool is locked until the outstanding flash swap is repaid.",
+                label
+            );
+        }
+
+        /// Gets the resource address of the transient flash swap receipts issued by this pool.
+        /// 
+        /// # Returns:
+        /// 
+        /// `ResourceAddress` - The resource address of the flash swap receipts.
+        pub fn flash_swap_receipt_address(&self) -> ResourceAddress {
+            return self.flash_swap_receipt_address;
+        }
+
         /// Gets the resource addresses of the tokens in this liquidity pool and returns them as a `Vec<ResourceAddress>`.
         /// 
         /// # Returns:
@@ -233,6 +396,37 @@ blueprint!{
             return if addresses[0] == resource_address {addresses[1]} else {addresses[0]};
         }
 
+        /// Gets the amount of reserves of the given token in the liquidity pool.
+        /// 
+        /// This method performs a number of checks before the reserves are obtained:
+        /// 
+        /// * **Check 1:** Checks that the provided resource address belongs to this liquidity pool.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The resource address of the token to get the reserves of.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The amount of the given token in the reserves of the pool.
+        pub fn reserve(
+            &self,
+            resource_address: ResourceAddress
+        ) -> Decimal {
+            // Checking if the passed resource address belongs to this pool.
+            self.assert_belongs_to_pool(resource_address, String::from("Reserve"));
+            return self.vaults[&resource_address].amount();
+        }
+
+        /// Gets the fee imposed on swaps through this liquidity pool.
+        /// 
+        /// # Returns:
+        /// 
+        /// `Decimal` - A decimal value between 0 and 100 of the fee imposed on swaps through this pool.
+        pub fn fee(&self) -> Decimal {
+            return self.fee_to_pool;
+        }
+
         /// Calculates the k in the constant market maker equation: `x * y = k`.
         /// 
         /// # Returns:
@@ -333,6 +527,200 @@ blueprint!{
             return dx;
         }
 
+        /// Calculates the current spot price of the given token in terms of the other token in the pool.
+        /// 
+        /// This method performs a number of checks before the price is calculated:
+        /// 
+        /// * **Check 1:** Checks that the provided resource address belongs to this liquidity pool.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The resource address of the token to get the price of.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The amount of the other token that one unit of the given token is worth at the current 
+        /// reserves. This is zero if either of the reserves is empty.
+        pub fn spot_price(
+            &self,
+            resource_address: ResourceAddress
+        ) -> Decimal {
+            // Checking if the passed resource address belongs to this pool.
+            self.assert_belongs_to_pool(resource_address, String::from("Spot Price"));
+
+            let x: Decimal = self.vaults[&resource_address].amount();
+            let y: Decimal = self.vaults[&self.other_resource_address(resource_address)].amount();
+            return if x == Decimal::zero() { Decimal::zero() } else { y / x };
+        }
+
+        /// Gets the value that the cumulative price of the given token would have if it were updated now.
+        /// 
+        /// This method performs a number of checks before the cumulative price is calculated:
+        /// 
+        /// * **Check 1:** Checks that the provided resource address belongs to this liquidity pool.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The resource address of the token to get the cumulative price of.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The cumulative price of the given token in terms of the other token.
+        pub fn current_price_cumulative(
+            &self,
+            resource_address: ResourceAddress
+        ) -> Decimal {
+            // Checking if the passed resource address belongs to this pool.
+            self.assert_belongs_to_pool(resource_address, String::from("Current Price Cumulative"));
+
+            // The current reserves have held since the last update, so the time elapsed since then is accounted for at
+            // the current spot price.
+            let elapsed: u64 = Runtime::current_epoch() - self.last_update_epoch;
+            return self.price_cumulative[&resource_address] + self.spot_price(resource_address) * Decimal::from(elapsed);
+        }
+
+        /// Calculates the time-weighted average price of the given token over the requested window of epochs.
+        /// 
+        /// This method finds the most recent price observation which is at least `window` epochs old and computes 
+        /// the average price between this observation and the current epoch from the price accumulators. Since 
+        /// observations are only recorded when the pool is interacted with, the window actually used may be longer 
+        /// than the window requested.
+        /// 
+        /// This method performs a number of checks before the price is calculated:
+        /// 
+        /// * **Check 1:** Checks that the provided resource address belongs to this liquidity pool.
+        /// * **Check 2:** Checks that the window is at least one epoch long.
+        /// * **Check 3:** Checks that the pool has a price observation old enough for the requested window.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The resource address of the token to get the price of.
+        /// * `window` (u64) - The number of epochs to average the price over.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The time-weighted average price of the given token in terms of the other token.
+        pub fn twap(
+            &self,
+            resource_address: ResourceAddress,
+            window: u64
+        ) -> Decimal {
+            // Performing the checks to ensure that the average price can be calculated
+            self.assert_belongs_to_pool(resource_address, String::from("TWAP"));
+            assert!(window >= 1, "[TWAP]: The window must be at least one epoch long.");
+
+            let current_epoch: u64 = Runtime::current_epoch();
+            assert!(
+                current_epoch >= window,
+                "[TWAP]: The pool does not have enough price history for the requested window."
+            );
+            let target_epoch: u64 = current_epoch - window;
+
+            // Finding the most recent observation that was recorded at or before the target epoch.
+            let count: usize = self.price_observations.len();
+            let observation: &PriceObservation = (1..=count)
+                .map(|age| &self.price_observations[(self.next_price_observation + count - age) % count])
+                .find(|observation| observation.epoch <= target_epoch)
+                .expect("[TWAP]: The pool does not have enough price history for the requested window.");
+
+            let elapsed: u64 = current_epoch - observation.epoch;
+            let price_difference: Decimal = 
+                self.current_price_cumulative(resource_address) - observation.price_cumulative[&resource_address];
+            return price_difference / Decimal::from(elapsed);
+        }
+
+        /// Sets the percentage of the swap fee that is paid to the protocol.
+        /// 
+        /// This method is only callable by the RaDEX component through the pool admin badge. Setting the share to zero 
+        /// switches the protocol fee off and all of the swap fees go to the liquidity providers.
+        /// 
+        /// This method performs a number of checks before the share is set:
+        /// 
+        /// * **Check 1:** Checks that the protocol fee share is between 0 and 100.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `protocol_fee_share` (Decimal) - A decimal value of the percentage of the swap fee that is paid to the
+        /// protocol.
+        pub fn set_protocol_fee_share(
+            &mut self,
+            protocol_fee_share: Decimal
+        ) {
+            assert!(
+                (protocol_fee_share >= Decimal::zero()) & (protocol_fee_share <= dec!("100")), 
+                "[Set Protocol Fee]: Protocol fee share must be between 0 and 100"
+            );
+            self.protocol_fee_share = protocol_fee_share;
+        }
+
+        /// Withdraws all of the protocol fees accrued in this liquidity pool.
+        /// 
+        /// This method is only callable by the RaDEX component through the pool admin badge.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Vec<Bucket>` - A vector of buckets of the protocol fees accrued for each of the two tokens in the pool.
+        pub fn withdraw_protocol_fees(&mut self) -> Vec<Bucket> {
+            return self.protocol_fee_vaults.values_mut()
+                .map(|vault| vault.take_all())
+                .collect::<Vec<Bucket>>();
+        }
+
+        /// Takes the protocol's share of the swap fee out of the given input tokens.
+        /// 
+        /// This method calculates the protocol's share of the fee for a swap with the given amount of input and moves
+        /// it from the input bucket to the protocol fee vault. The remaining input tokens are then deposited into the
+        /// reserves by the caller. Since the protocol's share is only ever a part of the swap fee, the k value of the 
+        /// pool never decreases when the remaining input is deposited.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `tokens` (&mut Bucket) - A bucket of the input tokens of the swap.
+        /// * `input_amount` (Decimal) - The amount of input tokens that the swap fee is imposed on.
+        fn take_protocol_fee(
+            &mut self,
+            tokens: &mut Bucket,
+            input_amount: Decimal
+        ) {
+            let protocol_fee: Decimal = input_amount 
+                * (self.fee_to_pool / dec!("100")) 
+                * (self.protocol_fee_share / dec!("100"));
+            if protocol_fee > Decimal::zero() {
+                self.protocol_fee_vaults.get_mut(&tokens.resource_address()).unwrap().put(tokens.take(protocol_fee));
+            }
+        }
+
+        /// Updates the price accumulators of the pool and records a price observation for the current epoch.
+        /// 
+        /// This method must be called before the reserves of the pool change (before any swap or addition or removal 
+        /// of liquidity) so that the time elapsed since the last update is accounted for at the price which has held 
+        /// over that time.
+        fn update_price_accumulators(&mut self) {
+            let current_epoch: u64 = Runtime::current_epoch();
+            if current_epoch == self.last_update_epoch {
+                return;
+            }
+
+            // Adding the price that has held since the last update weighted by the number of epochs elapsed.
+            for address in self.addresses() {
+                let price_cumulative: Decimal = self.current_price_cumulative(address);
+                self.price_cumulative.insert(address, price_cumulative);
+            }
+            self.last_update_epoch = current_epoch;
+
+            // Recording the observation for this epoch, in place of the oldest observation if the buffer is full.
+            let observation: PriceObservation = PriceObservation {
+                epoch: current_epoch,
+                price_cumulative: self.price_cumulative.clone()
+            };
+            if self.price_observations.len() < MAX_PRICE_OBSERVATIONS {
+                self.price_observations.push(observation);
+            } else {
+                self.price_observations[self.next_price_observation] = observation;
+            }
+            self.next_price_observation = (self.next_price_observation + 1) % MAX_PRICE_OBSERVATIONS;
+        }
+
         /// Deposits a bucket of tokens into this liquidity pool.
         /// 
         /// This method determines if a given bucket of tokens belongs to the liquidity pool or not. If it's found that
@@ -444,6 +832,7 @@ blueprint!{
             // Checking if the tokens belong to this liquidity pool.
             self.assert_belongs_to_pool(token1.resource_address(), String::from("Add Liquidity"));
             self.assert_belongs_to_pool(token2.resource_address(), String::from("Add Liquidity"));
+            self.assert_no_flash_swap(String::from("Add Liquidity"));
 
             // Checking that the buckets passed are not empty
             assert!(!token1.is_empty(), "[Add Liquidity]: Can not add liquidity from an empty bucket");
@@ -453,6 +842,9 @@ blueprint!{
                 token1.resource_address(), token1.amount(), token2.resource_address(), token2.amount()
             );
 
+            // Updating the price accumulators before the reserves of the pool change.
+            self.update_price_accumulators();
+
             // Sorting out the two buckets passed and getting the values of `dm` and `dn`.
             let (mut bucket1, mut bucket2): (Bucket, Bucket) = sort_buckets(token1, token2);
             let dm: Decimal = bucket1.amount();
@@ -533,6 +925,10 @@ blueprint!{
                 tracking_tokens.resource_address(), self.tracking_token_address,
                 "[Remove Liquidity]: The tracking tokens given do not belong to this liquidity pool."
             );
+            self.assert_no_flash_swap(String::from("Remove Liquidity"));
+
+            // Updating the price accumulators before the reserves of the pool change.
+            self.update_price_accumulators();
 
             // Calculating the percentage ownership that the tracking tokens amount corresponds to
             let tracking_tokens_manager: &ResourceManager = borrow_resource_manager!(self.tracking_token_address);
@@ -572,10 +968,12 @@ blueprint!{
         /// * `Bucket` - A bucket of the other tokens.
         pub fn swap(
             &mut self,
-            tokens: Bucket
+            mut tokens: Bucket
         ) -> Bucket {
             // Checking if the tokens belong to this liquidity pool.
             self.assert_belongs_to_pool(tokens.resource_address(), String::from("Swap"));
+            self.assert_no_flash_swap(String::from("Swap"));
+            self.update_price_accumulators();
             info!("[Swap]: K before swap: {}", self.k());
 
             // Calculating the output amount for the given input amount of tokens and withdrawing it from the vault
@@ -585,7 +983,10 @@ blueprint!{
                 output_amount
             );
 
-            // Depositing the tokens into the liquidity pool and returning a bucket of the swapped tokens.
+            // Taking the protocol fee, depositing the tokens into the liquidity pool and returning a bucket of the 
+            // swapped tokens.
+            let input_amount: Decimal = tokens.amount();
+            self.take_protocol_fee(&mut tokens, input_amount);
             self.deposit(tokens);
             info!("[Swap]: K after swap: {}", self.k());
             return output_tokens;
@@ -660,6 +1061,7 @@ blueprint!{
         ) -> (Bucket, Bucket) {
             // Checking that the bucket passed does indeed belong to this liquidity pool
             self.assert_belongs_to_pool(tokens.resource_address(), String::from("Swap For Exact"));
+            self.assert_no_flash_swap(String::from("Swap For Exact"));
 
             // Calculating the amount of input tokens that would be required to produce the desired amount of output 
             // tokens
@@ -673,8 +1075,11 @@ blueprint!{
             );
 
             // Depositing the amount of input required into the vaults and taking out the requested amount
+            self.update_price_accumulators();
             info!("[Swap For Exact]: K before swap: {}", self.k());
-            self.deposit(tokens.take(input_required));
+            let mut input_tokens: Bucket = tokens.take(input_required);
+            self.take_protocol_fee(&mut input_tokens, input_required);
+            self.deposit(input_tokens);
             let output_tokens: Bucket = self.withdraw(
                 self.other_resource_address(tokens.resource_address()), 
                 output_amount
@@ -683,5 +1088,139 @@ blueprint!{
             info!("[Swap For Exact]: Amount gievn out: {}", output_tokens.amount());
             return (output_tokens, tokens);
         }
+
+        /// Takes tokens out of the liquidity pool without any upfront payment.
+        /// 
+        /// This method gives out the requested amount of tokens along with a transient receipt NFT which records the 
+        /// amount owed and the fee. The receipt can not be deposited into any vault, so the transaction can only 
+        /// succeed if the receipt is given back to the `repay_flash_swap` method along with the repayment. Until then,
+        /// all of the other methods which change the reserves of the pool are locked.
+        /// 
+        /// This method performs a number of checks before the flash swap is made:
+        /// 
+        /// * **Check 1:** Checks that the resource address given does indeed belong to this liquidity pool.
+        /// * **Check 2:** Checks that there is no other outstanding flash swap on the pool.
+        /// * **Check 3:** Checks that the amount requested is positive and less than the reserves of the pool.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The address of the resource to take out of the liquidity pool.
+        /// * `amount` (Decimal) - The amount of tokens to take out of the liquidity pool.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the tokens taken out of the pool.
+        /// * `Bucket` - A bucket of the transient flash swap receipt.
+        pub fn flash_swap(
+            &mut self,
+            resource_address: ResourceAddress,
+            amount: Decimal
+        ) -> (Bucket, Bucket) {
+            // Performing the checks to ensure that the flash swap can go through
+            self.assert_belongs_to_pool(resource_address, String::from("Flash Swap"));
+            self.assert_no_flash_swap(String::from("Flash Swap"));
+            assert!(
+                (amount > Decimal::zero()) & (amount < self.vaults[&resource_address].amount()),
+                "[Flash Swap]: The amount must be positive and less than the reserves of the pool."
+            );
+
+            // Updating the price accumulators and recording the k value before the reserves of the pool change.
+            self.update_price_accumulators();
+            self.flash_swap_k_before = Some(self.k());
+            info!("[Flash Swap]: K before flash swap: {}", self.k());
+
+            // Minting the receipt of the flash swap. The fee is the same as the fee imposed on regular swaps.
+            let fee: Decimal = amount * self.fee_to_pool / dec!("100");
+            let receipt: Bucket = self.flash_swap_admin_badge.authorize(|| {
+                borrow_resource_manager!(self.flash_swap_receipt_address).mint_non_fungible(
+                    &NonFungibleId::random(),
+                    FlashSwapReceipt {
+                        resource_address: resource_address,
+                        amount: amount,
+                        fee: fee,
+                        amount_due: amount + fee,
+                    }
+                )
+            });
+
+            return (self.withdraw(resource_address, amount), receipt);
+        }
+
+        /// Repays an outstanding flash swap and burns its receipt.
+        /// 
+        /// The repayment may be made in either of the two tokens of the pool. If it is made in the tokens which were 
+        /// taken out, then the `amount_due` recorded on the receipt is taken from the repayment. If it is made in the
+        /// other token, then the amount taken is the amount of input that a regular swap would have required to give
+        /// out the tokens which were taken out at the reserves before the flash swap. In both cases, the protocol's 
+        /// share of the fee is taken and the constant product invariant is checked against the k value of the pool 
+        /// before the flash swap.
+        /// 
+        /// This method performs a number of checks before the flash swap is repaid:
+        /// 
+        /// * **Check 1:** Checks that the receipt is a single flash swap receipt issued by this liquidity pool.
+        /// * **Check 2:** Checks that the repayment is of a token that belongs to this liquidity pool.
+        /// * **Check 3:** Checks that the repayment is enough to cover the amount owed.
+        /// * **Check 4:** Checks that the k value of the pool has not decreased.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `repayment` (Bucket) - A bucket of the tokens used to repay the flash swap.
+        /// * `receipt` (Bucket) - A bucket of the flash swap receipt.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the remaining repayment tokens.
+        pub fn repay_flash_swap(
+            &mut self,
+            mut repayment: Bucket,
+            receipt: Bucket
+        ) -> Bucket {
+            // Checking that the receipt is valid and that the repayment can go through
+            assert_eq!(
+                receipt.resource_address(), self.flash_swap_receipt_address,
+                "[Repay Flash Swap]: The receipt given was not issued by this liquidity pool."
+            );
+            assert_eq!(receipt.amount(), Decimal::one(), "[Repay Flash Swap]: Only a single receipt may be given.");
+            self.assert_belongs_to_pool(repayment.resource_address(), String::from("Repay Flash Swap"));
+            let k_before: Decimal = self.flash_swap_k_before
+                .expect("[Repay Flash Swap]: There is no outstanding flash swap on this pool.");
+
+            // Calculating the amount owed in the tokens of the repayment.
+            let receipt_data: FlashSwapReceipt = receipt.non_fungible::<FlashSwapReceipt>().data();
+            let amount_owed: Decimal = if repayment.resource_address() == receipt_data.resource_address {
+                receipt_data.amount_due
+            } else {
+                // The reserves of the borrowed token are currently lower by the borrowed amount, so this is the same as
+                // `calculate_input_amount` at the reserves before the flash swap.
+                let x: Decimal = self.vaults[&repayment.resource_address()].amount();
+                let y: Decimal = self.vaults[&receipt_data.resource_address].amount();
+                let r: Decimal = (dec!("100") - self.fee_to_pool) / dec!("100");
+                (receipt_data.amount * x) / (r * y)
+            };
+            assert!(
+                repayment.amount() >= amount_owed,
+                "[Repay Flash Swap]: Not enough tokens given to repay the flash swap."
+            );
+
+            // Taking the protocol fee and depositing the rest of the amount owed into the reserves of the pool.
+            let mut owed_tokens: Bucket = repayment.take(amount_owed);
+            let input_amount: Decimal = if owed_tokens.resource_address() == receipt_data.resource_address {
+                receipt_data.amount
+            } else {
+                amount_owed
+            };
+            self.take_protocol_fee(&mut owed_tokens, input_amount);
+            self.deposit(owed_tokens);
+
+            // Checking the constant product invariant, burning the receipt, and unlocking the pool.
+            info!("[Repay Flash Swap]: K after flash swap: {}", self.k());
+            assert!(self.k() >= k_before, "[Repay Flash Swap]: The constant product invariant was violated.");
+            self.flash_swap_admin_badge.authorize(|| {
+                receipt.burn();
+            });
+            self.flash_swap_k_before = None;
+
+            return repayment;
+        }
     }
 }
\ No newline at end of file
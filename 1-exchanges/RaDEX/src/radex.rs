@@ -10,37 +10,95 @@ blueprint!{
     /// their own. Instead, a RaDEX component may be thought of as a registry of all of the RaDEX liquidity pools and as 
     /// a router which routes swaps and other method calls to the correct liquidity pool.
     struct RaDEX{
-        /// This is a hashmap that maps a tuple of two addresses to a Scrypto component. This scrypto component is a 
-        /// liquidity pool meaning that this hashmap maps a tuple of two addresses to a liquidity pool. This hashmap is
-        /// used as a way of quickly finding the liquidity pool associated with a given address pair. If a pair of 
-        /// addresses does not exist in this hashmap it means that there does not exist a liquidity pool for it on RaDEX
-        liquidity_pools: HashMap<(ResourceAddress, ResourceAddress), LiquidityPool>,
+        /// This is a hashmap that maps a tuple of two addresses and a fee tier to a Scrypto component. This scrypto 
+        /// component is a liquidity pool meaning that this hashmap maps a tuple of two addresses and a fee tier to a 
+        /// liquidity pool. This hashmap is used as a way of quickly finding the liquidity pool associated with a given
+        /// address pair and fee tier. Multiple liquidity pools may exist for the same address pair as long as they are
+        /// of different fee tiers. If an address pair does not exist in any of the keys of this hashmap it means that
+        /// there does not exist a liquidity pool for it on RaDEX.
+        liquidity_pools: HashMap<(ResourceAddress, ResourceAddress, u16), LiquidityPool>,
 
         /// That's quite the mouthful. This is a hashmap that is mainly used when liquidity providers are tying to 
         /// remove their portion of liquidity from the liquidity pool. This hashmap is used to find the address pair (
         /// and in turn the liquidity pool) associated with a given tracking token. If the resource address of a given
         /// tracking token does not exist as one of the keys to this hashmap, then this means that this tracking token
         /// does not belong to any of the liquidity pools in RaDEX.
-        tracking_token_address_pair_mapping: HashMap<ResourceAddress, (ResourceAddress, ResourceAddress)>
+        tracking_token_address_pair_mapping: HashMap<ResourceAddress, (ResourceAddress, ResourceAddress, u16)>,
+
+        /// This hashmap is used in the same way as the tracking token hashmap above but for the flash swap receipts. It
+        /// maps the resource address of the flash swap receipts of each liquidity pool to the address pair and fee 
+        /// tier of the pool so that a flash swap can be repaid to the pool which it was taken from.
+        flash_swap_receipt_address_pair_mapping: HashMap<ResourceAddress, (ResourceAddress, ResourceAddress, u16)>,
+
+        /// The fee tiers (in basis points) which liquidity pools may be created with. A fee tier of 30 means that a fee
+        /// of 0.3% is imposed on all swaps through the liquidity pool. New fee tiers may be added by the admin.
+        fee_tiers: Vec<u16>,
+
+        /// This is a decimal value between 0 and 100 which defines the percentage of the swap fees of all of the 
+        /// liquidity pools which is paid to the protocol instead of the liquidity providers. This is the protocol fee
+        /// switch; a value of zero means that the protocol fee is switched off.
+        protocol_fee_share: Decimal,
+
+        /// The badge that the RaDEX component uses to authorize the changing of the protocol fee share of the liquidity
+        /// pools and the withdrawal of the protocol fees from them.
+        pool_admin_badge: Vault,
+
+        /// These are the vaults where the protocol fees collected from the liquidity pools are stored until they are 
+        /// withdrawn by the admin.
+        protocol_fees: HashMap<ResourceAddress, Vault>
     }
 
     impl RaDEX {
         /// Instantiates a new RaDEX component. 
         /// 
+        /// The RaDEX component is created with the 0.05%, 0.3%, and 1% fee tiers and with the protocol fee switched 
+        /// off. An admin badge is returned to the caller which may be used to switch the protocol fee on, add new fee 
+        /// tiers, and withdraw the protocol fees.
+        /// 
         /// # Returns 
         /// 
-        /// `Component` - A new RaDEX component.
-        pub fn new() -> ComponentAddress {
-            // The RaDEX AMM does not take any arguments 
-            return Self {
+        /// * `Component` - A new RaDEX component.
+        /// * `Bucket` - A bucket containing the admin badge of the RaDEX component.
+        pub fn new() -> (ComponentAddress, Bucket) {
+            // Creating the admin badge which is given to the caller and the pool admin badge which is kept by the 
+            // component to authorize the calls made to the protocol fee methods of the liquidity pools.
+            let admin_badge: Bucket = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", "RaDEX Admin Badge")
+                .metadata("symbol", "RAB")
+                .metadata("description", "This is an admin badge that has the authority to manage the protocol fees of RaDEX")
+                .initial_supply(1);
+
+            let pool_admin_badge: Bucket = ResourceBuilder::new_fungible()
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata("name", "RaDEX Pool Admin Badge")
+                .metadata("symbol", "RPAB")
+                .metadata("description", "This is a badge that has the authority to manage the protocol fees of the RaDEX liquidity pools")
+                .initial_supply(1);
+
+            let access_rules: AccessRules = AccessRules::new()
+                .method("add_fee_tier", rule!(require(admin_badge.resource_address())))
+                .method("set_protocol_fee_share", rule!(require(admin_badge.resource_address())))
+                .method("withdraw_protocol_fees", rule!(require(admin_badge.resource_address())))
+                .default(rule!(allow_all));
+
+            let radex: ComponentAddress = Self {
                 liquidity_pools: HashMap::new(), 
-                tracking_token_address_pair_mapping: HashMap::new()
+                tracking_token_address_pair_mapping: HashMap::new(),
+                flash_swap_receipt_address_pair_mapping: HashMap::new(),
+                fee_tiers: vec![5, 30, 100],
+                protocol_fee_share: Decimal::zero(),
+                pool_admin_badge: Vault::with_bucket(pool_admin_badge),
+                protocol_fees: HashMap::new()
             }
             .instantiate()
+            .add_access_check(access_rules)
             .globalize();
+
+            return (radex, admin_badge);
         }
 
-        /// Checks if a liquidity pool for the given pair of tokens exists or not.
+        /// Checks if a liquidity pool of any fee tier for the given pair of tokens exists or not.
         /// 
         /// # Arguments:
         /// 
@@ -55,10 +113,63 @@ blueprint!{
             address1: ResourceAddress,
             address2: ResourceAddress
         ) -> bool {
-            // Sorting the two addresses passed and then checking if the tuple of sorted addresses exists in the hashmap
-            // of liquidity pools or not.
+            return !self.pool_fee_tiers(address1, address2).is_empty();
+        }
+
+        /// Checks if a liquidity pool of the given fee tier for the given pair of tokens exists or not.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `address` (ResourceAddress) - The resource address of the first token.
+        /// * `address` (ResourceAddress) - The resource address of the second token.
+        /// * `fee_tier` (u16) - The fee tier of the liquidity pool in basis points.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `bool` - A boolean of whether a liquidity pool exists for this trading pair and fee tier.
+        pub fn pool_with_fee_tier_exists(
+            &self,
+            address1: ResourceAddress,
+            address2: ResourceAddress,
+            fee_tier: u16
+        ) -> bool {
+            // Sorting the two addresses passed and then checking if the tuple of sorted addresses and the fee tier 
+            // exists in the hashmap of liquidity pools or not.
+            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(address1, address2);
+            return self.liquidity_pools.contains_key(&(sorted_addresses.0, sorted_addresses.1, fee_tier));
+        }
+
+        /// Gets the fee tiers of all of the liquidity pools which exist for the given pair of tokens.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `address` (ResourceAddress) - The resource address of the first token.
+        /// * `address` (ResourceAddress) - The resource address of the second token.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Vec<u16>` - A vector of the fee tiers (in basis points) of the liquidity pools of this trading pair.
+        pub fn pool_fee_tiers(
+            &self,
+            address1: ResourceAddress,
+            address2: ResourceAddress
+        ) -> Vec<u16> {
             let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(address1, address2);
-            return self.liquidity_pools.contains_key(&sorted_addresses);
+            return self.liquidity_pools.keys()
+                .filter(|(pool_address1, pool_address2, _)| {
+                    (*pool_address1, *pool_address2) == sorted_addresses
+                })
+                .map(|(_, _, fee_tier)| *fee_tier)
+                .collect::<Vec<u16>>();
+        }
+
+        /// Gets the fee tiers (in basis points) which liquidity pools may be created with.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Vec<u16>` - A vector of the allowed fee tiers in basis points.
+        pub fn fee_tiers(&self) -> Vec<u16> {
+            return self.fee_tiers.clone();
         }
 
         /// Asserts that a liquidity pool for the given address pair exists on the DEX.
@@ -80,21 +191,23 @@ blueprint!{
             );
         }
         
-        /// Asserts that a liquidity pool for the given address pair doesn't exist on the DEX.
+        /// Asserts that a liquidity pool for the given address pair and fee tier doesn't exist on the DEX.
         /// 
         /// # Arguments:
         /// 
         /// * `address` (ResourceAddress) - The resource address of the first token.
         /// * `address` (ResourceAddress) - The resource address of the second token.
+        /// * `fee_tier` (u16) - The fee tier of the liquidity pool in basis points.
         pub fn assert_pool_doesnt_exists(
             &self,
             address1: ResourceAddress,
             address2: ResourceAddress,
+            fee_tier: u16,
             label: String
         ) {
             assert!(
-                !self.pool_exists(address1, address2), 
-                "[{}]: A liquidity pool with the given address pair already exists.", 
+                !self.pool_with_fee_tier_exists(address1, address2, fee_tier), 
+                "[{}]: A liquidity pool with the given address pair and fee tier already exists.", 
                 label
             );
         }
@@ -105,7 +218,9 @@ blueprint!{
         /// 
         /// This method does a number of checks before a Liquidity Pool is created, these checks are:
         /// 
-        /// * **Check 1:** Checks that there does not already exist a liquidity pool for the two given tokens.
+        /// * **Check 1:** Checks that the fee tier is one of the allowed fee tiers.
+        /// * **Check 2:** Checks that there does not already exist a liquidity pool for the two given tokens with the 
+        /// given fee tier.
         /// 
         /// The majority of the checking is done in the `new` function of the LiquidityPool where it checks to ensure 
         /// that the buckets are not empty, tokens are not both the same, as well as other things. The checks done here
@@ -116,6 +231,7 @@ blueprint!{
         /// 
         /// * `token1` (Bucket) - A bucket containing the amount of the first token used to initialize the pool.
         /// * `token2` (Bucket) - A bucket containing the amount of the second token used to initialize the pool.
+        /// * `fee_tier` (u16) - The fee tier of the liquidity pool in basis points.
         /// 
         /// # Returns:
         /// 
@@ -124,25 +240,44 @@ blueprint!{
             &mut self,
             token1: Bucket,
             token2: Bucket,
+            fee_tier: u16
         ) -> Bucket {
-            // Checking if a liquidity pool already exists between these two tokens
+            // Checking if the fee tier is allowed and if a liquidity pool already exists between these two tokens with
+            // the given fee tier.
+            assert!(
+                self.fee_tiers.contains(&fee_tier),
+                "[New Liquidity Pool]: The given fee tier is not one of the allowed fee tiers."
+            );
             self.assert_pool_doesnt_exists(
-                token1.resource_address(), token2.resource_address(), 
+                token1.resource_address(), token2.resource_address(), fee_tier,
                 String::from("New Liquidity Pool")
             );
 
             // Sorting the two buckets according to their resource addresses and creating a liquidity pool from these
-            // two buckets.
+            // two buckets. The fee tier is in basis points while the liquidity pool expects a percentage.
             let (bucket1, bucket2): (Bucket, Bucket) = sort_buckets(token1, token2);
-            let addresses: (ResourceAddress, ResourceAddress) = (bucket1.resource_address(), bucket2.resource_address()); 
+            let addresses: (ResourceAddress, ResourceAddress, u16) = (
+                bucket1.resource_address(), bucket2.resource_address(), fee_tier
+            ); 
             let (liquidity_pool, tracking_tokens): (ComponentAddress, Bucket) = LiquidityPool::new(
-                bucket1, bucket2, dec!("0.3")
+                bucket1, bucket2, 
+                Decimal::from(fee_tier) / dec!("100"), 
+                self.protocol_fee_share, 
+                self.pool_admin_badge.resource_address()
+            );
+
+            // Adding the resource address of the flash swap receipts of the pool to the hashmap that maps the flash 
+            // swap receipts with the address of their token pairs.
+            let liquidity_pool: LiquidityPool = liquidity_pool.into();
+            self.flash_swap_receipt_address_pair_mapping.insert(
+                liquidity_pool.flash_swap_receipt_address(),
+                addresses
             );
 
             // Adding the liquidity pool to the hashmap of all liquidity pools
             self.liquidity_pools.insert(
                 addresses,
-                liquidity_pool.into()
+                liquidity_pool
             );
 
             // Adding the resource address of the tracking tokens to the hashmap that maps the tracking tokens with 
@@ -166,6 +301,7 @@ blueprint!{
         /// 
         /// * `token1` (Bucket) - A bucket containing the amount of the first token to add to the pool.
         /// * `token2` (Bucket) - A bucket containing the amount of the second token to add to the pool.
+        /// * `fee_tier` (u16) - The fee tier of the liquidity pool in basis points.
         /// 
         /// # Returns:
         /// 
@@ -176,10 +312,13 @@ blueprint!{
             &mut self,
             token1: Bucket,
             token2: Bucket,
+            fee_tier: u16
         ) -> (Option<Bucket>, Option<Bucket>, Bucket) {
             // Sorting the two buckets of tokens passed to this method and getting the addresses of their resources.
             let (bucket1, bucket2): (Bucket, Bucket) = sort_buckets(token1, token2);
-            let addresses: (ResourceAddress, ResourceAddress) = (bucket1.resource_address(), bucket2.resource_address()); 
+            let addresses: (ResourceAddress, ResourceAddress, u16) = (
+                bucket1.resource_address(), bucket2.resource_address(), fee_tier
+            ); 
 
             // Attempting to get the liquidity pool component associated with the provided address pair.
             let optional_liquidity_pool: Option<&LiquidityPool> = self.liquidity_pools.get(&addresses);
@@ -195,7 +334,7 @@ blueprint!{
                     // terms of the two empty buckets being returned, but this is done to allow for the add liquidity
                     // method to be general and allow for the possibility of the liquidity pool not being there.
                     info!("[DEX Add Liquidity]: Pool for {:?} doesn't exist. Creating a new one.", addresses);
-                    (None, None, self.new_liquidity_pool(bucket1, bucket2))
+                    (None, None, self.new_liquidity_pool(bucket1, bucket2, fee_tier))
                 }
             }
         }
@@ -234,7 +373,7 @@ blueprint!{
 
             // Getting the address pair associated with the resource address of the tracking tokens and then requesting
             // the removal of liquidity from the liquidity pool
-            let addresses: (ResourceAddress, ResourceAddress) = self.tracking_token_address_pair_mapping[&tracking_tokens.resource_address()];
+            let addresses: (ResourceAddress, ResourceAddress, u16) = self.tracking_token_address_pair_mapping[&tracking_tokens.resource_address()];
             return self.liquidity_pools[&addresses].remove_liquidity(tracking_tokens);
         }
 
@@ -242,7 +381,8 @@ blueprint!{
         /// 
         /// This method is used to swap tokens for other tokens. This method first checks that there does exist a 
         /// liquidity pool between the input and the output tokens. If a liquidity pool is found, then the swap goes
-        /// through.
+        /// through. If liquidity pools of multiple fee tiers exist for the pair, then the swap goes through the pool 
+        /// which gives the largest output.
         /// 
         /// This method performs a number of checks before the swap is performed:
         /// 
@@ -264,12 +404,13 @@ blueprint!{
             // Checking if there does exist a liquidity pool for the given pair of tokens
             self.assert_pool_exists(tokens.resource_address(), output_resource_address, String::from("DEX Swap"));
 
-            // Sorting the two addresses passed, getting the associated liquidity pool and then performing the swap.
-            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+            // Getting the liquidity pool which gives the largest output for the input tokens and performing the swap.
+            let (liquidity_pool, _): (&LiquidityPool, Decimal) = self.best_pool_for_input(
                 tokens.resource_address(), 
-                output_resource_address
+                output_resource_address,
+                tokens.amount()
             );
-            return self.liquidity_pools[&sorted_addresses].swap(tokens);
+            return liquidity_pool.swap(tokens);
         }
 
         /// Swaps the exact amount of input tokens for tokens of the desired type.
@@ -301,12 +442,13 @@ blueprint!{
             // Checking if there does exist a liquidity pool for the given pair of tokens
             self.assert_pool_exists(tokens.resource_address(), output_resource_address, String::from("DEX Swap Exact"));
 
-            // Sorting the two addresses passed, getting the associated liquidity pool and then performing the swap.
-            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+            // Getting the liquidity pool which gives the largest output for the input tokens and performing the swap.
+            let (liquidity_pool, _): (&LiquidityPool, Decimal) = self.best_pool_for_input(
                 tokens.resource_address(), 
-                output_resource_address
+                output_resource_address,
+                tokens.amount()
             );
-            return self.liquidity_pools[&sorted_addresses].swap_exact_tokens_for_tokens(tokens, min_amount_out);
+            return liquidity_pool.swap_exact_tokens_for_tokens(tokens, min_amount_out);
         }
         
         /// Swaps the input tokens for a specific amount of tokens of the desired type.
@@ -337,12 +479,558 @@ blueprint!{
             // Checking if there does exist a liquidity pool for the given pair of tokens
             self.assert_pool_exists(tokens.resource_address(), output_resource_address, String::from("DEX Swap For Exact"));
 
-            // Sorting the two addresses passed, getting the associated liquidity pool and then performing the swap.
+            // Getting the liquidity pool which requires the least input for the desired output and performing the swap.
+            let (liquidity_pool, _): (&LiquidityPool, Decimal) = self.best_pool_for_output(
+                tokens.resource_address(), 
+                output_resource_address,
+                output_amount
+            );
+            return liquidity_pool.swap_tokens_for_exact_tokens(tokens, output_amount);
+        }
+
+        /// Takes tokens out of a liquidity pool without any upfront payment.
+        /// 
+        /// This method routes the flash swap to the liquidity pool of the given pair and fee tier. The tokens are given
+        /// out along with a transient receipt which can not be deposited anywhere; therefore, the flash swap must be 
+        /// repaid through the `repay_flash_swap` method in the same transaction for the transaction to succeed.
+        /// 
+        /// This method performs a number of checks before the flash swap is made:
+        /// 
+        /// * **Check 1:** Checks that there does exist a liquidity pool for the given pair of tokens and fee tier.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `resource_address` (ResourceAddress) - The resource address of the tokens to take out of the pool.
+        /// * `other_resource_address` (ResourceAddress) - The resource address of the other token of the pool.
+        /// * `fee_tier` (u16) - The fee tier (in basis points) of the liquidity pool.
+        /// * `amount` (Decimal) - The amount of tokens to take out of the pool.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the tokens taken out of the pool.
+        /// * `Bucket` - A bucket of the transient flash swap receipt.
+        pub fn flash_swap(
+            &mut self,
+            resource_address: ResourceAddress,
+            other_resource_address: ResourceAddress,
+            fee_tier: u16,
+            amount: Decimal
+        ) -> (Bucket, Bucket) {
+            // Checking if there does exist a liquidity pool for the given pair of tokens and fee tier
+            assert!(
+                self.pool_with_fee_tier_exists(resource_address, other_resource_address, fee_tier),
+                "[DEX Flash Swap]: No liquidity pool exists for the given address pair and fee tier."
+            );
+
+            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+                resource_address, 
+                other_resource_address
+            );
+            return self.liquidity_pools[&(sorted_addresses.0, sorted_addresses.1, fee_tier)]
+                .flash_swap(resource_address, amount);
+        }
+
+        /// Repays an outstanding flash swap to the liquidity pool which it was taken from.
+        /// 
+        /// This method performs a number of checks before the flash swap is repaid:
+        /// 
+        /// * **Check 1:** Checks that the receipt was issued by one of the liquidity pools of this DEX.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `repayment` (Bucket) - A bucket of the tokens used to repay the flash swap. This may be either of the two 
+        /// tokens of the liquidity pool.
+        /// * `receipt` (Bucket) - A bucket of the flash swap receipt.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the remaining repayment tokens.
+        pub fn repay_flash_swap(
+            &mut self,
+            repayment: Bucket,
+            receipt: Bucket
+        ) -> Bucket {
+            // Check to make sure that the receipt was issued by one of the liquidity pools of this DEX.
+            assert!(
+                self.flash_swap_receipt_address_pair_mapping.contains_key(&receipt.resource_address()),
+                "[DEX Repay Flash Swap]: The receipt given does not belong to this exchange."
+            );
+
+            let addresses: (ResourceAddress, ResourceAddress, u16) = self.flash_swap_receipt_address_pair_mapping[&receipt.resource_address()];
+            return self.liquidity_pools[&addresses].repay_flash_swap(repayment, receipt);
+        }
+
+        /// Gets the time-weighted average price of a token in terms of another token over the requested window.
+        /// 
+        /// This method is meant to be used by other blueprints as a price source which is resistant to manipulation. 
+        /// The price is read from the price accumulators of the liquidity pool of the given pair of tokens which are
+        /// updated on every swap and every addition or removal of liquidity.
+        /// 
+        /// This method performs a number of checks before the price is obtained:
+        /// 
+        /// * **Check 1:** Checks that there does exist a liquidity pool for the given pair of tokens and fee tier.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `base_resource_address` (ResourceAddress) - The resource address of the token to get the price of.
+        /// * `quote_resource_address` (ResourceAddress) - The resource address of the token that the price is in.
+        /// * `fee_tier` (u16) - The fee tier (in basis points) of the liquidity pool to read the price from.
+        /// * `window` (u64) - The number of epochs to average the price over.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The time-weighted average price of one unit of the base token in terms of the quote token.
+        pub fn twap(
+            &self,
+            base_resource_address: ResourceAddress,
+            quote_resource_address: ResourceAddress,
+            fee_tier: u16,
+            window: u64
+        ) -> Decimal {
+            // Checking if there does exist a liquidity pool for the given pair of tokens and fee tier
+            assert!(
+                self.pool_with_fee_tier_exists(base_resource_address, quote_resource_address, fee_tier),
+                "[DEX TWAP]: No liquidity pool exists for the given address pair and fee tier."
+            );
+
             let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+                base_resource_address, 
+                quote_resource_address
+            );
+            return self.liquidity_pools[&(sorted_addresses.0, sorted_addresses.1, fee_tier)]
+                .twap(base_resource_address, window);
+        }
+
+        /// Finds the best route of liquidity pools to swap the given amount of input tokens for the output token.
+        /// 
+        /// This method is used to find a path of tokens which connects the input token to the output token through the
+        /// liquidity pools registered in RaDEX. All of the simple paths (paths which do not visit the same token twice)
+        /// of up to `max_hops` liquidity pools are explored and each one of them is quoted through the 
+        /// `calculate_output_amount` method of the liquidity pools along the path. The path which gives the largest 
+        /// amount of output tokens is then returned. When liquidity pools of multiple fee tiers exist for a pair of 
+        /// tokens along the path, the pool which gives the largest output for that hop is used.
+        /// 
+        /// This method performs a number of checks before the route is found:
+        /// 
+        /// * **Check 1:** Checks that the input and output tokens are not the same.
+        /// * **Check 2:** Checks that the maximum number of hops is at least 1.
+        /// * **Check 3:** Checks that there exists at least one route between the input and output tokens.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `input_resource_address` (ResourceAddress) - The resource address of the input token.
+        /// * `output_resource_address` (ResourceAddress) - The resource address of the token to receive from the swap.
+        /// * `input_amount` (Decimal) - The amount of input tokens to find the best route for.
+        /// * `max_hops` (u8) - The maximum number of liquidity pools that the route may go through.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Vec<ResourceAddress>` - A vector of the resource addresses of the tokens along the route beginning with
+        /// the input token and ending with the output token.
+        /// * `Decimal` - The amount of output tokens that the route is quoted to give.
+        /// 
+        /// # Note:
+        /// 
+        /// The number of paths explored grows very quickly with `max_hops` and with the number of liquidity pools in
+        /// the DEX. Each explored pool is quoted through a call to the liquidity pool component which costs fees, so 
+        /// callers should keep `max_hops` small (3 or 4 hops is typically more than enough).
+        pub fn find_best_route(
+            &self,
+            input_resource_address: ResourceAddress,
+            output_resource_address: ResourceAddress,
+            input_amount: Decimal,
+            max_hops: u8
+        ) -> (Vec<ResourceAddress>, Decimal) {
+            // Performing the checks to ensure that a route may be looked for
+            assert_ne!(
+                input_resource_address, output_resource_address,
+                "[DEX Find Route]: The input and output tokens must be different."
+            );
+            assert!(max_hops >= 1, "[DEX Find Route]: A route must be allowed at least one hop.");
+
+            // Exploring all of the routes starting at the input token and keeping track of the best one found.
+            let mut path: Vec<ResourceAddress> = vec![input_resource_address];
+            let mut best_route: Option<(Vec<ResourceAddress>, Decimal)> = None;
+            self.explore_routes(&mut path, input_amount, output_resource_address, max_hops, &mut best_route);
+
+            let (route, output_amount): (Vec<ResourceAddress>, Decimal) = best_route.expect(
+                "[DEX Find Route]: No route exists between the given tokens within the maximum number of hops."
+            );
+            info!("[DEX Find Route]: Best route found: {:?} with an output of: {}", route, output_amount);
+            return (route, output_amount);
+        }
+
+        /// Quotes the amount of output tokens that would be given for swapping the input amount along the given route.
+        /// 
+        /// This method performs a number of checks before the quote is calculated:
+        /// 
+        /// * **Check 1:** Checks that the route contains at least two tokens.
+        /// * **Check 2:** Checks that there does exist a liquidity pool for each consecutive pair of tokens in the route.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `input_amount` (Decimal) - The amount of input tokens (the first token in the route) to quote.
+        /// * `route` (Vec<ResourceAddress>) - A vector of the resource addresses of the tokens along the route.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The amount of output tokens (the last token in the route) that the swap would give.
+        pub fn quote_route_output(
+            &self,
+            input_amount: Decimal,
+            route: Vec<ResourceAddress>
+        ) -> Decimal {
+            assert!(route.len() >= 2, "[DEX Quote Route]: A route must contain at least two tokens.");
+
+            // Going through the pools along the route in order and feeding the output of each pool into the next one.
+            let mut amount: Decimal = input_amount;
+            for hop in route.windows(2) {
+                self.assert_pool_exists(hop[0], hop[1], String::from("DEX Quote Route"));
+                amount = self.best_pool_for_input(hop[0], hop[1], amount).1;
+            }
+            return amount;
+        }
+
+        /// Quotes the amount of input tokens required to receive the given amount of output along the given route.
+        /// 
+        /// This method performs a number of checks before the quote is calculated:
+        /// 
+        /// * **Check 1:** Checks that the route contains at least two tokens.
+        /// * **Check 2:** Checks that there does exist a liquidity pool for each consecutive pair of tokens in the route.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `output_amount` (Decimal) - The amount of output tokens (the last token in the route) desired.
+        /// * `route` (Vec<ResourceAddress>) - A vector of the resource addresses of the tokens along the route.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Decimal` - The amount of input tokens (the first token in the route) that the swap would require.
+        pub fn quote_route_input(
+            &self,
+            output_amount: Decimal,
+            route: Vec<ResourceAddress>
+        ) -> Decimal {
+            assert!(route.len() >= 2, "[DEX Quote Route]: A route must contain at least two tokens.");
+
+            // Going through the pools along the route in reverse order and calculating the input required by each pool
+            // to produce the amount required by the pool after it.
+            let mut amount: Decimal = output_amount;
+            for hop in route.windows(2).rev() {
+                self.assert_pool_exists(hop[0], hop[1], String::from("DEX Quote Route"));
+                amount = self.best_pool_for_output(hop[0], hop[1], amount).1;
+            }
+            return amount;
+        }
+
+        /// Swaps the input tokens along the given route of liquidity pools.
+        /// 
+        /// This method is used to swap the input tokens through a number of liquidity pools in a single method call. 
+        /// The output of each pool is fed directly into the next pool along the route and only the final output is 
+        /// checked against the `min_amount_out` specified by the caller. If the final output does not satisfy the 
+        /// `min_amount_out` then this method fails and all of the swaps along the route are reverted.
+        /// 
+        /// This method performs a number of checks before the swap is performed:
+        /// 
+        /// * **Check 1:** Checks that the route contains at least two tokens.
+        /// * **Check 2:** Checks that the route begins with the token in the bucket passed.
+        /// * **Check 3:** Checks that there does exist a liquidity pool for each consecutive pair of tokens in the route.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `tokens` (Bucket) - A bucket containing the input tokens that will be swapped for other tokens.
+        /// * `route` (Vec<ResourceAddress>) - A vector of the resource addresses of the tokens along the route.
+        /// * `min_amount_out` (Decimal) - The minimum amount of tokens that the caller is willing to accept before the 
+        /// method fails.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the tokens at the end of the route.
+        pub fn swap_along_route(
+            &mut self,
+            tokens: Bucket,
+            route: Vec<ResourceAddress>,
+            min_amount_out: Decimal
+        ) -> Bucket {
+            // Checking that the route is valid for the tokens passed
+            assert!(route.len() >= 2, "[DEX Swap Route]: A route must contain at least two tokens.");
+            assert_eq!(
+                tokens.resource_address(), route[0],
+                "[DEX Swap Route]: The route must begin with the token being swapped."
+            );
+
+            // Performing the swaps along the route one pool at a time.
+            let mut output_tokens: Bucket = tokens;
+            for hop in route.windows(2) {
+                self.assert_pool_exists(hop[0], hop[1], String::from("DEX Swap Route"));
+                let (liquidity_pool, _): (&LiquidityPool, Decimal) = self.best_pool_for_input(
+                    hop[0], hop[1], output_tokens.amount()
+                );
+                output_tokens = liquidity_pool.swap(output_tokens);
+            }
+
+            // Checking the final amount of the output against the slippage specified by the caller.
+            assert!(output_tokens.amount() >= min_amount_out, "[DEX Swap Route]: min_amount_out not satisfied.");
+            return output_tokens;
+        }
+
+        /// Swaps the input tokens for the output token through the best route of liquidity pools.
+        /// 
+        /// This method finds the best route (of up to `max_hops` liquidity pools) to swap the tokens passed for the 
+        /// desired output token and then performs the swaps along this route. Everything happens atomically; so if the
+        /// output does not satisfy the `min_amount_out` specified by the caller, then all of the swaps are reverted.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `tokens` (Bucket) - A bucket containing the input tokens that will be swapped for other tokens.
+        /// * `output_resource_address` (ResourceAddress) - The resource address of the token to receive from the swap.
+        /// * `max_hops` (u8) - The maximum number of liquidity pools that the route may go through.
+        /// * `min_amount_out` (Decimal) - The minimum amount of tokens that the caller is willing to accept before the 
+        /// method fails.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Bucket` - A bucket of the other tokens.
+        pub fn swap_with_routing(
+            &mut self,
+            tokens: Bucket,
+            output_resource_address: ResourceAddress,
+            max_hops: u8,
+            min_amount_out: Decimal
+        ) -> Bucket {
+            let (route, _): (Vec<ResourceAddress>, Decimal) = self.find_best_route(
                 tokens.resource_address(), 
+                output_resource_address, 
+                tokens.amount(), 
+                max_hops
+            );
+            return self.swap_along_route(tokens, route, min_amount_out);
+        }
+
+        /// Recursively explores the routes starting at the last token in the given path.
+        /// 
+        /// This is a helper method used by `find_best_route` to perform a depth first search over the liquidity pools
+        /// in the DEX. Tokens which already exist in the path are not visited again, so no liquidity pool can be used
+        /// twice along the same route.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `path` (&mut Vec<ResourceAddress>) - The path of tokens explored so far.
+        /// * `amount` (Decimal) - The quoted amount of the last token in the path.
+        /// * `output_resource_address` (ResourceAddress) - The resource address of the token that the route must end at.
+        /// * `hops_left` (u8) - The number of liquidity pools that may still be added to the path.
+        /// * `best_route` (&mut Option<(Vec<ResourceAddress>, Decimal)>) - The best route found so far.
+        fn explore_routes(
+            &self,
+            path: &mut Vec<ResourceAddress>,
+            amount: Decimal,
+            output_resource_address: ResourceAddress,
+            hops_left: u8,
+            best_route: &mut Option<(Vec<ResourceAddress>, Decimal)>
+        ) {
+            let current: ResourceAddress = *path.last().unwrap();
+
+            // Getting all of the tokens which share a liquidity pool with the current token and have not yet been
+            // visited along this path. The same token may share multiple pools of different fee tiers with the 
+            // current token, so the neighbours are deduplicated.
+            let mut neighbours: Vec<ResourceAddress> = self.liquidity_pools.keys()
+                .filter_map(|(address1, address2, _)| {
+                    if *address1 == current { Some(*address2) } 
+                    else if *address2 == current { Some(*address1) } 
+                    else { None }
+                })
+                .filter(|address| !path.contains(address))
+                .collect::<Vec<ResourceAddress>>();
+            neighbours.sort_by_key(|address| address.to_vec());
+            neighbours.dedup();
+
+            for neighbour in neighbours {
+                let output_amount: Decimal = self.best_pool_for_input(current, neighbour, amount).1;
+
+                if neighbour == output_resource_address {
+                    // A complete route has been found, we keep it only if it gives more output than the best one so far
+                    let is_better: bool = match best_route {
+                        Some((_, best_amount)) => output_amount > *best_amount,
+                        None => true
+                    };
+                    if is_better {
+                        let mut route: Vec<ResourceAddress> = path.clone();
+                        route.push(neighbour);
+                        *best_route = Some((route, output_amount));
+                    }
+                } else if hops_left > 1 {
+                    path.push(neighbour);
+                    self.explore_routes(path, output_amount, output_resource_address, hops_left - 1, best_route);
+                    path.pop();
+                }
+            }
+        }
+
+        /// Finds the liquidity pool of the given pair which gives the largest output for the given input.
+        /// 
+        /// This method performs a number of checks before the liquidity pool is found:
+        /// 
+        /// * **Check 1:** Checks that there does exist a liquidity pool for the given pair of tokens.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `input_resource_address` (ResourceAddress) - The resource address of the input token.
+        /// * `output_resource_address` (ResourceAddress) - The resource address of the output token.
+        /// * `input_amount` (Decimal) - The amount of input tokens.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `&LiquidityPool` - The liquidity pool which gives the largest output.
+        /// * `Decimal` - The amount of output tokens that this liquidity pool gives for the input.
+        fn best_pool_for_input(
+            &self,
+            input_resource_address: ResourceAddress,
+            output_resource_address: ResourceAddress,
+            input_amount: Decimal
+        ) -> (&LiquidityPool, Decimal) {
+            self.assert_pool_exists(input_resource_address, output_resource_address, String::from("DEX Best Pool"));
+
+            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+                input_resource_address, 
+                output_resource_address
+            );
+            return self.pool_fee_tiers(input_resource_address, output_resource_address).into_iter()
+                .map(|fee_tier| {
+                    let liquidity_pool: &LiquidityPool = &self.liquidity_pools[&(sorted_addresses.0, sorted_addresses.1, fee_tier)];
+                    (liquidity_pool, liquidity_pool.calculate_output_amount(input_resource_address, input_amount))
+                })
+                .max_by(|(_, amount1), (_, amount2)| amount1.cmp(amount2))
+                .unwrap();
+        }
+
+        /// Finds the liquidity pool of the given pair which requires the least input for the given output.
+        /// 
+        /// Liquidity pools which do not have enough reserves of the output token to give the desired output are not 
+        /// considered.
+        /// 
+        /// This method performs a number of checks before the liquidity pool is found:
+        /// 
+        /// * **Check 1:** Checks that there does exist a liquidity pool for the given pair of tokens.
+        /// * **Check 2:** Checks that at least one of the liquidity pools has enough reserves for the desired output.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `input_resource_address` (ResourceAddress) - The resource address of the input token.
+        /// * `output_resource_address` (ResourceAddress) - The resource address of the output token.
+        /// * `output_amount` (Decimal) - The desired amount of output tokens.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `&LiquidityPool` - The liquidity pool which requires the least input.
+        /// * `Decimal` - The amount of input tokens that this liquidity pool requires for the output.
+        fn best_pool_for_output(
+            &self,
+            input_resource_address: ResourceAddress,
+            output_resource_address: ResourceAddress,
+            output_amount: Decimal
+        ) -> (&LiquidityPool, Decimal) {
+            self.assert_pool_exists(input_resource_address, output_resource_address, String::from("DEX Best Pool"));
+
+            let sorted_addresses: (ResourceAddress, ResourceAddress) = sort_addresses(
+                input_resource_address, 
                 output_resource_address
             );
-            return self.liquidity_pools[&sorted_addresses].swap_tokens_for_exact_tokens(tokens, output_amount);
+            return self.pool_fee_tiers(input_resource_address, output_resource_address).into_iter()
+                .map(|fee_tier| &self.liquidity_pools[&(sorted_addresses.0, sorted_addresses.1, fee_tier)])
+                .filter(|liquidity_pool| liquidity_pool.reserve(output_resource_address) > output_amount)
+                .map(|liquidity_pool| {
+                    (liquidity_pool, liquidity_pool.calculate_input_amount(output_resource_address, output_amount))
+                })
+                .min_by(|(_, amount1), (_, amount2)| amount1.cmp(amount2))
+                .expect("[DEX Best Pool]: No liquidity pool has enough liquidity for the desired output.");
+        }
+
+        /// Adds a new fee tier which liquidity pools may be created with.
+        /// 
+        /// This method is only callable by the holder of the RaDEX admin badge.
+        /// 
+        /// This method performs a number of checks before the fee tier is added:
+        /// 
+        /// * **Check 1:** Checks that the fee tier is not already one of the allowed fee tiers.
+        /// * **Check 2:** Checks that the fee tier is not more than 100%.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `fee_tier` (u16) - The new fee tier in basis points.
+        pub fn add_fee_tier(
+            &mut self,
+            fee_tier: u16
+        ) {
+            assert!(!self.fee_tiers.contains(&fee_tier), "[DEX Add Fee Tier]: The fee tier already exists.");
+            assert!(fee_tier <= 10000, "[DEX Add Fee Tier]: The fee tier can not be more than 100%.");
+            self.fee_tiers.push(fee_tier);
+        }
+
+        /// Sets the percentage of the swap fees which is paid to the protocol.
+        /// 
+        /// This method is the protocol fee switch. It sets the share of the swap fees of all of the existing liquidity
+        /// pools (and of all of the pools created afterwards) which is paid to the protocol instead of the liquidity 
+        /// providers. Setting the share to zero switches the protocol fee off. This method is only callable by the 
+        /// holder of the RaDEX admin badge.
+        /// 
+        /// This method performs a number of checks before the share is set:
+        /// 
+        /// * **Check 1:** Checks that the protocol fee share is between 0 and 100.
+        /// 
+        /// # Arguments:
+        /// 
+        /// * `protocol_fee_share` (Decimal) - A decimal value of the percentage of the swap fees which is paid to the
+        /// protocol.
+        pub fn set_protocol_fee_share(
+            &mut self,
+            protocol_fee_share: Decimal
+        ) {
+            assert!(
+                (protocol_fee_share >= Decimal::zero()) & (protocol_fee_share <= dec!("100")), 
+                "[DEX Set Protocol Fee]: Protocol fee share must be between 0 and 100"
+            );
+            self.protocol_fee_share = protocol_fee_share;
+
+            for liquidity_pool in self.liquidity_pools.values() {
+                self.pool_admin_badge.authorize(|| {
+                    liquidity_pool.set_protocol_fee_share(protocol_fee_share)
+                });
+            }
+        }
+
+        /// Collects the protocol fees accrued in all of the liquidity pools into the protocol fee vaults of RaDEX.
+        /// 
+        /// This method may be called by anybody as it only moves the protocol fees from the liquidity pools into the
+        /// RaDEX component.
+        pub fn collect_protocol_fees(&mut self) {
+            for liquidity_pool in self.liquidity_pools.values() {
+                let fees: Vec<Bucket> = self.pool_admin_badge.authorize(|| {
+                    liquidity_pool.withdraw_protocol_fees()
+                });
+
+                for bucket in fees {
+                    self.protocol_fees
+                        .entry(bucket.resource_address())
+                        .or_insert_with(|| Vault::new(bucket.resource_address()))
+                        .put(bucket);
+                }
+            }
+        }
+
+        /// Withdraws all of the protocol fees.
+        /// 
+        /// This method first collects the protocol fees accrued in all of the liquidity pools and then withdraws all 
+        /// of the protocol fees stored in RaDEX. This method is only callable by the holder of the RaDEX admin badge.
+        /// 
+        /// # Returns:
+        /// 
+        /// * `Vec<Bucket>` - A vector of buckets of the protocol fees of each of the tokens.
+        pub fn withdraw_protocol_fees(&mut self) -> Vec<Bucket> {
+            self.collect_protocol_fees();
+            return self.protocol_fees.values_mut()
+                .map(|vault| vault.take_all())
+                .filter(|bucket| !bucket.is_empty())
+                .collect::<Vec<Bucket>>();
         }
     }
 }
\ No newline at end of file
@@ -19,6 +19,11 @@ blueprint! {
                   // offer badge address, user badge address, price, Cash Vault
         user_vaults: HashMap<Address, (Vault, Vault)>, // this map hold the ressources that need to be collected:
                   // user badge address, Cash Vault, Token Vault
+        conditional_list: Vec<(Address, Address, Decimal, bool, Option<Decimal>, Option<Vault>)>, // list of dormant
+                  // conditional offers (stop-loss and take-profit): offer badge address, user badge address,
+                  // trigger price, triggered above (or below) the trigger price, limit price (None for market), Cash or
+                  // Token Vault
+        last_price: Decimal, // price of the last trade, zero if there is no trade yet
         dead_vault: Vec<Vault>, // just a vector of dead vault because we can't delete empty vault currenlty
     }
 
@@ -31,6 +36,8 @@ blueprint! {
                 bid_list: Vec::new(),
                 ask_list: Vec::new(),
                 user_vaults: HashMap::new(),
+                conditional_list: Vec::new(),
+                last_price: Decimal::zero(),
                 dead_vault: Vec::new(),
             }
             .instantiate()
@@ -70,26 +77,23 @@ blueprint! {
             create_badge("user")
         }
 
-        // this public method allow the user to add a bid offer (buy)
-        pub fn push_bid(
-            &mut self,
-            user_badge: BucketRef,
-            price: Decimal,
-            mut cash: Bucket,
-        ) -> Vec<Bucket> {
-            assert!(cash.resource_def() == self.cash_def, "wrong cash type");
-            assert!(price > Decimal::zero(), "negative or zero price");
-
+        // this helper method match the cash against the ask_list, while the ask price is inferior or equal to the price
+        //  (no limit for a market order without price), it returns the bought tokens and the cash left
+        fn match_bid(&mut self, price: Option<Decimal>, mut cash: Bucket) -> (Bucket, Bucket) {
             // prepare a new bucket to return tokens to the user if the order is executed immediately
             let mut ret_token_bucket = Bucket::new(self.token_def.clone());
             // the ask_list is sorted in decreasing order of price, so we check if the last one has a selling price 
             //  which is superior or equal to the amount our user is ready to buy
-            while !self.ask_list.is_empty() && self.ask_list[self.ask_list.len() - 1].2 <= price {
+            while !self.ask_list.is_empty()
+                && price.map_or(true, |price| self.ask_list[self.ask_list.len() - 1].2 <= price)
+            {
                 // if we enter in the loop it mean that our order will be at least partially filled
                 // we remove the most interesting offer from the list
                 let mut offer = self.ask_list.pop().unwrap();
                 let seller_badge = offer.1;
                 let ask_price = offer.2;
+                // the trade is made at the ask price, it's the new last price
+                self.last_price = ask_price;
                 // we compute the amount of token which can be buyed at this price by our buyer
                 let offer_qty = cash.amount() / ask_price;
                 let vault = offer.3.as_mut().unwrap();
@@ -103,7 +107,7 @@ blueprint! {
                     // we put back the offer in the list
                     self.ask_list.push(offer);
                     // and we return the tokens to the buyer
-                    return vec![ret_token_bucket];
+                    return (ret_token_bucket, Bucket::new(self.cash_def.clone()));
                 } else if offer_qty == vault.amount() {
                     // if there is the exact amount of token we want on the vault
 
@@ -114,7 +118,7 @@ blueprint! {
                     // we push the now empty vault in the list of dead_vault
                     self.dead_vault.push(offer.3.take().unwrap());
                     // and we return the tokens to the buyer
-                    return vec![ret_token_bucket];
+                    return (ret_token_bucket, Bucket::new(self.cash_def.clone()));
                 }
                 // if there is not enough token, we arrive in this branch
                 let qty = vault.amount();
@@ -127,52 +131,32 @@ blueprint! {
                 // we push the now empty vault in the list of dead_vault
                 self.dead_vault.push(offer.3.take().unwrap());
             }
-            if cash.amount() == Decimal::zero() {
-                return vec![ret_token_bucket];
-            }
-            // if we arrive here, the offer has not been completelly fullfilled
-            // we create a badge for our buyer
-            let badge = create_badge("bid offer");
-            // we add the offer to the bid_list
-            self.bid_list.push((
-                badge.resource_address(),
-                user_badge.resource_address(),
-                price,
-                Some(Vault::with_bucket(cash)),
-            ));
-            // we sort the list
-            self.bid_list.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
-            // and we return the tokens (if it has been partially filled) and offer badge
-            vec![ret_token_bucket, badge]
+            (ret_token_bucket, cash)
         }
 
-        // this public method allow the user to add a ask offer (sell)
-        // logic is similar to the push_bid method, so no comments
-        pub fn push_ask(
-            &mut self,
-            user_badge: BucketRef,
-            price: Decimal,
-            mut token: Bucket,
-        ) -> Vec<Bucket> {
-            assert!(token.resource_def() == self.token_def, "wrong token type");
-            assert!(price > Decimal::zero(), "negative or zero price");
+        // this helper method match the tokens against the bid_list, logic is similar to the match_bid method, so no
+        //  comments
+        fn match_ask(&mut self, price: Option<Decimal>, mut token: Bucket) -> (Bucket, Bucket) {
             let mut ret_cash_bucket = Bucket::new(self.cash_def.clone());
-            while !self.bid_list.is_empty() && self.bid_list[self.bid_list.len() - 1].2 >= price {
+            while !self.bid_list.is_empty()
+                && price.map_or(true, |price| self.bid_list[self.bid_list.len() - 1].2 >= price)
+            {
                 let mut offer = self.bid_list.pop().unwrap();
                 let buyer_badge = offer.1;
                 let bid_price = offer.2;
+                self.last_price = bid_price;
                 let offer_cost = token.amount() * bid_price;
                 let vault = offer.3.as_mut().unwrap();
                 if offer_cost < vault.amount() {
                     self.add_token_to_user(buyer_badge, token);
                     ret_cash_bucket.put(vault.take(offer_cost));
                     self.bid_list.push(offer);
-                    return vec![ret_cash_bucket];
+                    return (ret_cash_bucket, Bucket::new(self.token_def.clone()));
                 } else if offer_cost == vault.amount() {
                     self.add_token_to_user(buyer_badge, token);
                     ret_cash_bucket.put(vault.take_all());
                     self.dead_vault.push(offer.3.take().unwrap());
-                    return vec![ret_cash_bucket];
+                    return (ret_cash_bucket, Bucket::new(self.token_def.clone()));
                 }
                 let cash = vault.amount();
                 let qty = cash / bid_price;
@@ -180,18 +164,190 @@ blueprint! {
                 ret_cash_bucket.put(vault.take_all());
                 self.dead_vault.push(offer.3.take().unwrap());
             }
-            if token.amount() == Decimal::zero() {
-                return vec![ret_cash_bucket];
+            (ret_cash_bucket, token)
+        }
+
+        // this helper method add a resting bid offer to the bid_list and keep it sorted
+        fn add_bid(&mut self, offer_badge: Address, user_badge: Address, price: Decimal, cash: Bucket) {
+            self.bid_list.push((offer_badge, user_badge, price, Some(Vault::with_bucket(cash))));
+            self.bid_list.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+        }
+
+        // this helper method add a resting ask offer to the ask_list and keep it sorted
+        fn add_ask(&mut self, offer_badge: Address, user_badge: Address, price: Decimal, token: Bucket) {
+            self.ask_list.push((offer_badge, user_badge, price, Some(Vault::with_bucket(token))));
+            self.ask_list.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap());
+        }
+
+        // this helper method tell if a conditional offer is triggered by the last traded price
+        fn is_triggered(&self, trigger_price: Decimal, above: bool) -> bool {
+            if self.last_price == Decimal::zero() {
+                // no trade yet
+                return false;
+            }
+            if above {
+                self.last_price >= trigger_price
+            } else {
+                self.last_price <= trigger_price
+            }
+        }
+
+        // this helper method convert the triggered conditional offers into limit or market offers
+        // the executed offers can change the last price and trigger other offers, so we loop until there is no more
+        //  triggered offer
+        fn trigger_conditional_offers(&mut self) {
+            loop {
+                let index = self
+                    .conditional_list
+                    .iter()
+                    .position(|offer| self.is_triggered(offer.2, offer.3));
+                if index.is_none() {
+                    return;
+                }
+                let mut offer = self.conditional_list.remove(index.unwrap());
+                let (offer_badge, user_badge, limit_price) = (offer.0, offer.1, offer.4);
+                // we empty the vault of the offer and push it in the list of dead_vault
+                let mut vault = offer.5.take().unwrap();
+                let bucket = vault.take_all();
+                self.dead_vault.push(vault);
+                // the owner of the offer isn't the caller, so all the resources go in the user vaults
+                if bucket.resource_def() == self.cash_def {
+                    let (token, cash) = self.match_bid(limit_price, bucket);
+                    self.add_token_to_user(user_badge, token);
+                    if cash.amount() > Decimal::zero() {
+                        match limit_price {
+                            // a limit offer stay in the book with the same offer badge, so it can be cancelled
+                            Some(price) => self.add_bid(offer_badge, user_badge, price, cash),
+                            // the cash left by a market offer is returned to the user
+                            None => self.add_cash_to_user(user_badge, cash),
+                        }
+                    }
+                } else {
+                    let (cash, token) = self.match_ask(limit_price, bucket);
+                    self.add_cash_to_user(user_badge, cash);
+                    if token.amount() > Decimal::zero() {
+                        match limit_price {
+                            Some(price) => self.add_ask(offer_badge, user_badge, price, token),
+                            None => self.add_token_to_user(user_badge, token),
+                        }
+                    }
+                }
+            }
+        }
+
+        // this public method allow the user to add a bid offer (buy)
+        pub fn push_bid(
+            &mut self,
+            user_badge: BucketRef,
+            price: Decimal,
+            cash: Bucket,
+        ) -> Vec<Bucket> {
+            assert!(cash.resource_def() == self.cash_def, "wrong cash type");
+            assert!(price > Decimal::zero(), "negative or zero price");
+
+            let (ret_token_bucket, cash) = self.match_bid(Some(price), cash);
+            let mut ret = vec![ret_token_bucket];
+            if cash.amount() > Decimal::zero() {
+                // if we arrive here, the offer has not been completelly fullfilled
+                // we create a badge for our buyer
+                let badge = create_badge("bid offer");
+                // we add the offer to the bid_list
+                self.add_bid(badge.resource_address(), user_badge.resource_address(), price, cash);
+                ret.push(badge);
             }
-            let badge = create_badge("ask offer");
-            self.ask_list.push((
+            // the trades may have triggered some conditional offers
+            self.trigger_conditional_offers();
+            // and we return the tokens (if it has been partially filled) and offer badge
+            ret
+        }
+
+        // this public method allow the user to add a ask offer (sell)
+        // logic is similar to the push_bid method, so no comments
+        pub fn push_ask(
+            &mut self,
+            user_badge: BucketRef,
+            price: Decimal,
+            token: Bucket,
+        ) -> Vec<Bucket> {
+            assert!(token.resource_def() == self.token_def, "wrong token type");
+            assert!(price > Decimal::zero(), "negative or zero price");
+            let (ret_cash_bucket, token) = self.match_ask(Some(price), token);
+            let mut ret = vec![ret_cash_bucket];
+            if token.amount() > Decimal::zero() {
+                let badge = create_badge("ask offer");
+                self.add_ask(badge.resource_address(), user_badge.resource_address(), price, token);
+                ret.push(badge);
+            }
+            self.trigger_conditional_offers();
+            ret
+        }
+
+        // this public method allow the user to add a market offer, cash for a buy and token for a sell, it is matched
+        //  whatever the price and what can't be matched is returned instead of staying in the book
+        pub fn push_market(&mut self, bucket: Bucket) -> Vec<Bucket> {
+            let ret = if bucket.resource_def() == self.cash_def {
+                let (token, cash) = self.match_bid(None, bucket);
+                vec![token, cash]
+            } else {
+                assert!(bucket.resource_def() == self.token_def, "wrong token or cash type");
+                let (cash, token) = self.match_ask(None, bucket);
+                vec![cash, token]
+            };
+            self.trigger_conditional_offers();
+            ret
+        }
+
+        // this helper method add a dormant conditional offer, cash for a buy and token for a sell
+        // a stop-loss sell (or a take-profit buy) is triggered when the last price goes below the trigger price
+        // a take-profit sell (or a stop-loss buy) is triggered when the last price goes above the trigger price
+        fn push_conditional(
+            &mut self,
+            user_badge: BucketRef,
+            trigger_price: Decimal,
+            limit_price: Decimal,
+            bucket: Bucket,
+            stop_loss: bool,
+        ) -> Bucket {
+            let buy = bucket.resource_def() == self.cash_def;
+            assert!(buy || bucket.resource_def() == self.token_def, "wrong token or cash type");
+            assert!(trigger_price > Decimal::zero(), "negative or zero trigger price");
+            assert!(limit_price >= Decimal::zero(), "negative limit price");
+            let above = stop_loss == buy;
+            assert!(!self.is_triggered(trigger_price, above), "trigger price already crossed");
+            let badge = create_badge(if stop_loss { "stop-loss offer" } else { "take-profit offer" });
+            self.conditional_list.push((
                 badge.resource_address(),
                 user_badge.resource_address(),
-                price,
-                Some(Vault::with_bucket(token)),
+                trigger_price,
+                above,
+                if limit_price == Decimal::zero() { None } else { Some(limit_price) },
+                Some(Vault::with_bucket(bucket)),
             ));
-            self.ask_list.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap());
-            vec![ret_cash_bucket, badge]
+            badge
+        }
+
+        // this public method allow the user to add a stop-loss offer, it stays dormant until the last traded price
+        //  crosses the trigger price, then it becomes a limit offer at limit price (or a market offer if the limit
+        //  price is zero)
+        pub fn push_stop_loss(
+            &mut self,
+            user_badge: BucketRef,
+            trigger_price: Decimal,
+            limit_price: Decimal,
+            bucket: Bucket,
+        ) -> Bucket {
+            self.push_conditional(user_badge, trigger_price, limit_price, bucket, true)
+        }
+
+        // this public method allow the user to add a take-profit offer, logic is similar to the push_stop_loss method
+        pub fn push_take_profit(
+            &mut self,
+            user_badge: BucketRef,
+            trigger_price: Decimal,
+            limit_price: Decimal,
+            bucket: Bucket,
+        ) -> Bucket {
+            self.push_conditional(user_badge, trigger_price, limit_price, bucket, false)
         }
 
         // this method allow the user to cancel an offer
@@ -229,6 +385,22 @@ blueprint! {
                     offer.2 = Decimal::zero();
                 });
             self.ask_list.retain(|offer| offer.2 > Decimal::zero());
+            // and for the dormant conditional offers, which can contain cash or tokens
+            self.conditional_list
+                .iter_mut()
+                .filter(|offer| offer.0 == offer_badge.resource_address())
+                .for_each(|mut offer| {
+                    let vault_option = offer.5.take();
+                    let mut vault = vault_option.unwrap();
+                    if vault.resource_def() == self.cash_def {
+                        cash_bucket.put(vault.take_all());
+                    } else {
+                        token_bucket.put(vault.take_all());
+                    }
+                    self.dead_vault.push(vault);
+                    offer.2 = Decimal::zero();
+                });
+            self.conditional_list.retain(|offer| offer.2 > Decimal::zero());
             // the badge is now useless, we can burn it
             offer_badge.burn();
             // and we return to our caller the tokens and cash stored in the cancelled offer
@@ -267,6 +439,53 @@ blueprint! {
             }
         }
 
+        // this method return the price of the last trade, zero if there is no trade yet
+        pub fn last_price(&self) -> Decimal {
+            self.last_price
+        }
+
+        // this method return the highest bid price and the lowest ask price, None if there is no offer on the side
+        pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            // both lists are sorted so the most interesting offer is the last one
+            (self.bid_list.last().map(|offer| offer.2), self.ask_list.last().map(|offer| offer.2))
+        }
+
+        // this helper method aggregate the offers of a list by price, starting from the most interesting one
+        // the amount is always in tokens, so the cash of the bid offers is converted at the offer price
+        fn depth_of(
+            list: &Vec<(Address, Address, Decimal, Option<Vault>)>,
+            levels: usize,
+            bid: bool,
+        ) -> Vec<(Decimal, Decimal, u32)> {
+            let mut depth: Vec<(Decimal, Decimal, u32)> = Vec::new();
+            for offer in list.iter().rev() {
+                let vault_amount = offer.3.as_ref().map_or(Decimal::zero(), |v| v.amount());
+                let qty = if bid { vault_amount / offer.2 } else { vault_amount };
+                match depth.last_mut() {
+                    Some(level) if level.0 == offer.2 => {
+                        level.1 += qty;
+                        level.2 += 1;
+                    }
+                    _ => {
+                        if depth.len() == levels {
+                            break;
+                        }
+                        depth.push((offer.2, qty, 1));
+                    }
+                }
+            }
+            depth
+        }
+
+        // this method return the `levels` best price levels of each side: price, quantity of tokens and number of
+        //  offers, bids from the highest price and asks from the lowest price
+        pub fn depth(&self, levels: u32) -> (Vec<(Decimal, Decimal, u32)>, Vec<(Decimal, Decimal, u32)>) {
+            (
+                CroqOrderBook::depth_of(&self.bid_list, levels as usize, true),
+                CroqOrderBook::depth_of(&self.ask_list, levels as usize, false),
+            )
+        }
+
         // this method is used to monitor the current state of the auction, we can easily build a UI around it to
         //  display the order book
         pub fn monitor(&self) {
@@ -292,6 +511,19 @@ blueprint! {
                     item.3.as_ref().map_or(Decimal::zero(), |v| v.amount())
                 );
             });
+
+            info!("**conditional**");
+            info!("last price: {:?}", self.last_price);
+            info!("trigger price, above, limit price, amount of cash or tokens");
+            self.conditional_list.iter().for_each(|item| {
+                info!(
+                    "{:?}, {:?}, {:?}, {:?}",
+                    item.2,
+                    item.3,
+                    item.4,
+                    item.5.as_ref().map_or(Decimal::zero(), |v| v.amount())
+                );
+            });
         }
     }
 }
@@ -331,6 +331,103 @@ impl<'a, L: SubstateStore> TestEnv<'a, L> {
         assert!(!receipt.result.is_ok());
     }
 
+    fn push_market(&mut self, amount: &str, resource: Address, actor: (EcdsaPublicKey, Address)) {
+        let receipt = self
+            .executor
+            .run(
+                TransactionBuilder::new(&self.executor)
+                    .call_method(
+                        self.instance,
+                        "push_market",
+                        vec![format!("{},{}", amount, resource)],
+                        Some(actor.1),
+                    )
+                    .call_method_with_all_resources(actor.1, "deposit_batch")
+                    .build(vec![actor.0])
+                    .unwrap(),
+            )
+            .unwrap();
+        println!("{:?}\n", receipt);
+        assert!(receipt.result.is_ok());
+        assert!(receipt.resource_def(0).is_none());
+    }
+
+    fn push_conditional_no_check(
+        &mut self,
+        method: &str,
+        user_badge: Address,
+        trigger_price: &str,
+        limit_price: &str,
+        amount: &str,
+        resource: Address,
+        actor: (EcdsaPublicKey, Address),
+    ) -> Receipt {
+        let receipt = self
+            .executor
+            .run(
+                TransactionBuilder::new(&self.executor)
+                    .call_method(
+                        self.instance,
+                        method,
+                        vec![
+                            format!("{},{}", 1, user_badge),
+                            format!("{}", trigger_price),
+                            format!("{}", limit_price),
+                            format!("{},{}", amount, resource),
+                        ],
+                        Some(actor.1),
+                    )
+                    .call_method_with_all_resources(actor.1, "deposit_batch")
+                    .build(vec![actor.0])
+                    .unwrap(),
+            )
+            .unwrap();
+        println!("{:?}\n", receipt);
+        receipt
+    }
+    fn push_stop_loss(
+        &mut self,
+        user_badge: Address,
+        trigger_price: &str,
+        limit_price: &str,
+        amount: &str,
+        resource: Address,
+        actor: (EcdsaPublicKey, Address),
+    ) -> Address {
+        let receipt = self.push_conditional_no_check(
+            "push_stop_loss",
+            user_badge,
+            trigger_price,
+            limit_price,
+            amount,
+            resource,
+            actor,
+        );
+        assert!(receipt.result.is_ok());
+        return receipt.resource_def(0).unwrap();
+    }
+    fn push_take_profit(
+        &mut self,
+        user_badge: Address,
+        trigger_price: &str,
+        limit_price: &str,
+        amount: &str,
+        resource: Address,
+        actor: (EcdsaPublicKey, Address),
+    ) -> Address {
+        let receipt = self.push_conditional_no_check(
+            "push_take_profit",
+            user_badge,
+            trigger_price,
+            limit_price,
+            amount,
+            resource,
+            actor,
+        );
+        assert!(receipt.result.is_ok());
+        return receipt.resource_def(0).unwrap();
+    }
+
     fn cancel_no_check(&mut self, badge: Address, actor: (EcdsaPublicKey, Address)) -> Receipt {
         let receipt = self
             .executor
@@ -710,3 +807,72 @@ fn test_sorting_bid() {
     env.check_wallet(actors[3].1, "0", "-20", "unexpected wallet content");
     env.check_wallet(actors[4].1, "-30", "140", "unexpected wallet content");
 }
+
+#[test]
+fn test_stop_loss_market() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut env, actors) = TestEnv::new(&mut ledger);
+
+    let user_badge0 = env.register(actors[0]);
+    let user_badge1 = env.register(actors[1]);
+    let user_badge2 = env.register(actors[2]);
+    let user_badge3 = env.register(actors[3]);
+    let token = env.token;
+    env.push_bid(user_badge0, "4", "40", actors[0]);
+    env.push_stop_loss(user_badge1, "5", "0", "10", token, actors[1]);
+    env.push_bid(user_badge3, "5", "25", actors[3]);
+    env.push_ask_no_badge(user_badge2, "5", "5", actors[2]); // trade @5 trigger the stop-loss which sell @4
+    env.withdraw(user_badge0, actors[0]);
+    env.withdraw(user_badge1, actors[1]);
+    env.withdraw(user_badge3, actors[3]);
+    env.check_wallet(actors[0].1, "10", "-40", "unexpected wallet content");
+    env.check_wallet(actors[1].1, "-10", "40", "unexpected wallet content");
+    env.check_wallet(actors[2].1, "-5", "25", "unexpected wallet content");
+    env.check_wallet(actors[3].1, "5", "-25", "unexpected wallet content");
+}
+
+#[test]
+fn test_take_profit_limit_then_cancel() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut env, actors) = TestEnv::new(&mut ledger);
+
+    let user_badge0 = env.register(actors[0]);
+    let user_badge1 = env.register(actors[1]);
+    let user_badge2 = env.register(actors[2]);
+    let token = env.token;
+    let offer_badge = env.push_take_profit(user_badge0, "6", "6", "10", token, actors[0]);
+    env.check_wallet(actors[0].1, "-10", "0", "unexpected wallet content");
+    env.push_ask(user_badge1, "6", "5", actors[1]);
+    env.push_bid_no_badge(user_badge2, "6", "30", actors[2]); // trade @6 trigger the take-profit which stay in the book
+    env.cancel(offer_badge, actors[0]);
+    env.check_wallet(actors[0].1, "0", "0", "unexpected wallet content");
+}
+
+#[test]
+fn test_cancel_dormant_conditional() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut env, actors) = TestEnv::new(&mut ledger);
+
+    let user_badge0 = env.register(actors[0]);
+    let offer_badge = env.push_stop_loss(user_badge0, "5", "0", "50", RADIX_TOKEN, actors[0]);
+    env.check_wallet(actors[0].1, "0", "-50", "unexpected wallet content");
+    env.cancel(offer_badge, actors[0]);
+    env.check_wallet(actors[0].1, "0", "0", "unexpected wallet content");
+}
+
+#[test]
+fn test_market_bid_sweeps_asks_and_returns_rest() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut env, actors) = TestEnv::new(&mut ledger);
+
+    let user_badge0 = env.register(actors[0]);
+    let user_badge1 = env.register(actors[1]);
+    env.push_ask(user_badge0, "5", "10", actors[0]);
+    env.push_ask(user_badge1, "6", "20", actors[1]);
+    env.push_market("200", RADIX_TOKEN, actors[2]); // buy all the asks, the 30 cash left is returned
+    env.withdraw(user_badge0, actors[0]);
+    env.withdraw(user_badge1, actors[1]);
+    env.check_wallet(actors[0].1, "-10", "50", "unexpected wallet content");
+    env.check_wallet(actors[1].1, "-20", "120", "unexpected wallet content");
+    env.check_wallet(actors[2].1, "30", "-170", "unexpected wallet content");
+}
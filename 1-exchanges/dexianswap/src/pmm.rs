@@ -21,7 +21,12 @@ blueprint! {
         _i: Decimal,
         _k: Decimal,
         _fee: Decimal,
-        lp_token_def: ResourceDef
+        lp_token_def: ResourceDef,
+        admin_badge_def: ResourceDef,
+        // oracle component, method and pair used to update the guide price `_i` on each trade
+        // the method takes the pair and returns (price, epoch) like DexianOracle `get_price`
+        // a price older than the max age in epochs is rejected
+        oracle: Option<(Address, String, String, u64)>
     }
 
     impl PMMPool{
@@ -34,7 +39,7 @@ blueprint! {
             lp_name: String,
             lp_url: String,
             lp_initial_supply: Decimal
-        ) -> (Component, Bucket){
+        ) -> (Component, Bucket, Bucket){
             assert!(
                 !base_tokens.is_empty() && !quote_tokens.is_empty(),
                 "You must pass in an initial supply of each token."
@@ -45,8 +50,17 @@ blueprint! {
                 "Invalid fee in thousandths"
             );
 
+            assert!(
+                k >= Decimal::zero() && k <= Decimal::one(),
+                "Invalid k, must be between 0 and 1"
+            );
+
             let mid_price = base_tokens.amount() / quote_tokens.amount();
 
+            let admin_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "PMM Pool Admin Badge")
+                .initial_supply_fungible(Decimal::one());
+
             let lp_minter_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
                 .metadata("name", "LP Token Mint Auth")
                 .metadata("symbol", "LP")
@@ -75,11 +89,95 @@ blueprint! {
                 _i:mid_price,
                 _k:k,
                 _fee:fee,
-                lp_token_def
+                lp_token_def,
+                admin_badge_def: admin_badge.resource_def(),
+                oracle: None
             }
             .instantiate();
 
-            (pmm_pool, lp_tokens)
+            (pmm_pool, lp_tokens, admin_badge)
+        }
+
+        // set the oracle used to update the guide price on each trade, trades fail while its price is older than max_age_epochs
+        #[auth(admin_badge_def)]
+        pub fn set_oracle(
+            &mut self,
+            oracle_component: Address,
+            oracle_method: String,
+            pair: String,
+            max_age_epochs: u64
+        ) {
+            self.oracle = Some((oracle_component, oracle_method, pair, max_age_epochs));
+            self.update_guide_price();
+        }
+
+        // retune the curve slope k and the fee, the targets are re-derived with the new k
+        #[auth(admin_badge_def)]
+        pub fn set_parameters(
+            &mut self,
+            k: Decimal,
+            fee: Decimal
+        ) {
+            assert!(
+                fee >= Decimal::zero() && fee <= Decimal::one(),
+                "Invalid fee, must be between 0 and 1"
+            );
+
+            assert!(
+                k >= Decimal::zero() && k <= Decimal::one(),
+                "Invalid k, must be between 0 and 1"
+            );
+
+            self._k = k;
+            self._fee = fee;
+            self.adjust_target();
+        }
+
+        pub fn get_parameters(&self) -> (Decimal, Decimal, Decimal, RState) {
+            (self._i, self._k, self._fee, self._r_state)
+        }
+
+        // read the guide price from the oracle (if any) and re-derive the targets
+        fn update_guide_price(&mut self) {
+            if let Some((oracle_component, oracle_method, pair, max_age_epochs)) = &self.oracle {
+                let (price, epoch): (Decimal, u64) = Component::from(*oracle_component)
+                    .call(oracle_method, args![pair.clone()]);
+                assert!(price > Decimal::zero(), "Invalid oracle price");
+                assert!(
+                    Context::current_epoch() <= epoch + *max_age_epochs,
+                    "Stale oracle price"
+                );
+                self._i = price;
+                self.adjust_target();
+            }
+        }
+
+        // re-derive base0/quote0 targets and RState from the current balances, i and k
+        fn adjust_target(&mut self) {
+            let b = self.base_vault.amount();
+            let q = self.quote_vault.amount();
+            match self._r_state {
+                RState::BelowOne => {
+                    self.quote0_amnt = PMMPool::solve_quadratic_function_for_target(
+                        q,
+                        b - self.base0_amnt,
+                        self._i,
+                        self._k
+                    );
+                },
+                RState::AboveOne => {
+                    self.base0_amnt = PMMPool::solve_quadratic_function_for_target(
+                        b,
+                        q - self.quote0_amnt,
+                        Decimal::one() / self._i,
+                        self._k
+                    );
+                },
+                RState::One => {}
+            }
+            if b == self.base0_amnt && q == self.quote0_amnt {
+                self._r_state = RState::One;
+            }
         }
 
         pub fn add_liquidity(
@@ -135,6 +233,7 @@ blueprint! {
             &mut self,
             base_bucket: Bucket
         ) -> Bucket {
+            self.update_guide_price();
             let pay_base_amnt = base_bucket.amount();
             let (quote_amnt, new_r_state) = PMMPool::sell_base_token(
                 pay_base_amnt,
@@ -156,6 +255,7 @@ blueprint! {
             &mut self,
             quote_bucket: Bucket
         ) -> Bucket {
+            self.update_guide_price();
             let pay_quote_amnt = quote_bucket.amount();
             let (quote_amnt, new_r_state) = PMMPool::sell_quote_token(
                 pay_quote_amnt,
@@ -4,7 +4,14 @@ use scrypto::prelude::*;
 struct OrdenData {
     #[scrypto(mutable)]
     retirado: bool,
-    vendido: bool
+    #[scrypto(mutable)]
+    vendido: bool,
+    // activo de venta que queda en la orden
+    #[scrypto(mutable)]
+    restante: Decimal,
+    // pago recibido por las ejecuciones de la orden
+    #[scrypto(mutable)]
+    cobrado: Decimal
 }
 
 blueprint! {
@@ -14,38 +21,49 @@ blueprint! {
 
         identification_minter: Vault,
         identification_nft_def: ResourceDef,
-        identification_admin_def: ResourceDef,   
+        identification_admin_def: ResourceDef,
 
         comision: Decimal,
-        caja_comision: Vault
+        caja_comision: Vault,
+        // porcentaje cobrado en el activo negociado cuando no se paga la comisión en XRD
+        comision_activo: Decimal,
+        cajas_comision_activo: HashMap<Address, Vault>
         }
 
     impl Dexp2p {
         pub fn new(fee: Decimal) -> (Component, Bucket) {
-           
+            // porcentaje cobrado en el activo negociado por defecto
+            Self::new_with_asset_fee(fee, dec!("0.001"))
+        }
+
+        pub fn new_with_asset_fee(fee: Decimal, asset_fee: Decimal) -> (Component, Bucket) {
+            assert!(asset_fee >= Decimal::zero() && asset_fee < Decimal::one(), "Comisión en activo no válida");
+
             let identification_admin: Bucket = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
                 .metadata("name", "Admin DexP2p")
                 .initial_supply_fungible(1);
-           
+
             let identification_minter: Bucket = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
                 .metadata("name", "Autorización para acuñar DexP2p")
                 .initial_supply_fungible(1);
-          
+
             let identification_nft_def: ResourceDef = ResourceBuilder::new_non_fungible()
                 .flags(MINTABLE|BURNABLE|INDIVIDUAL_METADATA_MUTABLE)
                 .badge(identification_minter.resource_def(), MAY_MINT|MAY_BURN|MAY_CHANGE_INDIVIDUAL_METADATA)
                 .no_initial_supply();
-            
+
             let comp = Self {
                 registro_orden: HashMap::new(),
                 registro_ventas: HashMap::new(),
                 comision: fee,
                 caja_comision: Vault::new(RADIX_TOKEN),
+                comision_activo: asset_fee,
+                cajas_comision_activo: HashMap::new(),
                 identification_minter: Vault::with_bucket(identification_minter),
                 identification_nft_def: identification_nft_def,
                 identification_admin_def: identification_admin.resource_def()
             }
-            
+
             .instantiate();
 
             (comp, identification_admin)
@@ -56,76 +74,219 @@ blueprint! {
             self.comision = new_fee;
         }
 
+        #[auth(identification_admin_def)]
+        pub fn cambiar_fee_activo(&mut self, new_fee: Decimal) {
+            assert!(new_fee >= Decimal::zero() && new_fee < Decimal::one(), "Comisión en activo no válida");
+            self.comision_activo = new_fee;
+        }
+
         pub fn nueva_orden(&mut self, activo_comprar: Address, activo_vender: Bucket, precio_compra: Decimal, mut fee_xrd: Bucket) -> (Bucket, Bucket) {
 
-            assert!(fee_xrd.amount() >= self.comision , "Comisión insuficiente");  
+            assert!(fee_xrd.amount() >= self.comision , "Comisión insuficiente");
             self.caja_comision.put(fee_xrd.take(self.comision));
 
-            let badge = self.identification_minter.authorize(|auth| {
-                self.identification_nft_def.mint_non_fungible(&NonFungibleKey::from(Uuid::generate()), OrdenData{retirado: false, vendido: false}, auth)
-            });
-        
-            let activo: Vault = Vault::with_bucket(activo_vender);
+            let badge = self.crear_orden(activo_comprar, activo_vender, precio_compra);
 
-            self.registro_orden.insert(badge.get_non_fungible_key(),(activo_comprar, activo ,precio_compra));  
-            
             (fee_xrd, badge)
         }
 
+        // la comisión se descuenta del activo de venta
+        pub fn nueva_orden_fee_activo(&mut self, activo_comprar: Address, mut activo_vender: Bucket, precio_compra: Decimal) -> Bucket {
+            let fee = activo_vender.amount() * self.comision_activo;
+            self.cobrar_comision_activo(activo_vender.take(fee));
+
+            self.crear_orden(activo_comprar, activo_vender, precio_compra)
+        }
+
         pub fn eliminar_orden(&mut self, orden_badge: Bucket) -> Bucket {
             assert!(orden_badge.resource_def() == self.identification_nft_def, "No es una valida");
             assert!(orden_badge.amount() == Decimal::one(), "Solo puedes eliminar una orden");
             assert!(self.registro_orden.contains_key(&orden_badge.get_non_fungible_key()), "No existe la orden");
+            assert!(
+                self.registro_ventas.get(&orden_badge.get_non_fungible_key()).map_or(true, |ventas| ventas.is_empty()),
+                "Retira primero lo cobrado por la orden"
+            );
 
             let orden = self.registro_orden.get_mut(&NonFungibleKey::from(orden_badge.get_non_fungible_key())).unwrap().1.take_all();
 
             self.identification_minter.authorize(|auth| self.identification_nft_def.burn_with_auth(orden_badge, auth));
-            
+
             orden
         }
 
-        pub fn ejecutar_orden(&mut self, identificador: NonFungibleKey, mut pago: Bucket, mut fee_xrd: Bucket) -> (Bucket, Bucket, Bucket) {
+        pub fn ejecutar_orden(&mut self, identificador: NonFungibleKey, pago: Bucket, mut fee_xrd: Bucket) -> (Bucket, Bucket, Bucket) {
             assert!(self.registro_orden.contains_key(&identificador), "No existe la orden");
-            
-            assert!(fee_xrd.amount() >= self.comision , "Comisión insuficiente");  
-            
-            let orden = self.registro_orden.get_mut(&identificador).unwrap();
 
-            assert!(orden.1.amount() > Decimal::zero() , "Esta orden ya ha sido ejecutada");
-            
+            assert!(fee_xrd.amount() >= self.comision , "Comisión insuficiente");
+
+            let cantidad: Decimal = self.registro_orden.get(&identificador).unwrap().1.amount();
+
+            assert!(cantidad > Decimal::zero() , "Esta orden ya ha sido ejecutada");
+
             self.caja_comision.put(fee_xrd.take(self.comision));
 
-            let cantidad: Decimal = orden.1.amount() / orden.2;
-            assert_eq!(orden.0, pago.resource_address(), "El pago no coincide con el activo de venta"); 
-            assert!(pago.amount() >= cantidad, "Saldo insuficiente");  
-            //vendedor
-            self.registro_ventas.insert(identificador.clone(), Vault::with_bucket(pago.take(cantidad)));  
-            //comprador
-            let badge = self.identification_minter.authorize(|auth| {
-                self.identification_nft_def.mint_non_fungible(&NonFungibleKey::from(Uuid::generate()), OrdenData{retirado: false, vendido: true}, auth)
-            });
+            let (pago, badge) = self.llenar_orden(&identificador, cantidad, pago);
+
+            (pago, fee_xrd, badge)
+        }
 
-            self.registro_ventas.insert(NonFungibleKey::from(badge.get_non_fungible_key()), Vault::with_bucket(orden.1.take_all()));
+        // compra solo `cantidad` del activo de venta, la orden sigue abierta con el resto
+        pub fn ejecutar_orden_parcial(&mut self, identificador: NonFungibleKey, cantidad: Decimal, pago: Bucket, mut fee_xrd: Bucket) -> (Bucket, Bucket, Bucket) {
+            assert!(self.registro_orden.contains_key(&identificador), "No existe la orden");
 
-            self.identification_minter
-                .authorize(|auth| self.identification_nft_def.update_non_fungible_data(&identificador, OrdenData{retirado: false, vendido: true} , auth));
+            assert!(fee_xrd.amount() >= self.comision , "Comisión insuficiente");
+            self.caja_comision.put(fee_xrd.take(self.comision));
 
+            let (pago, badge) = self.llenar_orden(&identificador, cantidad, pago);
 
             (pago, fee_xrd, badge)
         }
 
+        // la comisión se cobra del pago, además del precio de `cantidad`
+        pub fn ejecutar_orden_fee_activo(&mut self, identificador: NonFungibleKey, cantidad: Decimal, mut pago: Bucket) -> (Bucket, Bucket) {
+            assert!(self.registro_orden.contains_key(&identificador), "No existe la orden");
+
+            let fee = cantidad / self.registro_orden.get(&identificador).unwrap().2 * self.comision_activo;
+            assert!(pago.amount() >= fee, "Saldo insuficiente");
+            self.cobrar_comision_activo(pago.take(fee));
+
+            self.llenar_orden(&identificador, cantidad, pago)
+        }
+
+        // retira las comisiones cobradas en el activo indicado
+        #[auth(identification_admin_def)]
+        pub fn retirar_comisiones_activo(&mut self, activo: Address) -> Bucket {
+            match self.cajas_comision_activo.get_mut(&activo) {
+                Some(caja) => caja.take_all(),
+                None => Bucket::new(activo)
+            }
+        }
+
         pub fn retirar_orden(&mut self, orden_badge: BucketRef) -> Bucket {
             assert!(orden_badge.resource_def() == self.identification_nft_def, "No es una valida");
             assert!(orden_badge.amount() == Decimal::one(), "Solo puedes realizar un retiro a la vez");
             assert!(self.registro_ventas.contains_key(&orden_badge.get_non_fungible_key()), "No existe el retiro");
 
-            let retorno = self.registro_ventas.get_mut(&orden_badge.get_non_fungible_key()).unwrap();
+            let identificador = orden_badge.get_non_fungible_key();
+            let retorno = self.registro_ventas.get_mut(&identificador).unwrap();
 
             assert!(retorno.amount() > Decimal::zero(), "Esta orden ya fue retirada");
 
-            self.identification_minter.authorize(|auth| self.identification_nft_def.update_non_fungible_data(&orden_badge.get_non_fungible_key(), OrdenData{retirado: true, vendido: true}, auth));
+            let mut data: OrdenData = self.identification_nft_def.get_non_fungible_data(&identificador);
+            data.retirado = true;
+            let nft_def = self.identification_nft_def.clone();
+            self.identification_minter.authorize(|auth| nft_def.update_non_fungible_data(&identificador, data, auth));
 
             retorno.take_all()
         }
+
+        /* --- English names --- */
+        pub fn new_order(&mut self, buy_asset: Address, sell_asset: Bucket, buy_price: Decimal, fee_xrd: Bucket) -> (Bucket, Bucket) {
+            self.nueva_orden(buy_asset, sell_asset, buy_price, fee_xrd)
+        }
+
+        pub fn new_order_asset_fee(&mut self, buy_asset: Address, sell_asset: Bucket, buy_price: Decimal) -> Bucket {
+            self.nueva_orden_fee_activo(buy_asset, sell_asset, buy_price)
+        }
+
+        pub fn cancel_order(&mut self, order_badge: Bucket) -> Bucket {
+            self.eliminar_orden(order_badge)
+        }
+
+        pub fn execute_order(&mut self, order_id: NonFungibleKey, payment: Bucket, fee_xrd: Bucket) -> (Bucket, Bucket, Bucket) {
+            self.ejecutar_orden(order_id, payment, fee_xrd)
+        }
+
+        pub fn execute_partial_order(&mut self, order_id: NonFungibleKey, amount: Decimal, payment: Bucket, fee_xrd: Bucket) -> (Bucket, Bucket, Bucket) {
+            self.ejecutar_orden_parcial(order_id, amount, payment, fee_xrd)
+        }
+
+        pub fn execute_order_asset_fee(&mut self, order_id: NonFungibleKey, amount: Decimal, payment: Bucket) -> (Bucket, Bucket) {
+            self.ejecutar_orden_fee_activo(order_id, amount, payment)
+        }
+
+        pub fn withdraw_order(&mut self, order_badge: BucketRef) -> Bucket {
+            self.retirar_orden(order_badge)
+        }
+
+        #[auth(identification_admin_def)]
+        pub fn change_fee(&mut self, new_fee: Decimal) {
+            self.comision = new_fee;
+        }
+
+        #[auth(identification_admin_def)]
+        pub fn change_asset_fee(&mut self, new_fee: Decimal) {
+            assert!(new_fee >= Decimal::zero() && new_fee < Decimal::one(), "Comisión en activo no válida");
+            self.comision_activo = new_fee;
+        }
+
+        #[auth(identification_admin_def)]
+        pub fn withdraw_asset_fees(&mut self, asset: Address) -> Bucket {
+            self.retirar_comisiones_activo(asset)
+        }
+
+        /* --- Utils --- */
+        fn crear_orden(&mut self, activo_comprar: Address, activo_vender: Bucket, precio_compra: Decimal) -> Bucket {
+            assert!(precio_compra > Decimal::zero(), "El precio tiene que ser positivo");
+
+            let restante = activo_vender.amount();
+            let badge = self.identification_minter.authorize(|auth| {
+                self.identification_nft_def.mint_non_fungible(&NonFungibleKey::from(Uuid::generate()), OrdenData{retirado: false, vendido: false, restante: restante, cobrado: Decimal::zero()}, auth)
+            });
+
+            let activo: Vault = Vault::with_bucket(activo_vender);
+
+            self.registro_orden.insert(badge.get_non_fungible_key(),(activo_comprar, activo ,precio_compra));
+
+            badge
+        }
+
+        // compra `cantidad` del activo de venta de la orden, devuelve el cambio del pago y el NFT de retirada del comprador
+        fn llenar_orden(&mut self, identificador: &NonFungibleKey, cantidad: Decimal, mut pago: Bucket) -> (Bucket, Bucket) {
+            let orden = self.registro_orden.get_mut(identificador).unwrap();
+
+            assert!(cantidad > Decimal::zero(), "La cantidad tiene que ser positiva");
+            assert!(cantidad <= orden.1.amount(), "La orden no tiene tanto activo de venta");
+
+            let coste: Decimal = cantidad / orden.2;
+            assert_eq!(orden.0, pago.resource_address(), "El pago no coincide con el activo de venta");
+            assert!(pago.amount() >= coste, "Saldo insuficiente");
+            //vendedor
+            let cobro = pago.take(coste);
+            match self.registro_ventas.get_mut(identificador) {
+                Some(ventas) => ventas.put(cobro),
+                None => {
+                    self.registro_ventas.insert(identificador.clone(), Vault::with_bucket(cobro));
+                }
+            }
+            //comprador
+            let badge = self.identification_minter.authorize(|auth| {
+                self.identification_nft_def.mint_non_fungible(&NonFungibleKey::from(Uuid::generate()), OrdenData{retirado: false, vendido: true, restante: Decimal::zero(), cobrado: Decimal::zero()}, auth)
+            });
+
+            let orden = self.registro_orden.get_mut(identificador).unwrap();
+            self.registro_ventas.insert(NonFungibleKey::from(badge.get_non_fungible_key()), Vault::with_bucket(orden.1.take(cantidad)));
+
+            let mut data: OrdenData = self.identification_nft_def.get_non_fungible_data(identificador);
+            data.restante = orden.1.amount();
+            data.cobrado += coste;
+            data.vendido = orden.1.is_empty();
+            data.retirado = false;
+            let nft_def = self.identification_nft_def.clone();
+            self.identification_minter
+                .authorize(|auth| nft_def.update_non_fungible_data(identificador, data, auth));
+
+            (pago, badge)
+        }
+
+        fn cobrar_comision_activo(&mut self, fee: Bucket) {
+            let activo = fee.resource_address();
+            match self.cajas_comision_activo.get_mut(&activo) {
+                Some(caja) => caja.put(fee),
+                None => {
+                    self.cajas_comision_activo.insert(activo, Vault::with_bucket(fee));
+                }
+            }
+        }
     }
 }
@@ -110,6 +110,11 @@ EOF
 log "check the manifest syntax by compiling it"
 xlog rtmc --output ${FN}c $FN && rm ${FN}c
 
+log "The same kind of manifest can be generated from the signed orders with settle-batch."
+log "Dry run it against a copy of the ledger to see the resulting balances before submitting anything"
+xlog $HARE settle-batch --dry-run --signer $ACCOUNT1_PUBKEY $ACCOUNT1 taker_submit_batch_swap.rtm signed_order_b1.txt signed_order_c1.txt
+cat taker_submit_batch_swap.rtm
+
 log "submit the transaction to execute the trade"
 resim run $TRACE $FN
 
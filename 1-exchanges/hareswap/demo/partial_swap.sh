@@ -0,0 +1,88 @@
+#!/usr/bin/env sh
+#set -x
+set -e
+
+# Uncomment the line below to trace all transactions
+#TRACE=--trace
+
+# setup the common baseline environment for this test
+source ./baseline.sh
+
+log "Partial Swap Example"
+
+# 0.2 Maker setup
+source ./maker_setup_simple.sh
+
+log "Taker decides to get a Request-For-Quote using HareSwap"
+
+# switch to taker
+resim set-default-account $ACCOUNT1 $ACCOUNT1_PUBKEY
+
+log "Taker requests: Whoever has TAKER_AUTH would like to buy up to 200.0 of M in exchange for T"
+MAKER_AMOUNT=200.0
+xlog $HARE request-for-quote buy-base partial_order.txt $MAKER_AMOUNT $M $T $TAKER_AUTH
+
+log "simulate sending partial order to Maker"
+log "Taker >>> partial_order.txt >>> Maker"
+
+log "Maker quotes 100.0 T for the 200.0 M but lets the order be filled in pieces at the same price."
+log "The unique identifier for this order is 'P1' which is good until (and including) epoch 42"
+TAKER_AMOUNT=100.0
+VOUCHER_KEY=P1
+DEADLINE_EPOCH=42
+xlog $HARE make-signed-order --partially-fillable partial_order.txt $TAKER_AMOUNT $MAKER_COMPONENT $VOUCHER_ADDRESS $VOUCHER_KEY $MAKER_OFFLINE_KEY_PRI $DEADLINE_EPOCH > signed_order.txt
+
+log "simulate sending signed order back to Taker"
+log "Maker >>> signed_order.txt >>> Taker"
+
+echo
+echo "Instruction to execute the signed order (missing the last arguments, now including the fill amount):"
+cat signed_order.txt
+echo
+
+SIGNED_ORDER=$(cat signed_order.txt)
+
+# build and submit a transaction filling FILL_AMOUNT M for the proportional PAY_AMOUNT of T
+fill() {
+FILL_AMOUNT=$1
+PAY_AMOUNT=$2
+FN=taker_submit_partial_swap.rtm
+cat > $FN   <<EOF
+CLONE_BUCKET_REF BucketRef(1u32) BucketRef("account_badge_t_auth");
+CALL_METHOD Address("$ACCOUNT1") "withdraw" Decimal("1") Address("$TAKER_AUTH") BucketRef("account_badge_t_auth");
+TAKE_ALL_FROM_WORKTOP Address("$TAKER_AUTH") Bucket("auth_for_exec_bucket");
+CLONE_BUCKET_REF BucketRef(1u32) BucketRef("account_badge_t");
+CALL_METHOD Address("$ACCOUNT1") "withdraw" Decimal("$PAY_AMOUNT") Address("$T") BucketRef("account_badge_t");
+TAKE_ALL_FROM_WORKTOP Address("$T") Bucket("T");
+CREATE_BUCKET_REF Bucket("auth_for_exec_bucket") BucketRef("auth_for_exec");
+$SIGNED_ORDER Decimal("$FILL_AMOUNT") Bucket("T") BucketRef("auth_for_exec");
+ASSERT_WORKTOP_CONTAINS Decimal("$FILL_AMOUNT") Address("$M");
+CALL_METHOD_WITH_ALL_RESOURCES Address("$ACCOUNT1") "deposit_batch";
+EOF
+
+xlog rtmc --output ${FN}c $FN && rm ${FN}c
+resim run $TRACE $FN
+}
+
+log "-----"
+log "Taker fills the first 50.0 M paying 25.0 T"
+fill 50.0 25.0
+
+log "Taker fills another 150.0 M paying 75.0 T, which uses up the order"
+fill 150.0 75.0
+
+log "Any further fill is rejected by the Maker component (expect a failure here)"
+if fill 1.0 0.5; then
+    echo "ERROR: the order was filled past its maximum size"
+    exit 1
+fi
+
+success
+log "look at the accounts:"
+log "Taker has changed: T: 1000-100=900, +200 M"
+log "Maker (Shared Account) has changed: M: 500-200=300, T: +100"
+log "---"
+log "The Taker's System Account"
+resim show $ACCOUNT1
+log "The Maker's Shared Account"
+resim show $MAKER_ACCOUNT
@@ -0,0 +1,72 @@
+#!/usr/bin/env sh
+#set -x
+set -e
+
+# Uncomment the line below to trace all transactions
+#TRACE=--trace
+
+# setup the common baseline environment for this test
+source ./baseline.sh
+
+log "RFQ Server Example"
+
+# 0.2 Maker setup
+source ./maker_setup_simple.sh
+
+log "Maker starts a quoting daemon instead of hand-running make-signed-order for each request"
+log "Prices come from a config file: M is worth 0.5 T with a 2% spread"
+cat > pricing.conf <<EOF
+# base quote mid_price spread
+$M $T 0.5 0.02
+EOF
+RFQ_SERVER=127.0.0.1:7878
+FIRST_VOUCHER_KEY=1000
+VALID_EPOCHS=5
+$HARE serve $RFQ_SERVER $MAKER_COMPONENT $VOUCHER_ADDRESS $FIRST_VOUCHER_KEY $MAKER_OFFLINE_KEY_PRI $VALID_EPOCHS fixed-spread pricing.conf &
+SERVER_PID=$!
+trap "kill $SERVER_PID" EXIT
+sleep 1
+
+# switch to taker
+resim set-default-account $ACCOUNT1 $ACCOUNT1_PUBKEY
+
+log "Taker requests: Whoever has TAKER_AUTH would like to buy 200.0 of M in exchange for T"
+MAKER_AMOUNT=200.0
+xlog $HARE request-for-quote buy-base partial_order.txt $MAKER_AMOUNT $M $T $TAKER_AUTH
+
+log "Taker >>> partial_order.txt >>> RFQ server >>> signed_order.txt >>> Taker"
+xlog $HARE request $RFQ_SERVER partial_order.txt > signed_order.txt
+
+log "The daemon quoted 200.0 * 0.5 * 1.02 = 102 T"
+TAKER_AMOUNT=102.0
+echo
+cat signed_order.txt
+echo
+
+SIGNED_ORDER=$(cat signed_order.txt)
+FN=taker_submit_rfq_server.rtm
+cat > $FN   <<EOF
+CLONE_BUCKET_REF BucketRef(1u32) BucketRef("account_badge_t_auth");
+CALL_METHOD Address("$ACCOUNT1") "withdraw" Decimal("1") Address("$TAKER_AUTH") BucketRef("account_badge_t_auth");
+TAKE_ALL_FROM_WORKTOP Address("$TAKER_AUTH") Bucket("auth_for_exec_bucket");
+CLONE_BUCKET_REF BucketRef(1u32) BucketRef("account_badge_t");
+CALL_METHOD Address("$ACCOUNT1") "withdraw" Decimal("$TAKER_AMOUNT") Address("$T") BucketRef("account_badge_t");
+TAKE_ALL_FROM_WORKTOP Address("$T") Bucket("T");
+CREATE_BUCKET_REF Bucket("auth_for_exec_bucket") BucketRef("auth_for_exec");
+$SIGNED_ORDER Bucket("T") BucketRef("auth_for_exec");
+ASSERT_WORKTOP_CONTAINS Decimal("$MAKER_AMOUNT") Address("$M");
+CALL_METHOD_WITH_ALL_RESOURCES Address("$ACCOUNT1") "deposit_batch";
+EOF
+
+log "check the manifest syntax by compiling it"
+xlog rtmc --output ${FN}c $FN && rm ${FN}c
+
+log "submit the transaction to execute the trade"
+resim run $TRACE $FN
+
+success
+log "look at the accounts:"
+log "Taker has changed: T: 1000-102=898, +200 M"
+log "Maker (Shared Account) has changed: M: 500-200=300, T: +102"
+resim show $ACCOUNT1
+resim show $MAKER_ACCOUNT
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 // non-scrypto dependencies
 use clap::{ArgEnum, Parser, Subcommand};
@@ -12,7 +16,7 @@ use radix_engine::model::{Instruction, Transaction};
 use radix_engine::transaction::*;
 use scrypto::buffer::scrypto_encode;
 use scrypto::prelude::*;
-use scrypto::types::EcdsaPublicKey;
+use scrypto::types::{EcdsaPublicKey, ParseEcdsaPublicKeyError};
 use scrypto::utils::sha256;
 use simulator::ledger::*;
 use simulator::resim::*;
@@ -37,6 +41,10 @@ pub enum Command {
     RequestForQuote(RequestForQuote),
     MakeSignedOrder(MakeSignedOrder),
     TokenizeOrder(TokenizeOrder),
+    RevokeOrder(RevokeOrder),
+    SettleBatch(SettleBatch),
+    Serve(Serve),
+    Request(Request),
     Test(TestCommands),
 }
 
@@ -64,6 +72,7 @@ pub enum Error {
     ParseAddressError(ParseAddressError),
     ParseDecimalError(ParseDecimalError),
     ParseNonFungibleKeyError(ParseNonFungibleKeyError),
+    ParsePublicKeyError(ParseEcdsaPublicKeyError),
     SBORDecodeError(sbor::DecodeError),
     Utf8Error(std::str::Utf8Error),
     ManifestParserError(transaction_manifest::parser::ParserError),
@@ -71,6 +80,8 @@ pub enum Error {
     VerifyCheckError(VerifyError),
     BadPrivateKeyError(k256::ecdsa::Error),
     ParserNotEOFError,
+    PricingError(String),
+    RfqDeclined(String),
     GenerateEntrypointError(transaction_manifest::DecompileError),
     GenerateEntrypointFormatError,
     TestError,
@@ -86,6 +97,10 @@ pub fn run() -> Result<(), Error> {
         Command::RequestForQuote(cmd) => cmd.run(),
         Command::MakeSignedOrder(cmd) => cmd.run(),
         Command::TokenizeOrder(cmd) => cmd.run(),
+        Command::RevokeOrder(cmd) => cmd.run(),
+        Command::SettleBatch(cmd) => cmd.run(),
+        Command::Serve(cmd) => cmd.run(),
+        Command::Request(cmd) => cmd.run(),
         Command::Test(cmd) => match cmd.command {
             TestCommand::NFTSetup(cmd) => cmd.run(),
         },
@@ -190,6 +205,12 @@ pub struct MakeSignedOrder {
     deadline_epoch: u64,
     /// optional callback to use instead of teh handle_order_default_callback.  This should be in the form of a CALL_METHOD instruction
     callback: Option<String>,
+    /// let takers fill the order in several pieces up to the requested base amount, each paying a proportional amount of resource_q
+    #[clap(long)]
+    partially_fillable: bool,
+    /// nonce used to revoke the order before it is executed (see `revoke-order`).  Defaults to one derived from the voucher_key
+    #[clap(long)]
+    voucher_nonce: Option<u64>,
 }
 
 impl MakeSignedOrder {
@@ -197,17 +218,65 @@ impl MakeSignedOrder {
         // parse arguments
         let partial_order_bytes = fs::read(&self.partial_order_file).map_err(Error::IoError)?;
         let resource_q_contents = BucketContents::from_str(&self.resource_q_amount).map_err(Error::ParseAmountError)?;
-        let maker_component_address =
-            Address::from_str(&self.maker_component_address).map_err(Error::ParseAddressError)?;
-        let voucher_resource: ResourceDef = Address::from_str(&self.voucher_address)
-            .map_err(Error::ParseAddressError)?
-            .into();
         let voucher_key = NonFungibleKey::from_str(&self.voucher_key).map_err(Error::ParseNonFungibleKeyError)?;
-        let private_key_bytes = fs::read(&self.private_key_file).map_err(Error::IoError)?;
+        let voucher_nonce = self
+            .voucher_nonce
+            .unwrap_or_else(|| default_voucher_nonce(&voucher_key));
         //let deadline = u64::from_str(&self.deadline_epoch)?;//.map_err(Error::ParseDeadline)?;
         let deadline = self.deadline_epoch;
+        let signer = OrderSigner::new(
+            &self.maker_component_address,
+            &self.voucher_address,
+            &self.private_key_file,
+            self.callback.as_ref(),
+            self.partially_fillable,
+        )?;
+
+        // decode the PartialOrder
+        let partial_order_encoded = partial_order_bytes;
+        let partial_order: PartialOrder = scrypto_decode(&partial_order_encoded).map_err(Error::SBORDecodeError)?;
+
+        let result = signer.sign(partial_order, resource_q_contents, voucher_key, voucher_nonce, deadline)?;
+
+        // print the instruction to stdout in Radix Transaction Manifest (rtm) format
+        // so the sender can compose it with whatever they want
+        print!("{}", result);
+
+        // *SECURITY NOTICE*: care should be taken by the transaction submitter to
+        // validate this upon receipt and not introduce "instruction injection" vulnerabilities
+        // or any other "component redirection" vulnerabilities
+
+        Ok(())
+    }
+}
+
+/// The maker's side of signing orders: everything needed to turn a PartialOrder and a quote into a SignedOrder
+/// instruction which does not change from order to order.  Shared by `make-signed-order` and `serve`
+pub struct OrderSigner {
+    maker_component_address: Address,
+    voucher_resource: ResourceDef,
+    signing_key: SigningKey,
+    maker_callback: Callback,
+    partially_fillable: bool,
+}
+
+impl OrderSigner {
+    /// parse the (string) arguments shared by the maker subcommands
+    pub fn new(
+        maker_component_address: &str,
+        voucher_address: &str,
+        private_key_file: &PathBuf,
+        callback: Option<&String>,
+        partially_fillable: bool,
+    ) -> Result<Self, Error> {
+        let maker_component_address = Address::from_str(maker_component_address).map_err(Error::ParseAddressError)?;
+        let voucher_resource: ResourceDef = Address::from_str(voucher_address)
+            .map_err(Error::ParseAddressError)?
+            .into();
+        let private_key_bytes = fs::read(private_key_file).map_err(Error::IoError)?;
+        let signing_key = SigningKey::from_bytes(&private_key_bytes).map_err(Error::BadPrivateKeyError)?;
         // set the callback by parsing the argument string or using the default
-        let maker_callback = if self.callback.is_none() {
+        let maker_callback = if callback.is_none() {
             // this is the default callback expected in the Maker Component
             // It is used for "simple" swaps where the Maker has a SharedAccount
             // managing the bought and sold assets.
@@ -218,8 +287,8 @@ impl MakeSignedOrder {
             }
         } else {
             // parse the CallMethod Instruction into the Callback type with the same args
-            let callback_str = self.callback.as_ref().unwrap(); // unwrap is safe on this branch
-            let tx: Transaction = transaction_manifest::compile(&callback_str).map_err(Error::CompileError)?;
+            let callback_str = callback.unwrap(); // unwrap is safe on this branch
+            let tx: Transaction = transaction_manifest::compile(callback_str).map_err(Error::CompileError)?;
             assert_eq!(tx.instructions.len(), 1, "callback error, too many instructions"); // backend only supports 1 for now
             match tx.instructions[0].clone() {
                 Instruction::CallMethod {
@@ -235,24 +304,43 @@ impl MakeSignedOrder {
             }
         };
 
-        // decode the PartialOrder
-        let partial_order_encoded = partial_order_bytes;
-        let partial_order: PartialOrder = scrypto_decode(&partial_order_encoded).map_err(Error::SBORDecodeError)?;
+        Ok(OrderSigner {
+            maker_component_address,
+            voucher_resource,
+            signing_key,
+            maker_callback,
+            partially_fillable,
+        })
+    }
 
+    /// match the partial_order with the quote, sign it and return the instruction (without the trailing arguments) the taker will use to execute it
+    ///
+    /// The voucher expires with the order at the deadline
+    pub fn sign(
+        &self,
+        partial_order: PartialOrder,
+        quote_contents: BucketContents,
+        voucher_key: NonFungibleKey,
+        voucher_nonce: u64,
+        deadline: u64,
+    ) -> Result<String, Error> {
         // create the MatchedOrder from the inputs
         let matched_order = MatchedOrder {
             partial_order,
-            quote_contents: resource_q_contents,
-            maker_address: maker_component_address,
-            maker_callback,
+            quote_contents,
+            maker_address: self.maker_component_address,
+            maker_callback: self.maker_callback.clone(),
             deadline,
+            partially_fillable: self.partially_fillable,
         };
 
         // construct a Voucher for the MatchedOrder
 
         let voucher = Voucher::from_nfd(
-            voucher_resource.clone(),
+            self.voucher_resource.clone(),
             Some(voucher_key.clone()),
+            deadline,
+            voucher_nonce,
             matched_order.clone(),
         );
 
@@ -260,19 +348,23 @@ impl MakeSignedOrder {
         let voucher_encoded = scrypto_encode(&voucher);
 
         // sign the voucher
-        let signing_key = SigningKey::from_bytes(&private_key_bytes).map_err(Error::BadPrivateKeyError)?;
-        let signature: Signature = signing_key.try_sign(&voucher_encoded).map_err(Error::SigningError)?;
+        let signature: Signature = self
+            .signing_key
+            .try_sign(&voucher_encoded)
+            .map_err(Error::SigningError)?;
         let sig_bytes = signature.to_der().to_bytes().to_vec();
 
         // double check that the sig verifies (all the format conversions are ok)
-        let public_key = to_public_key(&signing_key);
+        let public_key = to_public_key(&self.signing_key);
         verify(&public_key, &voucher_encoded, &sig_bytes).map_err(Error::VerifyCheckError)?;
 
         // create the SignedOrder for consumption by the submitter.
         let signed_order = SignedOrder {
             order: matched_order,
-            voucher_resource,
+            voucher_resource: self.voucher_resource.clone(),
             voucher_key,
+            voucher_expiry: deadline,
+            voucher_nonce,
             signature: sig_bytes,
         };
 
@@ -282,9 +374,15 @@ impl MakeSignedOrder {
         // create the instruction the sender will use to execute this signed order (after adding the appropriate buckets)
         // this is not technically part of the signed order and not authenticated.  The taker/sender is free to send the
         // signed order (along with their badge) to the more advanced "tokenize_order" method.
+        // Partially fillable orders can't be tokenized and take the fill amount as the next argument instead.
+        let method = if self.partially_fillable {
+            "execute_partial_order"
+        } else {
+            "execute_order"
+        };
         let execute_entrypoint = Instruction::CallMethod {
-            component_address: maker_component_address,
-            method: "execute_order".to_owned(), // method name matches the Maker blueprint implementation, hardcoding this here
+            component_address: self.maker_component_address,
+            method: method.to_owned(), // method names match the Maker blueprint implementation, hardcoding them here
             args: vec![signed_order_encoded],
         };
         // build the transaction so we can use the available API to easily print it
@@ -295,15 +393,7 @@ impl MakeSignedOrder {
         // drop the trailing semicolon and newline since extra args are required
         let (result, _) = manifest.rsplit_once(";").ok_or(Error::GenerateEntrypointFormatError)?;
 
-        // print the instruction to stdout in Radix Transaction Manifest (rtm) format
-        // so the sender can compose it with whatever they want
-        print!("{}", result);
-
-        // *SECURITY NOTICE*: care should be taken by the transaction submitter to
-        // validate this upon receipt and not introduce "instruction injection" vulnerabilities
-        // or any other "component redirection" vulnerabilities
-
-        Ok(())
+        Ok(result.to_owned())
     }
 }
 
@@ -377,6 +467,674 @@ impl TokenizeOrder {
     }
 }
 
+/* Revoke Order */
+
+/// used by the maker: revoke a signed order before it is executed, eg. a stale quote or one sent to the wrong taker
+///
+/// Outputs (on stdout) a complete CALL_METHOD instruction to the Maker's `revoke_voucher` method with a signature over
+/// the voucher nonce.  Anyone can submit it, it only needs to reach the ledger before the order does.
+#[derive(Parser, Debug)]
+pub struct RevokeOrder {
+    /// component address for the Maker component which handles the order
+    maker_component_address: String,
+    /// resource address the Maker's "Transporter" mints for its orders, the same as for `make-signed-order`
+    voucher_address: String,
+    /// path to file containing the serialized private key which signed the order
+    private_key_file: PathBuf,
+    /// the voucher_key of the order to revoke
+    voucher_key: String,
+    /// the nonce the order was signed with, if it was set explicitly with `make-signed-order --voucher-nonce`
+    #[clap(long)]
+    voucher_nonce: Option<u64>,
+}
+
+impl RevokeOrder {
+    pub fn run(&self) -> Result<(), Error> {
+        // parse arguments
+        let maker_component_address =
+            Address::from_str(&self.maker_component_address).map_err(Error::ParseAddressError)?;
+        let voucher_address = Address::from_str(&self.voucher_address).map_err(Error::ParseAddressError)?;
+        let private_key_bytes = fs::read(&self.private_key_file).map_err(Error::IoError)?;
+        let voucher_key = NonFungibleKey::from_str(&self.voucher_key).map_err(Error::ParseNonFungibleKeyError)?;
+        let voucher_nonce = self
+            .voucher_nonce
+            .unwrap_or_else(|| default_voucher_nonce(&voucher_key));
+
+        // sign the revocation
+        let signing_key = SigningKey::from_bytes(&private_key_bytes).map_err(Error::BadPrivateKeyError)?;
+        let message = revocation_message(&voucher_address, voucher_nonce);
+        let signature: Signature = signing_key.try_sign(&message).map_err(Error::SigningError)?;
+        let sig_bytes = signature.to_der().to_bytes().to_vec();
+
+        // double check that the sig verifies (all the format conversions are ok)
+        let public_key = to_public_key(&signing_key);
+        verify(&public_key, &message, &sig_bytes).map_err(Error::VerifyCheckError)?;
+
+        let revoke_entrypoint = Instruction::CallMethod {
+            component_address: maker_component_address,
+            method: "revoke_voucher".to_owned(), // method name matches the Maker blueprint implementation, hardcoding this here
+            args: vec![scrypto_encode(&voucher_nonce), scrypto_encode(&sig_bytes)],
+        };
+        let tx = Transaction {
+            instructions: vec![revoke_entrypoint],
+        };
+        let manifest = transaction_manifest::decompile(&tx).map_err(Error::GenerateEntrypointError)?;
+
+        // print the instruction to stdout in Radix Transaction Manifest (rtm) format
+        print!("{}", manifest);
+
+        Ok(())
+    }
+}
+
+/// the voucher nonce used when none is given explicitly: derived from the voucher_key so the maker can always recompute it
+pub fn default_voucher_nonce(voucher_key: &NonFungibleKey) -> u64 {
+    let hash = sha256(scrypto_encode(voucher_key));
+    let mut nonce_bytes = [0u8; 8];
+    nonce_bytes.copy_from_slice(&hash.as_ref()[..8]);
+    u64::from_le_bytes(nonce_bytes)
+}
+
+/* Settle Batch */
+
+/// used by the taker: combine several signed orders (possibly from different makers) into a single transaction manifest
+/// which settles all of them atomically
+///
+/// Orders are settled in the order given.  When the output of earlier orders covers what a later order needs it is
+/// routed straight into that order (as done by hand in `demo/double_swap.sh`), otherwise it is withdrawn from the
+/// account.  Each distinct taker_auth is withdrawn once and shared by the orders.  Every output is checked with
+/// ASSERT_WORKTOP_CONTAINS and everything left over is deposited back into the account.  Partially fillable orders are
+/// settled for their full size.
+#[derive(Parser, Debug)]
+pub struct SettleBatch {
+    /// executor trace flag (for --dry-run)
+    #[clap(short, long)]
+    trace: bool,
+    /// run the manifest against a scratch copy of the resim ledger and show the resulting account balances.  The resim ledger itself is not changed
+    #[clap(long, requires = "signer")]
+    dry_run: bool,
+    /// public key of the transaction signer (for --dry-run)
+    #[clap(long)]
+    signer: Option<String>,
+    /// account to fund the orders from and deposit the results to, must be controlled by the transaction signer
+    account: String,
+    /// path to store the transaction manifest
+    output_path: PathBuf,
+    /// paths to files each containing a signed order instruction (ie. the `make-signed-order` output), in settlement order
+    #[clap(required = true)]
+    signed_order_files: Vec<PathBuf>,
+}
+
+impl SettleBatch {
+    pub fn run(&self) -> Result<(), Error> {
+        let account = Address::from_str(&self.account).map_err(Error::ParseAddressError)?;
+
+        let mut batch = BatchManifest::new(account);
+        for signed_order_file in &self.signed_order_files {
+            let instruction = fs::read_to_string(signed_order_file).map_err(Error::IoError)?;
+            batch.settle(instruction.trim())?;
+        }
+        let manifest = batch.finish()?;
+
+        fs::write(&self.output_path, &manifest).map_err(Error::IoError)?;
+
+        if self.dry_run {
+            let signer = self.signer.as_ref().unwrap(); // clap requires signer with dry_run
+            let signer = EcdsaPublicKey::from_str(signer).map_err(Error::ParsePublicKeyError)?;
+            dry_run(&manifest, account, signer, self.trace)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the manifest text for `settle-batch` one order at a time
+struct BatchManifest {
+    account: Address,
+    manifest: String,
+    /// what the orders so far have left on the worktop (and later orders may use), by resource
+    produced: HashMap<Address, BucketContents>,
+    /// the bucket holding each taker_auth withdrawn so far
+    auths: Vec<(BucketRequirement, String)>,
+    orders: usize,
+    badges: usize,
+}
+
+impl BatchManifest {
+    fn new(account: Address) -> Self {
+        BatchManifest {
+            account,
+            manifest: String::new(),
+            produced: HashMap::new(),
+            auths: vec![],
+            orders: 0,
+            badges: 0,
+        }
+    }
+
+    /// append the instructions to settle one signed order instruction
+    fn settle(&mut self, instruction: &str) -> Result<(), Error> {
+        // compile the instruction to get at the SignedOrder, see `tokenize-order`
+        let tx: Transaction =
+            transaction_manifest::compile(&format!("{};", instruction)).map_err(Error::CompileError)?;
+        assert_eq!(tx.instructions.len(), 1, "signed order file had too many instructions");
+        let signed_order: SignedOrder = match &tx.instructions[0] {
+            Instruction::CallMethod { args, .. } => scrypto_decode(&args[0]).map_err(Error::SBORDecodeError)?,
+            _ => panic!("signed order file did not contain a CallMethod"),
+        };
+        let order = signed_order.order;
+        self.orders += 1;
+
+        // depending on the direction (ie. inverted or not) determine who provides which side, like the Maker component
+        let quote_requirement = BucketRequirement {
+            resource: order.partial_order.quote_resource.clone(),
+            contents: order.quote_contents.clone(),
+        };
+        let base_requirement = order.partial_order.base_requirement.clone();
+        let (taker_requirement, maker_requirement) = if order.partial_order.inverted {
+            (base_requirement, quote_requirement)
+        } else {
+            (quote_requirement, base_requirement)
+        };
+
+        // route the output of earlier orders in if possible
+        let payment = format!("payment{}", self.orders);
+        if !self.take_produced(&taker_requirement, &payment)? {
+            self.withdraw(&taker_requirement, &payment)?;
+        }
+
+        let auth = self.auth_bucket(&order.partial_order.taker_auth)?;
+        let auth_ref = format!("auth{}", self.orders);
+        self.push(format!(
+            "CREATE_BUCKET_REF Bucket(\"{}\") BucketRef(\"{}\")",
+            auth, auth_ref
+        ));
+
+        // partially fillable orders take the fill amount before the bucket
+        let fill_amount = match (
+            &order.partially_fillable,
+            &order.partial_order.base_requirement.contents,
+        ) {
+            (false, _) => String::new(),
+            (true, BucketContents::Fungible(amount)) => format!("{} ", manifest_value(amount)?),
+            (true, BucketContents::NonFungible(_)) => panic!("partially fillable orders must be fungible"),
+        };
+        self.push(format!(
+            "{} {}Bucket(\"{}\") BucketRef(\"{}\")",
+            instruction, fill_amount, payment, auth_ref
+        ));
+
+        // check we got what we expect, and make it available to the following orders
+        let received = match &maker_requirement.contents {
+            BucketContents::Fungible(amount) => *amount,
+            BucketContents::NonFungible(keys) => Decimal::from(keys.len() as u64),
+        };
+        self.push(format!(
+            "ASSERT_WORKTOP_CONTAINS {} {}",
+            manifest_value(&received)?,
+            manifest_value(&maker_requirement.resource.address())?
+        ));
+        self.add_produced(maker_requirement);
+
+        Ok(())
+    }
+
+    /// deposit whatever is left into the account and return the manifest
+    fn finish(mut self) -> Result<String, Error> {
+        let account = manifest_value(&self.account)?;
+        self.push(format!("CALL_METHOD_WITH_ALL_RESOURCES {} \"deposit_batch\"", account));
+        Ok(self.manifest)
+    }
+
+    /// the bucket holding taker_auth, withdrawing it the first time it is needed
+    fn auth_bucket(&mut self, taker_auth: &BucketRequirement) -> Result<String, Error> {
+        if let Some((_, bucket)) = self.auths.iter().find(|(auth, _)| auth == taker_auth) {
+            return Ok(bucket.clone());
+        }
+        let bucket = format!("taker_auth{}", self.auths.len() + 1);
+        self.withdraw(taker_auth, &bucket)?;
+        self.auths.push((taker_auth.clone(), bucket.clone()));
+        Ok(bucket)
+    }
+
+    /// take requirement from the earlier orders' output into bucket, or return false if they did not leave enough
+    fn take_produced(&mut self, requirement: &BucketRequirement, bucket: &str) -> Result<bool, Error> {
+        let address = requirement.resource.address();
+        let remaining = match (self.produced.get(&address), &requirement.contents) {
+            (Some(BucketContents::Fungible(available)), BucketContents::Fungible(amount)) if available >= amount => {
+                BucketContents::Fungible(*available - *amount)
+            }
+            (Some(BucketContents::NonFungible(available)), BucketContents::NonFungible(keys))
+                if keys.is_subset(available) =>
+            {
+                BucketContents::NonFungible(available.difference(keys).cloned().collect())
+            }
+            _ => return Ok(false),
+        };
+        self.produced.insert(address, remaining);
+        self.take_from_worktop(requirement, bucket)?;
+        Ok(true)
+    }
+
+    /// record an order's output as available on the worktop
+    fn add_produced(&mut self, requirement: BucketRequirement) {
+        let address = requirement.resource.address();
+        let contents = match (self.produced.remove(&address), requirement.contents) {
+            (Some(BucketContents::Fungible(available)), BucketContents::Fungible(amount)) => {
+                BucketContents::Fungible(available + amount)
+            }
+            (Some(BucketContents::NonFungible(mut available)), BucketContents::NonFungible(keys)) => {
+                available.extend(keys);
+                BucketContents::NonFungible(available)
+            }
+            (_, contents) => contents,
+        };
+        self.produced.insert(address, contents);
+    }
+
+    /// withdraw requirement from the account into bucket
+    fn withdraw(&mut self, requirement: &BucketRequirement, bucket: &str) -> Result<(), Error> {
+        self.badges += 1;
+        let badge = format!("account_badge{}", self.badges);
+        self.push(format!("CLONE_BUCKET_REF BucketRef(1u32) BucketRef(\"{}\")", badge));
+        let (method, contents) = match &requirement.contents {
+            BucketContents::Fungible(amount) => ("withdraw", manifest_value(amount)?),
+            BucketContents::NonFungible(keys) => ("withdraw_non_fungibles", manifest_value(keys)?),
+        };
+        self.push(format!(
+            "CALL_METHOD {} \"{}\" {} {} BucketRef(\"{}\")",
+            manifest_value(&self.account)?,
+            method,
+            contents,
+            manifest_value(&requirement.resource.address())?,
+            badge
+        ));
+        self.take_from_worktop(requirement, bucket)
+    }
+
+    /// take exactly requirement from the worktop into bucket
+    fn take_from_worktop(&mut self, requirement: &BucketRequirement, bucket: &str) -> Result<(), Error> {
+        let (instruction, contents) = match &requirement.contents {
+            BucketContents::Fungible(amount) => ("TAKE_FROM_WORKTOP", manifest_value(amount)?),
+            BucketContents::NonFungible(keys) => ("TAKE_NON_FUNGIBLES_FROM_WORKTOP", manifest_value(keys)?),
+        };
+        self.push(format!(
+            "{} {} {} Bucket(\"{}\")",
+            instruction,
+            contents,
+            manifest_value(&requirement.resource.address())?,
+            bucket
+        ));
+        Ok(())
+    }
+
+    fn push(&mut self, instruction: String) {
+        self.manifest.push_str(&instruction);
+        self.manifest.push_str(";\n");
+    }
+}
+
+/// format a value the way it is written in a transaction manifest, eg. Decimal("1.5")
+fn manifest_value<T: sbor::Encode>(value: &T) -> Result<String, Error> {
+    let validated_arg = validate_data(&scrypto_encode(value))
+        .map_err(transaction_manifest::DecompileError::DataValidationError)
+        .map_err(Error::DecompileError)?;
+    Ok(validated_arg.to_string())
+}
+
+/// run the manifest signed by signer against a scratch copy of the resim ledger then show the receipt and the account
+///
+/// The copy is thrown away afterwards so this never changes the resim ledger
+fn dry_run(manifest: &str, account: Address, signer: EcdsaPublicKey, trace: bool) -> Result<(), Error> {
+    let scratch_dir = std::env::temp_dir().join(format!("hare-dry-run-{}", std::process::id()));
+    copy_dir(&get_data_dir().map_err(Error::ResimError)?, &scratch_dir).map_err(Error::IoError)?;
+    let result = run_manifest(manifest, account, signer, trace, scratch_dir.clone());
+    fs::remove_dir_all(&scratch_dir).map_err(Error::IoError)?;
+    result
+}
+
+/// the `dry_run` implementation against the ledger in data_dir
+fn run_manifest(
+    manifest: &str,
+    account: Address,
+    signer: EcdsaPublicKey,
+    trace: bool,
+    data_dir: PathBuf,
+) -> Result<(), Error> {
+    let mut ledger = RadixEngineDB::with_bootstrap(data_dir);
+    let tx: Transaction = transaction_manifest::compile(manifest).map_err(Error::CompileError)?;
+
+    let receipt = {
+        let mut executor = TransactionExecutor::new(&mut ledger, trace);
+        let transaction = {
+            let mut builder = TransactionBuilder::new(&executor);
+            for instruction in tx.instructions {
+                builder.add_instruction(instruction);
+            }
+            builder.build(vec![signer]).map_err(|_| Error::RuntimeError)?
+        };
+        executor.run(transaction).map_err(|_| Error::RuntimeError)?
+    };
+    println!("{:?}\n", receipt);
+    receipt.result.as_ref().map_err(|_| Error::RuntimeError)?;
+
+    // show the balances as they would be after the batch settles
+    dump_component(account, &ledger).map_err(|_| Error::RuntimeError)
+}
+
+/// recursively copy the directory from into the (new) directory to
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/* RFQ Server */
+
+/// used by the maker: run a daemon which answers request-for-quote (RFQ) messages on a local TCP socket with signed orders
+///
+/// The wire format is deliberately minimal.  A client connects, writes the SBOR-encoded PartialOrder bytes (exactly the
+/// `request-for-quote` output file) and shuts down its side of the connection.  The server replies with a single status
+/// byte (0 on success) followed by either the signed order instruction (exactly the `make-signed-order` output) or an
+/// error message, then closes the connection.  See `request` for the client side.
+///
+/// WARNING: there is no authentication of clients, so only listen on a local address.
+#[derive(Parser, Debug)]
+pub struct Serve {
+    /// local address to listen on, eg. 127.0.0.1:7878
+    listen_address: String,
+    /// component address for the Maker component which is the entry point to be called with the SignedOrders to complete the on-ledger order settlement
+    maker_component_address: String,
+    /// resource address identifying the resource a "Transporter" will mint when converting the SignedOrders to on-ledger NonFungable tokens
+    voucher_address: String,
+    /// voucher key (as an integer) for the first order, incremented for each order signed.  Must not reuse keys from an earlier run
+    first_voucher_key: u128,
+    /// path to file containing the serialized private key which will sign the orders - must match on-ledger public key
+    private_key_file: PathBuf,
+    /// number of epochs after the current (resim ledger) epoch the orders can still be executed
+    valid_epochs: u64,
+    /// optional callback to use instead of the handle_order_default_callback.  This should be in the form of a CALL_METHOD instruction
+    #[clap(long)]
+    callback: Option<String>,
+    /// sign partially fillable orders, see `make-signed-order`
+    #[clap(long)]
+    partially_fillable: bool,
+    /// seconds to wait for a client to send its RFQ before dropping it, requests are handled one at a time so an idle client would block the others
+    #[clap(long, default_value_t = 10)]
+    read_timeout: u64,
+    /// how to decide the quote for each request
+    #[clap(subcommand)]
+    pricing: Pricing,
+}
+
+/// the available pricing strategies for `serve`
+#[derive(Subcommand, Debug)]
+pub enum Pricing {
+    FixedSpread(FixedSpread),
+    LedgerPool(LedgerPool),
+}
+
+/// Decides the maker's quote for a request-for-quote.  Implement this to plug in another way of pricing orders
+pub trait PricingStrategy {
+    /// the quote_contents the maker will trade for the base_requirement in partial_order, or an Error to decline the request
+    fn quote(&self, partial_order: &PartialOrder) -> Result<BucketContents, Error>;
+}
+
+impl Serve {
+    pub fn run(&self) -> Result<(), Error> {
+        let signer = OrderSigner::new(
+            &self.maker_component_address,
+            &self.voucher_address,
+            &self.private_key_file,
+            self.callback.as_ref(),
+            self.partially_fillable,
+        )?;
+        let strategy: &dyn PricingStrategy = match &self.pricing {
+            Pricing::FixedSpread(pricing) => pricing,
+            Pricing::LedgerPool(pricing) => pricing,
+        };
+
+        let listener = TcpListener::bind(&self.listen_address).map_err(Error::IoError)?;
+        eprintln!("hare serve: listening for RFQs on {}", self.listen_address);
+
+        // requests are handled one at a time which keeps the voucher keys sequential (and the resim ledger unlocked between requests)
+        let mut next_voucher_key = self.first_voucher_key;
+        for stream in listener.incoming() {
+            // a failed connection should not stop the server either
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("hare serve: failed to accept a connection: {:?}", e);
+                    continue;
+                }
+            };
+            let written = match self.handle(&mut stream, &signer, strategy, next_voucher_key.into()) {
+                Ok(signed_order) => {
+                    eprintln!("hare serve: signed order with voucher key {}", next_voucher_key);
+                    next_voucher_key += 1;
+                    write_reply(&mut stream, true, &signed_order)
+                }
+                Err(e) => {
+                    eprintln!("hare serve: declined RFQ: {:?}", e);
+                    write_reply(&mut stream, false, &format!("{:?}", e))
+                }
+            };
+            // a client hanging up early should not stop the server
+            if let Err(e) = written {
+                eprintln!("hare serve: failed to reply: {:?}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// read one RFQ from the stream, price it and sign the order
+    fn handle(
+        &self,
+        stream: &mut TcpStream,
+        signer: &OrderSigner,
+        strategy: &dyn PricingStrategy,
+        voucher_key: NonFungibleKey,
+    ) -> Result<String, Error> {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(self.read_timeout)))
+            .map_err(Error::IoError)?;
+        let mut partial_order_bytes = vec![];
+        stream.read_to_end(&mut partial_order_bytes).map_err(Error::IoError)?;
+        let partial_order: PartialOrder = scrypto_decode(&partial_order_bytes).map_err(Error::SBORDecodeError)?;
+
+        let quote_contents = strategy.quote(&partial_order)?;
+        let voucher_nonce = default_voucher_nonce(&voucher_key);
+
+        // only open the ledger briefly since resim needs it too
+        let epoch = RadixEngineDB::with_bootstrap(get_data_dir().map_err(Error::ResimError)?).get_epoch();
+
+        signer.sign(
+            partial_order,
+            quote_contents,
+            voucher_key,
+            voucher_nonce,
+            epoch + self.valid_epochs,
+        )
+    }
+}
+
+/// write the status byte and message making up a `serve` reply
+fn write_reply(stream: &mut TcpStream, ok: bool, message: &str) -> std::io::Result<()> {
+    stream.write_all(&[if ok { 0 } else { 1 }])?;
+    stream.write_all(message.as_bytes())
+}
+
+/// quote the base amount in partial_order at price (quote per base) with the spread applied in the maker's favour
+fn quote_with_spread(partial_order: &PartialOrder, price: Decimal, spread: Decimal) -> Result<BucketContents, Error> {
+    let amount = match &partial_order.base_requirement.contents {
+        BucketContents::Fungible(amount) => *amount,
+        BucketContents::NonFungible(_) => {
+            return Err(Error::PricingError(
+                "only fungible base requirements can be priced".to_owned(),
+            ))
+        }
+    };
+    // when inverted the taker is selling the base asset so the maker pays less, otherwise the taker pays more
+    let price = if partial_order.inverted {
+        price * (Decimal::one() - spread)
+    } else {
+        price * (Decimal::one() + spread)
+    };
+    Ok(BucketContents::Fungible(amount * price))
+}
+
+/// pricing strategy: quote a fixed spread around mid prices listed in a config file
+///
+/// The config file has one market per line: `<base resource> <quote resource> <mid price> <spread>` where the mid price
+/// is the amount of quote resource per base resource and the spread is a fraction (eg. 0.01 for 1%).  Lines starting
+/// with '#' are ignored.  A market also answers requests in the opposite direction at the inverse price.  The file is
+/// read for every request so prices can be updated without restarting the server.
+#[derive(Parser, Debug)]
+pub struct FixedSpread {
+    /// path to the config file
+    config_file: PathBuf,
+}
+
+impl FixedSpread {
+    /// read the config file into a map of (base, quote) to (mid price, spread)
+    fn load(&self) -> Result<HashMap<(Address, Address), (Decimal, Decimal)>, Error> {
+        let config = fs::read_to_string(&self.config_file).map_err(Error::IoError)?;
+        let mut markets = HashMap::new();
+        for line in config.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(Error::PricingError(format!("bad market in config file: {}", line)));
+            }
+            let base = Address::from_str(fields[0]).map_err(Error::ParseAddressError)?;
+            let quote = Address::from_str(fields[1]).map_err(Error::ParseAddressError)?;
+            let price = Decimal::from_str(fields[2]).map_err(Error::ParseDecimalError)?;
+            let spread = Decimal::from_str(fields[3]).map_err(Error::ParseDecimalError)?;
+            markets.insert((base, quote), (price, spread));
+        }
+        Ok(markets)
+    }
+}
+
+impl PricingStrategy for FixedSpread {
+    fn quote(&self, partial_order: &PartialOrder) -> Result<BucketContents, Error> {
+        let markets = self.load()?;
+        let base = partial_order.base_requirement.resource.address();
+        let quote = partial_order.quote_resource.address();
+        let (price, spread) = if let Some((price, spread)) = markets.get(&(base, quote)) {
+            (*price, *spread)
+        } else if let Some((price, spread)) = markets.get(&(quote, base)) {
+            (Decimal::one() / *price, *spread)
+        } else {
+            return Err(Error::PricingError(format!("no market for {} / {}", base, quote)));
+        };
+        quote_with_spread(partial_order, price, spread)
+    }
+}
+
+/// pricing strategy: quote a spread around the spot price of a pool component on the resim ledger
+///
+/// The spot price is the ratio of the quote and base resources held in the pool component's vaults (as in a constant
+/// product AMM).  Only vaults directly in the component state are counted, not those inside LazyMaps.
+#[derive(Parser, Debug)]
+pub struct LedgerPool {
+    /// component address of a pool holding both the base and quote resources
+    pool_component_address: String,
+    /// spread to apply around the spot price, eg. 0.01 for 1%
+    spread: String,
+}
+
+impl PricingStrategy for LedgerPool {
+    fn quote(&self, partial_order: &PartialOrder) -> Result<BucketContents, Error> {
+        let pool = Address::from_str(&self.pool_component_address).map_err(Error::ParseAddressError)?;
+        let spread = Decimal::from_str(&self.spread).map_err(Error::ParseDecimalError)?;
+
+        let ledger = RadixEngineDB::with_bootstrap(get_data_dir().map_err(Error::ResimError)?);
+        let base_balance = pool_balance(&ledger, pool, partial_order.base_requirement.resource.address())?;
+        let quote_balance = pool_balance(&ledger, pool, partial_order.quote_resource.address())?;
+        if base_balance == Decimal::zero() || quote_balance == Decimal::zero() {
+            return Err(Error::PricingError(format!(
+                "pool {} has no liquidity for this pair",
+                pool
+            )));
+        }
+
+        quote_with_spread(partial_order, quote_balance / base_balance, spread)
+    }
+}
+
+/// total amount of resource held in the vaults of a component's state on the ledger
+fn pool_balance(
+    ledger: &RadixEngineDB,
+    component_address: Address,
+    resource_address: Address,
+) -> Result<Decimal, Error> {
+    let component = ledger
+        .get_component(component_address)
+        .ok_or_else(|| Error::PricingError(format!("pool component {} not found", component_address)))?;
+    let state = validate_data(component.state())
+        .map_err(transaction_manifest::DecompileError::DataValidationError)
+        .map_err(Error::DecompileError)?;
+
+    let mut balance = Decimal::zero();
+    for vid in state.vaults.iter() {
+        if let Some(vault) = ledger.get_vault(&component_address, vid) {
+            if vault.resource_address() == resource_address {
+                balance = balance + vault.amount();
+            }
+        }
+    }
+    Ok(balance)
+}
+
+/* Request */
+
+/// used by the taker: send a request-for-quote file to a maker's `serve` daemon and print the signed order instruction to stdout
+///
+/// The output is the same as `make-signed-order` so it can be used in a transaction manifest (or with `tokenize-order`) directly
+#[derive(Parser, Debug)]
+pub struct Request {
+    /// address of the maker's `serve` daemon, eg. 127.0.0.1:7878
+    server_address: String,
+    /// path to file containing the SBOR-encoded PartialOrder bytes (ie. the `request-for-quote` output)
+    partial_order_file: PathBuf,
+}
+
+impl Request {
+    pub fn run(&self) -> Result<(), Error> {
+        let partial_order_bytes = fs::read(&self.partial_order_file).map_err(Error::IoError)?;
+
+        let mut stream = TcpStream::connect(&self.server_address).map_err(Error::IoError)?;
+        stream.write_all(&partial_order_bytes).map_err(Error::IoError)?;
+        stream.shutdown(Shutdown::Write).map_err(Error::IoError)?; // marks the end of the request
+
+        let mut reply = vec![];
+        stream.read_to_end(&mut reply).map_err(Error::IoError)?;
+        let (status, message) = reply
+            .split_first()
+            .ok_or_else(|| Error::RfqDeclined("empty reply".to_owned()))?;
+        let message = std::str::from_utf8(message).map_err(Error::Utf8Error)?;
+        if *status != 0 {
+            return Err(Error::RfqDeclined(message.to_owned()));
+        }
+
+        // print the instruction to stdout in Radix Transaction Manifest (rtm) format, see `make-signed-order`
+        print!("{}", message);
+
+        Ok(())
+    }
+}
+
 /* New Key Pair */
 
 /// used by the maker: generate new key pair for offline signing and online verification
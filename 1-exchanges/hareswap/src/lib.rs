@@ -20,7 +20,7 @@ pub mod api {
     pub use super::model::*;
     pub use super::requirement::*;
     pub use super::transporter::authentication::{sign, verify, VerifyError};
-    pub use super::transporter::voucher::Voucher;
+    pub use super::transporter::voucher::{revocation_message, Voucher};
 }
 
 /// only used for a demo probably would be in a seperate package if this were production-ready
@@ -23,6 +23,7 @@ blueprint! {
         redeem_auth: Vault,
         account: SharedAccount,
         account_auth: Vault,
+        fills: LazyMap<NonFungibleKey, Decimal>, // base amount filled so far for partially fillable orders, by voucher_key
     }
 
     impl Maker {
@@ -59,7 +60,8 @@ blueprint! {
                 order_def,
                 redeem_auth,
                 account: account.into(), // convert the Component (address) passed in to a SharedAccount for use by the default callback.  If the callback and account interface mismatch, panic ensues.
-                account_auth: Vault::with_bucket(account_auth)
+                account_auth: Vault::with_bucket(account_auth),
+                fills: LazyMap::new(),
             }.instantiate()
         }
 
@@ -87,6 +89,7 @@ blueprint! {
                 redeem_auth,
                 account: account.into(),
                 account_auth,
+                fills: LazyMap::new(),
             }.instantiate()
         }
 
@@ -220,8 +223,8 @@ blueprint! {
 
             debug!("execute_order_token: settle_order completed. now burn order token");
 
-            // burn by giving it back to the transporter (to burn the token and turn it back into a Voucher) and then just ignore the Voucher
-            let _ = self.transporter.make(order_tokens);
+            // burn by giving it back to the transporter (to burn the token and turn it back into a Voucher) and then just ignore the Voucher (so it's already expired)
+            let _ = self.transporter.make(order_tokens, 0, 0);
 
             debug!("execute_order_token: returning maker_bucket: {:?}", maker_bucket);
             maker_bucket
@@ -247,18 +250,24 @@ blueprint! {
                 order,
                 voucher_resource,
                 voucher_key,
+                voucher_expiry,
+                voucher_nonce,
                 signature,
             } = signed_order;
             // check taker_auth matches the order before redeeming it.  (if it matches but the signature is bad it wont redeem properly anyway.  This stops frontrunning)
             assert_eq!(order.partial_order.taker_auth.check_at_least_ref(&taker_auth), true, "tokenize_order: taker_auth not accepted");
 
+            // a partially fillable order is tracked by the fills here, not by the order token, so it can't be tokenized
+            assert!(!order.partially_fillable, "tokenize_order: partially fillable orders must use execute_partial_order");
+
             // rebuild a voucher from the SignedOrder contents (ie. the MatchedOrder data and voucher metadata)
-            let voucher = Voucher::from_nfd(voucher_resource, Some(voucher_key), order);
+            let voucher = Voucher::from_nfd(voucher_resource, Some(voucher_key), voucher_expiry, voucher_nonce, order);
 
             // and then rebuild the sealed_voucher by serializing and including the signature
             let sealed_voucher = voucher.to_sealed(signature);
 
             // "transport" the MatchedOrder back into existance by redeeming the voucher.   Only this Maker is allowed to use this Transporter (thanks to redeem_auth)
+            // The Transporter also refuses expired and revoked vouchers
             self.redeem_auth.authorize(|auth|
                 self.transporter.redeem(sealed_voucher, None, auth) // panics on bad vouchers
             )
@@ -275,5 +284,77 @@ blueprint! {
             self.execute_order_token(orders, from_taker)
         }
 
+        /// Execute a piece of a partially fillable SignedOrder sending in the "from Taker" Bucket and returning the "from Maker" Bucket.
+        ///
+        /// The signed base_requirement is the maximum size of the order.
+        /// fill_amount is the amount of the base asset traded in this piece
+        /// and the quote amount is scaled in proportion to it.  The callback
+        /// then settles an ordinary MatchedOrder for exactly this piece, so
+        /// the proportional amounts are checked the same way as a full order.
+        ///
+        /// The order is never tokenized.  Instead the signature is verified
+        /// directly and the amount filled so far is tracked against the
+        /// voucher_key to stop the order being filled past its maximum size.
+        /// This lets a single RFQ answer serve many small takers (as long as
+        /// they all hold taker_auth).
+        pub fn execute_partial_order(&mut self, signed_order: SignedOrder, fill_amount: Decimal, from_taker: Bucket, taker_auth: BucketRef) -> Bucket {
+            let SignedOrder {
+                order,
+                voucher_resource,
+                voucher_key,
+                voucher_expiry,
+                voucher_nonce,
+                signature,
+            } = signed_order;
+            assert!(order.partially_fillable, "execute_partial_order: order is not partially fillable, use execute_order");
+            assert_eq!(order.partial_order.taker_auth.check_at_least_ref(&taker_auth), true, "execute_partial_order: taker_auth not accepted");
+            taker_auth.drop();
+            assert_eq!(voucher_resource, self.order_def, "execute_partial_order: invalid voucher resource");
+
+            // verify the signature covers this order by rebuilding the sealed voucher exactly as tokenize_order does, but don't redeem it
+            // so the checks the Transporter would do when redeeming are repeated here
+            let voucher = Voucher::from_nfd(voucher_resource, Some(voucher_key.clone()), voucher_expiry, voucher_nonce, order.clone());
+            let _ = voucher.to_sealed(signature).unseal(&self.verifying_key); // panics on bad signatures
+            assert!(!self.transporter.is_revoked(voucher_nonce), "execute_partial_order: voucher nonce {} has been revoked", voucher_nonce);
+
+            let epoch = Context::current_epoch();
+            assert!(epoch <= voucher_expiry, "execute_partial_order: voucher expired at epoch {} (current epoch {})", voucher_expiry, epoch);
+            assert!(epoch <= order.deadline, "The order has expired.  Current epoch ({}) is past the order deadline ({})", epoch, order.deadline);
+
+            let (max_amount, quote_amount) = match (&order.partial_order.base_requirement.contents, &order.quote_contents) {
+                (BucketContents::Fungible(base), BucketContents::Fungible(quote)) => (*base, *quote),
+                _ => panic!("execute_partial_order: partial fills are only supported between Fungible resources"),
+            };
+            assert!(fill_amount > Decimal::zero(), "execute_partial_order: fill_amount must be positive");
+
+            // track the fill before settling, a panic anywhere below reverts it anyway
+            let filled = self.fills.get(&voucher_key).unwrap_or(Decimal::zero());
+            assert!(filled + fill_amount <= max_amount, "execute_partial_order: fill of {} exceeds the remaining order size {}", fill_amount, max_amount - filled);
+            self.fills.insert(voucher_key, filled + fill_amount);
+
+            // shrink the order down to this piece
+            let mut fill_order = order;
+            fill_order.partial_order.base_requirement.contents = BucketContents::Fungible(fill_amount);
+            fill_order.quote_contents = BucketContents::Fungible(quote_amount * fill_amount / max_amount);
+            debug!("execute_partial_order: filling {} of {} (already filled {})", fill_amount, max_amount, filled);
+
+            self.settle_order(fill_order, from_taker)
+        }
+
+        /// Revoke every SignedOrder with voucher_nonce so it can no longer be executed or tokenized, eg. for a stale quote or a compromised order
+        ///
+        /// The signer authorizes this off-ledger with the same key used to sign orders: signature must be
+        /// over `revocation_message(order_resource, voucher_nonce)` (see `hare revoke-order`).  Order tokens which were already
+        /// tokenized are not affected.
+        pub fn revoke_voucher(&mut self, voucher_nonce: u64, signature: Vec<u8>) {
+            self.transporter.revoke(voucher_nonce, signature);
+            info!("revoke_voucher: revoked voucher nonce {}", voucher_nonce);
+        }
+
+        /// Returns the base amount already filled for the partially fillable order with voucher_key
+        pub fn get_filled_amount(&self, voucher_key: NonFungibleKey) -> Decimal {
+            self.fills.get(&voucher_key).unwrap_or(Decimal::zero())
+        }
+
     }
 }
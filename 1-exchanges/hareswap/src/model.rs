@@ -59,6 +59,10 @@ pub struct MatchedOrder {
     pub maker_callback: Callback,
     /// The order deadline, specified as the last (greatest) epoch where the order can still be executed
     pub deadline: u64,
+    /// true if the base_requirement is a maximum size which may be filled in several pieces, each paying a
+    /// proportional share of quote_contents.  Both must be Fungible.  The amount already filled is tracked
+    /// on-ledger by the Maker against the SignedOrder's voucher_key
+    pub partially_fillable: bool,
 }
 
 /// mod signed_order contains only the SignedOrder struct because...
@@ -83,6 +87,10 @@ mod signed_order {
         pub order: MatchedOrder,
         pub voucher_resource: ResourceDef,
         pub voucher_key: NonFungibleKey,
+        /// the last epoch the Voucher can be redeemed (normally the same as the order deadline)
+        pub voucher_expiry: u64,
+        /// lets the signer revoke the order before it is executed, see `Maker::revoke_voucher`
+        pub voucher_nonce: u64,
         pub signature: Vec<u8>,
     }
 }
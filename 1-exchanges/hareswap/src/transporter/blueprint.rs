@@ -4,6 +4,10 @@
 //!
 //! Transporter utilizes the Voucher implemenation for most of the hard work.
 //!
+//! Vouchers expire after their expiry epoch and the signer can revoke them
+//! before that by nonce (see `revoke`), so a stale or compromised Voucher can
+//! be cancelled before it is redeemed.
+//!
 //! # LIMITATIONS
 //!
 //! This prototype only supports NonFungibles and a single one per Voucher.  It
@@ -13,6 +17,7 @@
 //! [Transporter]: blueprint::Transporter
 use scrypto::prelude::*;
 
+use super::authentication::verify_or_panic;
 use super::voucher::*;
 
 /// Used to verify the presented mint/burn authority badges work propertly as a safeguard
@@ -29,6 +34,7 @@ blueprint! {
         count: u128,
         public_key: EcdsaPublicKey,
         redeem_auth: ResourceDef, // avoid duplicates/frontrunning
+        revoked: LazyMap<u64, u64>, // revoked Voucher nonces and the epoch they were revoked
     }
 
     impl Transporter {
@@ -92,6 +98,7 @@ blueprint! {
                 count: 0,
                 public_key,
                 redeem_auth,
+                revoked: LazyMap::new(),
             }
             .instantiate()
         }
@@ -128,6 +135,23 @@ blueprint! {
             self.voucher_redeem(sealed_voucher.unseal(&self.public_key), Some(self.count.into()))
         }
 
+        /// Revoke every Voucher with nonce so it can no longer be redeemed
+        ///
+        /// Authorized by the same key that signs the Vouchers: signature must be over `revocation_message(resource, nonce)`
+        /// with the address of the resource this Transporter mints.
+        /// This way the signer can cancel a Voucher from off-ledger without holding any badge.
+        pub fn revoke(&mut self, nonce: u64, signature: Vec<u8>) {
+            verify_or_panic(&self.public_key, &revocation_message(&self.resource_def.address(), nonce), &signature);
+            if self.revoked.get(&nonce).is_none() {
+                self.revoked.insert(nonce, Context::current_epoch());
+            }
+        }
+
+        /// return true if Vouchers with nonce have been revoked
+        pub fn is_revoked(&self, nonce: u64) -> bool {
+            self.revoked.get(&nonce).is_some()
+        }
+
         /// Dispose of the bucket and makes an unsigned opaque Voucher to be signed in it's place.
         ///
         /// LIMITATION: only handles a bucket with single NonFungible
@@ -136,8 +160,8 @@ blueprint! {
         /// exist for sure.  HareSwap does not make use of this functionality.
         /// See `voucher_make` for the full implementation.  This wrapper exists
         /// to keep Voucher out of the function signature
-        pub fn make(&mut self, bucket: Bucket) -> Vec<u8> {
-            scrypto_encode(&self.voucher_make(bucket))
+        pub fn make(&mut self, bucket: Bucket, expiry_epoch: u64, nonce: u64) -> Vec<u8> {
+            scrypto_encode(&self.voucher_make(bucket, expiry_epoch, nonce))
         }
 
         /* non-public functionality */
@@ -148,6 +172,7 @@ blueprint! {
         /// sensitive, hence it is not public on purpose.  Because Voucher can't
         /// be in public signatures it helps us not make tha mistake too.
         fn voucher_redeem(&mut self, v: Voucher, optional_key: Option<NonFungibleKey>) -> Bucket {
+            assert!(!self.is_revoked(v.nonce()), "Transporter::voucher_redeem: voucher nonce {} has been revoked", v.nonce());
             self.mint_authority.authorize(
                 |auth| v.redeem(&self.resource_def, optional_key, auth)
             )
@@ -156,7 +181,7 @@ blueprint! {
         /// not public implementation for `make`.  Only non-public because of the Voucher return type
         ///
         /// LIMITATION: only handles a bucket with single NonFungible
-        fn voucher_make(&mut self, bucket: Bucket) -> Voucher {
+        fn voucher_make(&mut self, bucket: Bucket, expiry_epoch: u64, nonce: u64) -> Voucher {
             assert_eq!(bucket.amount(), Decimal::one(), "Transporter::voucher_make: only supports transportation of one nonFungibles per Voucher (for now)");
             assert_eq!(bucket.resource_def().resource_type(), ResourceType::NonFungible, "Transporter::voucher_make: only supports transportation of NonFungibles (for now)");
 
@@ -175,7 +200,7 @@ blueprint! {
                     resource_def.burn_with_auth(bucket, auth)
                 );
 
-                return Voucher::from_nfd(resource_def, key, nfd);  // purposeful "return".  More complex implemenation would handle multiple nfds
+                return Voucher::from_nfd(resource_def, key, expiry_epoch, nonce, nfd);  // purposeful "return".  More complex implemenation would handle multiple nfds
             };
             panic!("unreachable"); // asserted 1 in bucket
         }
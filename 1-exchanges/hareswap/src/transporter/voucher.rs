@@ -84,6 +84,10 @@ pub struct Voucher {
     resource_def: ResourceDef,
     /// the NonFungibleKey for the asset the Voucher represents.  Optional because the redeemer may be expected to provide that information
     key: Option<NonFungibleKey>,
+    /// the last (greatest) epoch where the Voucher can still be redeemed
+    expiry_epoch: u64,
+    /// chosen by the signer so the Voucher can be revoked before it is redeemed.  See `revocation_message`
+    nonce: u64,
     /// the NonFungibleData for the asset the Voucher represents, but serialized as a PassThruNFD to make everything work at runtime.
     nfd: PassThruNFD,
 }
@@ -97,9 +101,11 @@ impl Voucher {
     ///
     /// auth must have MAY_MINT authority for the resource (and of course the resource must be MINTABLE)
     ///
+    /// Checking the nonce has not been revoked is up to the caller, see `nonce`
+    ///
     /// # PANICS
     ///
-    /// Will panic if requirements arguments don't match the Voucher or if anything goes wrong while minting.
+    /// Will panic if requirements arguments don't match the Voucher, if the Voucher has expired or if anything goes wrong while minting.
     pub fn redeem(
         self,
         required_resource_def: &ResourceDef,
@@ -109,12 +115,21 @@ impl Voucher {
         let Voucher {
             mut resource_def,
             key,
+            expiry_epoch,
+            nonce: _,
             nfd,
         } = self;
         assert_eq!(
             resource_def, *required_resource_def,
             "Voucher::redeem: resource requirement not met"
         );
+        let epoch = Context::current_epoch();
+        assert!(
+            epoch <= expiry_epoch,
+            "Voucher::redeem: expired at epoch {} (current epoch {})",
+            expiry_epoch,
+            epoch
+        );
         // test key against required key if both exist, otherwise use whichever is given.  panic if both are None
         let key = match required_key {
             None => key.unwrap(),
@@ -132,15 +147,29 @@ impl Voucher {
     /// create a Voucher from any NonFungibleData with the the included metadata
     ///
     /// Including Some key will ensure that key is the only value which can be redeemed.
-    /// A None key lets the redeemer decide the key
-    pub fn from_nfd<T: NonFungibleData>(resource_def: ResourceDef, key: Option<NonFungibleKey>, nfd: T) -> Voucher {
+    /// A None key lets the redeemer decide the key.
+    /// The Voucher can be redeemed up to and including expiry_epoch, unless nonce is revoked first.
+    pub fn from_nfd<T: NonFungibleData>(
+        resource_def: ResourceDef,
+        key: Option<NonFungibleKey>,
+        expiry_epoch: u64,
+        nonce: u64,
+        nfd: T,
+    ) -> Voucher {
         Voucher {
             resource_def,
             key,
+            expiry_epoch,
+            nonce,
             nfd: nfd.as_passthru(), // calling as_passthru is the "trick" and is an implementation detail for how Vouchers work which can be ignored for users.
         }
     }
 
+    /// the nonce the signer chose for this Voucher
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
     /// create a SealedVoucher from this Voucher and an opaque signature
     pub fn to_sealed(&self, signature: Vec<u8>) -> SealedVoucher {
         SealedVoucher {
@@ -163,15 +192,23 @@ impl PrivateDecode for Voucher {
         if index != ::sbor::type_id::FIELDS_TYPE_NAMED {
             return Err(::sbor::DecodeError::InvalidIndex(index));
         }
-        decoder.check_len(3)?;
+        decoder.check_len(5)?;
         let resource_def = ResourceDef::decode(decoder)?;
         let key = Option::<NonFungibleKey>::decode(decoder)?;
+        let expiry_epoch = u64::decode(decoder)?;
+        let nonce = u64::decode(decoder)?;
         // cannot derive Decode for Voucher because the decode method is
         // implemented for both  NonFungibleData and Decode traits.
         // Disambiguate here using "as sbor::Decode"
         let nfd = <PassThruNFD as sbor::Decode>::decode(decoder)?;
         decoder.check_end()?;
-        Ok(Voucher { resource_def, key, nfd })
+        Ok(Voucher {
+            resource_def,
+            key,
+            expiry_epoch,
+            nonce,
+            nfd,
+        })
     }
 }
 
@@ -195,3 +232,11 @@ impl SealedVoucher {
         private_decode_with_type(&self.serialized).unwrap()
     }
 }
+
+/// The message the signer signs to revoke every Voucher with nonce for the Transporter of voucher_resource.
+///
+/// It is plain text so it can never be mistaken for a serialized Voucher (and the signature for one used as the other).
+/// The resource address binds it to one Transporter, so it can't be replayed on another one sharing the signing key
+pub fn revocation_message(voucher_resource: &Address, nonce: u64) -> Vec<u8> {
+    format!("hareswap: revoke voucher nonce {} of {}", nonce, voucher_resource).into_bytes()
+}
@@ -56,15 +56,106 @@ pub struct OrderTicket {
   pub order_currency: String
 }
 
+/// A single fill between a buy and a sell order
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct Trade {
+    /// Price (in market's currency) the tokens were traded at
+    pub price: Decimal,
+    /// Amount of tokens traded
+    pub size: Decimal,
+    /// Epoch in which the fill happened
+    pub epoch: u64
+}
+
+/// Open, high, low, close and volume of all trades within a range of epochs
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct Candle {
+    /// First epoch covered by this candle
+    pub start_epoch: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Total amount of tokens traded
+    pub volume: Decimal
+}
+
+impl Candle {
+    fn new(start_epoch: u64, trade: &Trade) -> Candle {
+        Candle {
+            start_epoch: start_epoch,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size
+        }
+    }
+
+    fn add(&mut self, trade: &Trade) {
+        if trade.price > self.high { self.high = trade.price }
+        if trade.price < self.low { self.low = trade.price }
+
+        self.close = trade.price;
+        self.volume += trade.size;
+    }
+
+    /// Extends this candle with a later one
+    fn merge(&mut self, later: &Candle) {
+        if later.high > self.high { self.high = later.high }
+        if later.low < self.low { self.low = later.low }
+
+        self.close = later.close;
+        self.volume += later.volume;
+    }
+}
+
+/// Maximum number of recent trades kept per asset
+pub const MAX_TRADES: usize = 100;
+/// Maximum number of one epoch candles kept per asset
+pub const MAX_CANDLES: usize = 1000;
+
+/// Bounded price history of an asset, rolled forward as fills arrive
+#[derive(Debug, TypeId, Encode, Decode, Describe)]
+pub struct PriceHistory {
+    /// The most recent fills, oldest first (at most MAX_TRADES)
+    trades: Vec<Trade>,
+    /// One candle per epoch with fills, oldest first (at most MAX_CANDLES)
+    candles: Vec<Candle>
+}
+
+impl PriceHistory {
+    fn new() -> PriceHistory {
+        PriceHistory { trades: vec![], candles: vec![] }
+    }
+
+    fn add(&mut self, trade: Trade) {
+        match self.candles.last_mut() {
+            Some(candle) if candle.start_epoch == trade.epoch => candle.add(&trade),
+            _ => self.candles.push(Candle::new(trade.epoch, &trade))
+        }
+        if self.candles.len() > MAX_CANDLES {
+            self.candles.remove(0);
+        }
+
+        self.trades.push(trade);
+        if self.trades.len() > MAX_TRADES {
+            self.trades.remove(0);
+        }
+    }
+}
+
 #[derive(Debug, TypeId, Encode, Decode, Describe)]
 pub struct MarketPrices {
-    asset_prices: HashMap<String, Decimal>
+    asset_prices: HashMap<String, Decimal>,
+    /// Recent fills and candles per asset
+    asset_history: HashMap<String, PriceHistory>
 }
 
 #[allow(dead_code)]
 impl MarketPrices {
     pub fn new() -> MarketPrices {
-        MarketPrices { asset_prices: HashMap::new() }
+        MarketPrices { asset_prices: HashMap::new(), asset_history: HashMap::new() }
     }
 
     pub fn assets(&self) -> Vec<String> {
@@ -76,13 +167,57 @@ impl MarketPrices {
     }
 
     pub fn for_address(&self, address: Address) -> Option<Decimal> {
-        let resource_def = ResourceDef::from(address);
-        let asset_symbol = resource_def.metadata()["symbol"].clone();
-
-        self.get(asset_symbol)
+        self.get(MarketPrices::symbol(address))
     }
 
     pub fn update(&mut self, asset_symbol: String, price: Decimal) {
         self.asset_prices.insert(asset_symbol, price);
     }
+
+    /// Records a fill in the current epoch and makes its price the asset's market price.
+    pub fn record(&mut self, asset_symbol: String, price: Decimal, size: Decimal) {
+        let trade = Trade { price: price, size: size, epoch: Context::current_epoch() };
+
+        self.asset_history.entry(asset_symbol.clone()).or_insert_with(PriceHistory::new).add(trade);
+        self.update(asset_symbol, price);
+    }
+
+    /// Returns up to `count` (at most MAX_TRADES) of the most recent trades of the given asset, oldest first.
+    pub fn last_trades(&self, address: Address, count: usize) -> Vec<Trade> {
+        match self.asset_history.get(&MarketPrices::symbol(address)) {
+            Some(history) => history.trades[history.trades.len().saturating_sub(count)..].to_vec(),
+            None => vec![]
+        }
+    }
+
+    /// Aggregates the one epoch candles of the given asset into candles spanning `epochs_per_candle` epochs each,
+    /// oldest first. Epoch ranges without any trades are skipped, only the last MAX_CANDLES epochs with trades
+    /// are covered.
+    pub fn candles(&self, address: Address, epochs_per_candle: u64) -> Vec<Candle> {
+        assert!(epochs_per_candle > 0, "Candles must span at least one epoch");
+
+        let mut candles: Vec<Candle> = vec![];
+
+        let epoch_candles = match self.asset_history.get(&MarketPrices::symbol(address)) {
+            Some(history) => history.candles.as_slice(),
+            None => &[]
+        };
+
+        for epoch_candle in epoch_candles {
+            let start_epoch = epoch_candle.start_epoch - epoch_candle.start_epoch % epochs_per_candle;
+
+            match candles.last_mut() {
+                Some(candle) if candle.start_epoch == start_epoch => candle.merge(epoch_candle),
+                _ => candles.push(Candle { start_epoch: start_epoch, ..epoch_candle.clone() })
+            }
+        }
+
+        candles
+    }
+
+    fn symbol(address: Address) -> String {
+        let resource_def = ResourceDef::from(address);
+
+        resource_def.metadata()["symbol"].clone()
+    }
 }
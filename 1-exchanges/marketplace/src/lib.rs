@@ -128,47 +128,53 @@ blueprint! {
         }
 
         fn fill_order(&mut self, order: &mut Order) {
-            let mut last_price: Option<Decimal> = None;
+            let mut fills: Vec<(Decimal, Decimal)> = vec![];
 
             for matched_order in self.matching_orders(order) {
                 let price: Decimal = matched_order.price;
 
-                Market::fill_matched_order(order, matched_order, price);
+                let size = Market::fill_matched_order(order, matched_order, price);
 
-                last_price = Some(price);
+                fills.push((price, size));
 
                 if order.is_filled() {
                     break;
                 }
             }
 
-            for price in last_price {
-                self.market_prices.update(order.token_symbol(), price);
+            for (price, size) in fills {
+                self.market_prices.record(order.token_symbol(), price, size);
             }
         }
 
-        fn fill_matched_order(order_a: &mut Order, order_b: &mut Order, price: Decimal) {
+        /// Fills the matched buy and sell orders as far as possible and returns the amount of tokens traded.
+        fn fill_matched_order(order_a: &mut Order, order_b: &mut Order, price: Decimal) -> Decimal {
             assert!(order_a.is_buy_order() ^ order_b.is_buy_order(), "Expected a buy and a sell order.");
 
             if order_a.is_buy_order() && order_b.is_sell_order() {
-                Market::fill_sell_order(order_b, order_a, price);
+                Market::fill_sell_order(order_b, order_a, price)
             } else {
-                Market::fill_sell_order(order_a, order_b, price);
+                Market::fill_sell_order(order_a, order_b, price)
             }
         }
 
-        fn fill_sell_order(sell_order: &mut Order, buy_order: &mut Order, price: Decimal) {
+        fn fill_sell_order(sell_order: &mut Order, buy_order: &mut Order, price: Decimal) -> Decimal {
             let full_payment_amount = price * sell_order.purse.amount();
 
-            if full_payment_amount <= buy_order.payment.amount() {
+            let tokens = if full_payment_amount <= buy_order.payment.amount() {
                 sell_order.payment.put(buy_order.payment.take(full_payment_amount));
-                buy_order.purse.put(sell_order.purse.take_all());
+                sell_order.purse.take_all()
             } else {
                 let partial_token_amount = buy_order.payment.amount() / price;
 
                 sell_order.payment.put(buy_order.payment.take_all());
-                buy_order.purse.put(sell_order.purse.take(partial_token_amount));
-            }
+                sell_order.purse.take(partial_token_amount)
+            };
+            let size = tokens.amount();
+
+            buy_order.purse.put(tokens);
+
+            size
         }
 
         /// Given the right order ticket withdraws an order from the market.
@@ -322,5 +328,36 @@ blueprint! {
 
             info!(" \\-----------------/");
         }
+
+        /// Returns up to `count` of the most recent fills of the given token, oldest first.
+        pub fn get_last_trades(&self, token: Address, count: u32) -> Vec<Trade> {
+            self.market_prices.last_trades(token, count as usize)
+        }
+
+        /// Returns OHLCV candles of the given token, each one spanning `epochs_per_candle` epochs, oldest first.
+        pub fn get_candles(&self, token: Address, epochs_per_candle: u64) -> Vec<Candle> {
+            self.market_prices.candles(token, epochs_per_candle)
+        }
+
+        pub fn print_candles(&self, token: Address, epochs_per_candle: u64) {
+            info!(" /'''''''''''''''''''''''' CANDLES '''''''''''''''''''''''''\\");
+            info!(" +----------------------------------------------------------+");
+            info!(" | Epoch  |  Open   |  High   |   Low   |  Close  | Volume  |");
+            info!(" +----------------------------------------------------------+");
+
+            for candle in self.market_prices.candles(token, epochs_per_candle) {
+                info!(
+                    " | {:>6} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} |",
+                    candle.start_epoch,
+                    self.truncate(candle.open.to_string(), 7),
+                    self.truncate(candle.high.to_string(), 7),
+                    self.truncate(candle.low.to_string(), 7),
+                    self.truncate(candle.close.to_string(), 7),
+                    self.truncate(candle.volume.to_string(), 7)
+                );
+            }
+
+            info!(" \\----------------------------------------------------------/");
+        }
     }
 }
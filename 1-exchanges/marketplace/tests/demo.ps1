@@ -32,6 +32,11 @@ filter Get-Market-Price([String]$symbol) {
   $_ | Select-String "$symbol \|\s*(\d\.\d+).*" | %{ $_.Matches.Groups[1].Value }
 }
 
+# open, high, low, close and volume (integer part) of the candle starting at the given epoch
+filter Get-Candle([String]$epoch) {
+  $_ | Select-String "\|\s*$epoch \|\s*([\d.]+) \|\s*([\d.]+) \|\s*([\d.]+) \|\s*([\d.]+) \|\s*(\d+)[\d.]* \|" | %{ $_.Matches.Groups[1..5].Value -join ' ' }
+}
+
 function Wait-For-User([String]$message) {
   Write-Output ''
   Write-Host -NoNewLine "$message";
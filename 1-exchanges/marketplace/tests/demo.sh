@@ -36,6 +36,10 @@ function Get-Market-Price {
   grep "$1" | cut -d'|' -f3 | xargs
 }
 
+function Get-Candle {
+  grep -E "\| +$1 \|" | cut -d'|' -f3-7 | tr '|' ' ' | xargs | sed -r 's/\.[0-9]+$//'
+}
+
 function Wait-For-User {
   echo
   read -n 1 -s -r -p "$1"
@@ -64,7 +68,7 @@ function Exit-Unless-Equal {
 # We use export (and global variables in ps1) so users can inspect the saved values
 # after sourcing the demo script.
 BASH_STEPS_FILE=`tempfile`
-cat ./tests/steps.ps1 | sed -e 's/^\$/export /g' -e 's/ = /=`/g' -e '/=`/s/.$/`/g' | tr -d "\r" > $BASH_STEPS_FILE
+cat ./tests/steps.ps1 | sed -e 's/^\$/export /g' -e 's/ = /=`/g' -e '/=`/s/\r\?$/`/g' | tr -d "\r" > $BASH_STEPS_FILE
 
 set -e
 
@@ -79,6 +79,14 @@ $MARKET_PRICE = resim call-method $XRD_MARKET print_market_prices | Get-Market-P
 
 Exit-Unless-Equal $MARKET_PRICE "3.29" "Failure... (wrong market price) :("
 
+# every fill is recorded, so the market also yields recent trades and OHLCV candles (one per epoch here)
+resim call-method $XRD_MARKET get_last_trades $USDT 3
+
+# BO-2 filled at 3.27, then SO-3 at 3.26 and SO-4 at 3.29, around 183 USDT traded in epoch 0
+$CANDLE = resim call-method $XRD_MARKET print_candles $USDT 1 | Get-Candle 0
+
+Exit-Unless-Equal "$CANDLE" "3.27 3.29 3.26 3.29 183" "Failure... (wrong candle) :("
+
 Write-Output ""
 Write-Output "Market price is 3.29 as last sell order filled was SO-4"
 Write-Output ""
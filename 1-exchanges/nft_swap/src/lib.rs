@@ -1,5 +1,43 @@
+use sbor::*;
 use scrypto::prelude::*;
 
+//Number of epochs share holders have to vote on a buyout before it succeeds
+const BUYOUT_DURATION: u64 = 100;
+
+//A buyout bid for a whole NFT of the collection vault
+#[derive(Debug, TypeId, Encode, Decode, Describe)]
+pub struct Buyout {
+    //id of the buyout, written in the bidder badge and the vote receipts
+    id: u64,
+
+    //key of the NFT the bidder wants
+    key: NonFungibleKey,
+
+    //the buyout succeeds at this epoch unless share holders rejected it before
+    end_epoch: u64,
+
+    //supply of the nft shares when the buyout was bid, shares can't be minted until it is settled
+    voting_supply: Decimal,
+
+    //shares locked to approve or reject the buyout
+    approvals: Decimal,
+    rejections: Decimal,
+}
+
+//Given to the bidder, claims the NFT or the refund once the buyout is settled
+#[derive(NonFungibleData)]
+pub struct BidderBadge {
+    buyout: u64,
+}
+
+//Given to a voter, withdraws the locked shares once the buyout is settled
+#[derive(NonFungibleData)]
+pub struct VoteReceipt {
+    buyout: u64,
+    shares_address: Address,
+    shares: Decimal,
+}
+
 #[derive(NonFungibleData)]
 pub struct NftCollection{
     #[scrypto(mutable)]
@@ -28,14 +66,61 @@ blueprint! {
         //Royalties come from 10% of the swap price and are stored in this vault
         nft_royalty_vault: Vault,
 
-        //Resource definition of a nft share
+        //Resource definition of the nft shares minted from now on
         nft_shares_def: ResourceDef,
 
+        //Every nft shares resource issued, with the buyout proceeds each of its shares redeems.
+        //A new resource is issued after each successful buyout so shares minted later get no part of it
+        nft_shares_proceeds: HashMap<Address, Decimal>,
+
+        //Lowest bid accepted for a buyout
+        buyout_reserve_price: Decimal,
+
+        //Badge allowed to change the reserve price, none for components created with `new`
+        buyout_admin_badge: Option<Address>,
+
+        //Buyout being voted on, only one at a time
+        buyout: Option<Buyout>,
+
+        //Number of buyouts bid so far, used as buyout id
+        buyout_count: u64,
+
+        //XRD of the buyout bid being voted on
+        buyout_bid_vault: Vault,
+
+        //Shares locked by the voters, by nft shares resource
+        buyout_votes_vaults: HashMap<Address, Vault>,
+
+        //XRD of the successful buyouts, redeemable with nft shares
+        buyout_proceeds_vault: Vault,
+
+        //NFT or refund of each settled buyout, claimed with the bidder badge
+        buyout_claims: HashMap<u64, Vault>,
+
+        //Bidder badges and vote receipts, minted and burnt with the nft admin badge
+        bidder_badge_def: ResourceDef,
+        vote_receipt_def: ResourceDef,
+        receipt_count: u128,
     }
 
     impl Hello {
         
         pub fn new() -> Component {
+            Self::create(1000.into(), None)
+        }
+
+        //Same as `new` with a badge allowed to change the buyout reserve price
+        pub fn new_with_admin(buyout_reserve_price: Decimal) -> (Component, Bucket) {
+
+            let buyout_admin_badge: Bucket = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "Buyout Admin Badge")
+                .initial_supply_fungible(1);
+
+            let component: Component = Self::create(buyout_reserve_price, Some(buyout_admin_badge.resource_address()));
+            (component, buyout_admin_badge)
+        }
+
+        fn create(buyout_reserve_price: Decimal, buyout_admin_badge: Option<Address>) -> Component {
 
             //Creating admin badge to mint and burn nft shares and increment mutable nft metadata
             let nft_admin_badge: Bucket = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
@@ -43,12 +128,9 @@ blueprint! {
                 .initial_supply_fungible(1);
             
             //Creating nft shares that are mintable and burnable
-            let nft_shares_def: ResourceDef = ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
-                .metadata("name", "NFT Shares")
-                .metadata("symbol", "nfts")
-                .flags(BURNABLE | MINTABLE)
-                .badge(nft_admin_badge.resource_address(), MAY_BURN | MAY_MINT)
-                .no_initial_supply();
+            let nft_shares_def: ResourceDef = Self::create_shares_def(nft_admin_badge.resource_address());
+            let mut nft_shares_proceeds: HashMap<Address, Decimal> = HashMap::new();
+            nft_shares_proceeds.insert(nft_shares_def.address(), Decimal::zero());
 
             // Creates a fixed set of NFTs 
             let nft_collection :Bucket = ResourceBuilder::new_non_fungible()
@@ -77,6 +159,17 @@ blueprint! {
                     ),
                 ]);
 
+            //Creating the bidder badges and vote receipts of the buyouts
+            let bidder_badge_def: ResourceDef = ResourceBuilder::new_non_fungible()
+                .metadata("name", "Buyout Bidder Badge")
+                .flags(MINTABLE | BURNABLE)
+                .badge(nft_admin_badge.resource_address(), MAY_MINT | MAY_BURN)
+                .no_initial_supply();
+            let vote_receipt_def: ResourceDef = ResourceBuilder::new_non_fungible()
+                .metadata("name", "Buyout Vote Receipt")
+                .flags(MINTABLE | BURNABLE)
+                .badge(nft_admin_badge.resource_address(), MAY_MINT | MAY_BURN)
+                .no_initial_supply();
                   
             let component:Component = Self {
                 collected_xrd: Vault::new(RADIX_TOKEN),
@@ -86,6 +179,18 @@ blueprint! {
                 nft_price: 1000.into(),
                 nft_royalty_vault:Vault::new(RADIX_TOKEN),
                 nft_shares_def: nft_shares_def,
+                nft_shares_proceeds: nft_shares_proceeds,
+                buyout_reserve_price: buyout_reserve_price,
+                buyout_admin_badge: buyout_admin_badge,
+                buyout: None,
+                buyout_count: 0,
+                buyout_bid_vault: Vault::new(RADIX_TOKEN),
+                buyout_votes_vaults: HashMap::new(),
+                buyout_proceeds_vault: Vault::new(RADIX_TOKEN),
+                buyout_claims: HashMap::new(),
+                bidder_badge_def: bidder_badge_def,
+                vote_receipt_def: vote_receipt_def,
+                receipt_count: 0,
             } 
             
             .instantiate();
@@ -93,7 +198,20 @@ blueprint! {
             return component;   
         }
 
+        //Nft shares are mintable and burnable by the nft admin badge
+        fn create_shares_def(nft_admin_badge: Address) -> ResourceDef {
+            ResourceBuilder::new_fungible(DIVISIBILITY_MAXIMUM)
+                .metadata("name", "NFT Shares")
+                .metadata("symbol", "nfts")
+                .flags(BURNABLE | MINTABLE)
+                .badge(nft_admin_badge, MAY_BURN | MAY_MINT)
+                .no_initial_supply()
+        }
+
         pub fn buy_nft(&mut self, key:NonFungibleKey, mut payment:Bucket) -> (Bucket, Bucket) {
+            //an NFT under buyout can't be sold
+            assert!(self.buyout.as_ref().map_or(true, |buyout| buyout.key != key), "NFT under buyout");
+
             //take the cost of nft out of payment Bucket
             self.collected_xrd.put(payment.take(self.nft_price));
 
@@ -118,6 +236,9 @@ blueprint! {
         }
 
         pub fn mint_nft_shares(&mut self, sellers_address: Address, mut buyers_funds:Bucket) ->Bucket {
+
+            //shares minted during a buyout would vote on it
+            assert!(self.buyout.is_none(), "Shares can't be minted while a buyout is voted on");
             
             //adds sellers address to vector
             self.nft_owners.push(sellers_address);
@@ -156,11 +277,174 @@ blueprint! {
             //Finds the Decimal amount of nft shares in Bucket argument
             let number_of_shares: Decimal = nft_shares.amount();
 
+            //Part of the buyout proceeds of these shares, depends on the nft shares resource
+            let proceeds_per_share: Decimal = *self.nft_shares_proceeds.get(&nft_shares.resource_address()).expect("Not nft shares");
+            let proceeds_amount: Decimal = std::cmp::min(number_of_shares * proceeds_per_share, self.buyout_proceeds_vault.amount());
+
             //Burns the shares nft shares in the bucket
-            self.nft_admin_badge.authorize(|auth| {self.nft_shares_def.burn_with_auth(nft_shares, auth)});
+            self.nft_admin_badge.authorize(|auth| {nft_shares.burn_with_auth(auth)});
             
             //Takes the 1:1 equivalant nft shares : XRD from vault 
-            self.nft_royalty_vault.take(number_of_shares)
+            let mut xrd: Bucket = self.nft_royalty_vault.take(number_of_shares);
+
+            //Adds the part of the buyout proceeds
+            xrd.put(self.buyout_proceeds_vault.take(proceeds_amount));
+            xrd
         } 
+
+        pub fn bid_buyout(&mut self, key:NonFungibleKey, bid:Bucket) -> Bucket {
+
+            //only one buyout can be voted on at a time
+            assert!(self.buyout.is_none(), "A buyout is already in progress");
+
+            //the NFT must be in the collection vault
+            assert!(self.nft_collection_vault.get_non_fungible_keys().contains(&key), "NFT not in the vault");
+
+            //bid must be in XRD and reach the reserve price
+            assert!(bid.resource_address() == RADIX_TOKEN, "Bid must be in XRD");
+            assert!(bid.amount() >= self.buyout_reserve_price, "Bid below the reserve price");
+
+            //someone must hold shares to receive the proceeds
+            let voting_supply: Decimal = self.shares_supply();
+            assert!(voting_supply > Decimal::zero(), "No share holder");
+
+            //keeps the bid until the buyout is settled
+            self.buyout_bid_vault.put(bid);
+
+            self.buyout_count += 1;
+            self.buyout = Some(Buyout {
+                id: self.buyout_count,
+                key: key,
+                end_epoch: Context::current_epoch() + BUYOUT_DURATION,
+                voting_supply: voting_supply,
+                approvals: Decimal::zero(),
+                rejections: Decimal::zero(),
+            });
+
+            //the bidder claims the NFT or the refund with this badge
+            self.receipt_count += 1;
+            let key: NonFungibleKey = NonFungibleKey::from(self.receipt_count);
+            let bidder_badge: BidderBadge = BidderBadge {
+                buyout: self.buyout_count,
+            };
+            let bidder_badge_def: ResourceDef = self.bidder_badge_def.clone();
+            self.nft_admin_badge.authorize(|auth| {bidder_badge_def.mint_non_fungible(&key, bidder_badge, auth)})
+        }
+
+        pub fn vote_buyout(&mut self, nft_shares:Bucket, approve:bool) -> Bucket {
+
+            let buyout: &mut Buyout = self.buyout.as_mut().expect("No buyout in progress");
+            assert!(Context::current_epoch() < buyout.end_epoch, "Voting period is over");
+            let shares_address: Address = nft_shares.resource_address();
+            assert!(self.nft_shares_proceeds.contains_key(&shares_address), "Only nft shares can vote");
+
+            //counts the shares
+            let number_of_shares: Decimal = nft_shares.amount();
+            if approve {
+                buyout.approvals += number_of_shares;
+            } else {
+                buyout.rejections += number_of_shares;
+            }
+            let vote_receipt: VoteReceipt = VoteReceipt {
+                buyout: buyout.id,
+                shares_address: shares_address,
+                shares: number_of_shares,
+            };
+
+            //locks the shares until the buyout is settled
+            self.buyout_votes_vaults.entry(shares_address).or_insert_with(|| Vault::new(shares_address)).put(nft_shares);
+
+            //the voter withdraws the shares with this receipt
+            self.receipt_count += 1;
+            let key: NonFungibleKey = NonFungibleKey::from(self.receipt_count);
+            let vote_receipt_def: ResourceDef = self.vote_receipt_def.clone();
+            self.nft_admin_badge.authorize(|auth| {vote_receipt_def.mint_non_fungible(&key, vote_receipt, auth)})
+        }
+
+        pub fn settle_buyout(&mut self) {
+
+            let buyout: &Buyout = self.buyout.as_ref().expect("No buyout in progress");
+
+            //a majority of the shares existing at the bid decides before the end of the voting period, else the buyout succeeds
+            let supply: Decimal = self.shares_supply();
+            let half_supply: Decimal = buyout.voting_supply / 2;
+            let approved: bool = buyout.approvals > half_supply;
+            let rejected: bool = buyout.rejections > half_supply;
+            assert!(approved || rejected || Context::current_epoch() >= buyout.end_epoch, "Buyout still being voted on");
+
+            let buyout: Buyout = self.buyout.take().unwrap();
+
+            //without shares left nobody can receive the proceeds
+            if rejected || supply == Decimal::zero() {
+                //the bid is refunded
+                let refund: Bucket = self.buyout_bid_vault.take_all();
+                self.buyout_claims.insert(buyout.id, Vault::with_bucket(refund));
+                info!("Buyout rejected");
+            } else {
+                //the proceeds per share are fixed with the supply of this buyout
+                let proceeds_per_share: Decimal = self.buyout_bid_vault.amount() / supply;
+                for proceeds in self.nft_shares_proceeds.values_mut() {
+                    *proceeds += proceeds_per_share;
+                }
+                self.buyout_proceeds_vault.put(self.buyout_bid_vault.take_all());
+
+                //shares minted from now on are a new resource without part of these proceeds
+                self.nft_shares_def = Self::create_shares_def(self.nft_admin_badge.resource_address());
+                self.nft_shares_proceeds.insert(self.nft_shares_def.address(), Decimal::zero());
+
+                //the NFT waits for the bidder
+                let nft_bucket: Bucket = self.nft_collection_vault.take_non_fungible(&buyout.key);
+                self.buyout_claims.insert(buyout.id, Vault::with_bucket(nft_bucket));
+                info!("Buyout succeeded");
+            }
+        }
+
+        pub fn claim_buyout(&mut self, bidder_badge:Bucket) -> Bucket {
+
+            assert!(bidder_badge.resource_def() == self.bidder_badge_def, "Not a bidder badge");
+            assert!(bidder_badge.amount() == Decimal::one(), "Expecting one bidder badge");
+            let data: BidderBadge = self.bidder_badge_def.get_non_fungible_data(&bidder_badge.get_non_fungible_key());
+            self.nft_admin_badge.authorize(|auth| {bidder_badge.burn_with_auth(auth)});
+
+            //the NFT when the buyout succeeded, the bid when it was rejected
+            self.buyout_claims.get_mut(&data.buyout).expect("Buyout not settled").take_all()
+        }
+
+        pub fn withdraw_vote(&mut self, vote_receipt:Bucket) -> Bucket {
+
+            assert!(vote_receipt.resource_def() == self.vote_receipt_def, "Not a vote receipt");
+            assert!(vote_receipt.amount() == Decimal::one(), "Expecting one vote receipt");
+            let data: VoteReceipt = self.vote_receipt_def.get_non_fungible_data(&vote_receipt.get_non_fungible_key());
+            assert!(self.buyout.as_ref().map_or(true, |buyout| buyout.id != data.buyout), "Buyout not settled");
+            self.nft_admin_badge.authorize(|auth| {vote_receipt.burn_with_auth(auth)});
+
+            //gives the locked shares back
+            self.buyout_votes_vaults.get_mut(&data.shares_address).unwrap().take(data.shares)
+        }
+
+        pub fn set_buyout_reserve_price(&mut self, buyout_reserve_price:Decimal, admin_badge:BucketRef) {
+
+            assert!(
+                self.buyout_admin_badge == Some(admin_badge.resource_address()) && admin_badge.amount() > Decimal::zero(),
+                "Not the buyout admin badge"
+            );
+            admin_badge.drop();
+            assert!(buyout_reserve_price >= Decimal::zero(), "Reserve price can't be negative");
+
+            self.buyout_reserve_price = buyout_reserve_price;
+        }
+
+        pub fn buyout_status(&self) -> Option<(NonFungibleKey, Decimal, u64, Decimal, Decimal)> {
+
+            //NFT key, bid, end epoch, approving shares and rejecting shares
+            self.buyout.as_ref().map(|buyout| {
+                (buyout.key.clone(), self.buyout_bid_vault.amount(), buyout.end_epoch, buyout.approvals, buyout.rejections)
+            })
+        } 
+
+        //Supply of all the nft shares resources issued
+        fn shares_supply(&self) -> Decimal {
+            self.nft_shares_proceeds.keys().fold(Decimal::zero(), |supply, address| supply + ResourceDef::from(*address).total_supply())
+        }
     }
 }
\ No newline at end of file
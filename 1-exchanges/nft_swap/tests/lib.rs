@@ -1,3 +1,4 @@
+use radix_engine::engine::*;
 use radix_engine::ledger::*;
 use radix_engine::transaction::*;
 use scrypto::prelude::*;
@@ -32,3 +33,362 @@ fn test_hello() {
     println!("{:?}\n", receipt2);
     assert!(receipt2.result.is_ok());
 }
+
+struct Buyouts {
+    component: Address,
+    admin_badge: Address,
+    shares: Address,
+    bidder_badge: Address,
+    vote_receipt: Address,
+    collection: Address,
+}
+
+fn create_buyouts<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    key: EcdsaPublicKey,
+    account: Address,
+) -> Buyouts {
+    let package = executor.publish_package(include_code!("nft_swap")).unwrap();
+    let receipt = executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_function(
+                    package,
+                    "Hello",
+                    "new_with_admin",
+                    vec!["1000".to_string()],
+                    Some(account),
+                )
+                .call_method_with_all_resources(account, "deposit_batch")
+                .build(vec![key])
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(receipt.result.is_ok());
+    Buyouts {
+        component: receipt.component(0).unwrap(),
+        admin_badge: receipt.resource_def(0).unwrap(),
+        shares: receipt.resource_def(2).unwrap(),
+        collection: receipt.resource_def(3).unwrap(),
+        bidder_badge: receipt.resource_def(4).unwrap(),
+        vote_receipt: receipt.resource_def(5).unwrap(),
+    }
+}
+
+//Call a method of the component with resources of the account, return the receipt.
+fn call_receipt<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    component: Address,
+    method: &str,
+    args: Vec<String>,
+    key: EcdsaPublicKey,
+    account: Address,
+) -> Receipt {
+    executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_method(component, method, args, Some(account))
+                .call_method_with_all_resources(account, "deposit_batch")
+                .build(vec![key])
+                .unwrap(),
+        )
+        .unwrap()
+}
+
+//Call a method of the component with resources of the account, return true if the transaction succeed.
+fn call<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    component: Address,
+    method: &str,
+    args: Vec<String>,
+    key: EcdsaPublicKey,
+    account: Address,
+) -> bool {
+    call_receipt(executor, component, method, args, key, account)
+        .result
+        .is_ok()
+}
+
+//Amount of a resource held by the account, read from the vault the account keeps for it.
+fn get_balance<'a, L: SubstateStore>(
+    ledger: &'a L,
+    account: Address,
+    resource: Address,
+) -> Decimal {
+    let component = ledger.get_component(account).unwrap();
+    let state = validate_data(component.state()).unwrap();
+    let vaults = ledger.get_lazy_map(&account, &state.lazy_maps[0]).unwrap();
+    match vaults.map().get(&scrypto_encode(&resource)) {
+        Some(data) => {
+            let vault = validate_data(data).unwrap().vaults;
+            ledger.get_vault(&account, &vault[0]).unwrap().amount()
+        }
+        None => Decimal::zero(),
+    }
+}
+
+#[test]
+fn test_buyout_approved() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, false);
+    let admin_key = executor.new_public_key();
+    let admin = executor.new_account(admin_key);
+    let seller_key = executor.new_public_key();
+    let seller = executor.new_account(seller_key);
+    let bidder_key = executor.new_public_key();
+    let bidder = executor.new_account(bidder_key);
+    let buyouts = create_buyouts(&mut executor, admin_key, admin);
+    let xrd = format!("{}", RADIX_TOKEN);
+
+    //the seller receives 100 shares from the 10% royalty
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "mint_nft_shares",
+        vec![format!("{}", seller), format!("1000,{}", xrd)],
+        seller_key,
+        seller
+    ));
+    let shares = buyouts.shares;
+    assert_eq!(
+        get_balance(executor.ledger(), seller, shares),
+        Decimal::from(100u32)
+    );
+
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "bid_buyout",
+        vec![
+            "00000000000000000000000000000001".to_string(),
+            format!("1500,{}", xrd)
+        ],
+        bidder_key,
+        bidder
+    ));
+    //the bidder can't mint itself a majority of the shares
+    assert!(!call(
+        &mut executor,
+        buyouts.component,
+        "mint_nft_shares",
+        vec![format!("{}", bidder), format!("10000,{}", xrd)],
+        bidder_key,
+        bidder
+    ));
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "vote_buyout",
+        vec![format!("100,{}", shares), "true".to_string()],
+        seller_key,
+        seller
+    ));
+    //nothing to withdraw nor claim while the buyout is voted on
+    assert!(!call(
+        &mut executor,
+        buyouts.component,
+        "withdraw_vote",
+        vec![format!("1,{}", buyouts.vote_receipt)],
+        seller_key,
+        seller
+    ));
+    //shares minted from now on are a new resource
+    let receipt = call_receipt(
+        &mut executor,
+        buyouts.component,
+        "settle_buyout",
+        vec![],
+        admin_key,
+        admin,
+    );
+    assert!(receipt.result.is_ok());
+    let new_shares = receipt.resource_def(0).unwrap();
+
+    //the bidder claims the NFT and the voter its shares
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "claim_buyout",
+        vec![format!("1,{}", buyouts.bidder_badge)],
+        bidder_key,
+        bidder
+    ));
+    assert_eq!(
+        get_balance(executor.ledger(), bidder, buyouts.collection),
+        Decimal::one()
+    );
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "withdraw_vote",
+        vec![format!("1,{}", buyouts.vote_receipt)],
+        seller_key,
+        seller
+    ));
+    assert_eq!(
+        get_balance(executor.ledger(), seller, shares),
+        Decimal::from(100u32)
+    );
+
+    //shares minted after the buyout have no part of the proceeds
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "mint_nft_shares",
+        vec![format!("{}", seller), format!("1000,{}", xrd)],
+        seller_key,
+        seller
+    ));
+    assert_eq!(
+        get_balance(executor.ledger(), seller, new_shares),
+        Decimal::from(100u32)
+    );
+
+    let before = get_balance(executor.ledger(), seller, RADIX_TOKEN);
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "withdraw_xrd",
+        vec![format!("100,{}", new_shares)],
+        seller_key,
+        seller
+    ));
+    let after = get_balance(executor.ledger(), seller, RADIX_TOKEN);
+    assert_eq!(after - before, Decimal::from(100u32));
+
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "withdraw_xrd",
+        vec![format!("100,{}", shares)],
+        seller_key,
+        seller
+    ));
+    assert_eq!(
+        get_balance(executor.ledger(), seller, RADIX_TOKEN) - after,
+        Decimal::from(1600u32)
+    );
+}
+
+#[test]
+fn test_buyout_rejected_and_reserve_price() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut executor = TransactionExecutor::new(&mut ledger, false);
+    let admin_key = executor.new_public_key();
+    let admin = executor.new_account(admin_key);
+    let seller_key = executor.new_public_key();
+    let seller = executor.new_account(seller_key);
+    let bidder_key = executor.new_public_key();
+    let bidder = executor.new_account(bidder_key);
+    let buyouts = create_buyouts(&mut executor, admin_key, admin);
+    let xrd = format!("{}", RADIX_TOKEN);
+
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "mint_nft_shares",
+        vec![format!("{}", seller), format!("1000,{}", xrd)],
+        seller_key,
+        seller
+    ));
+    let shares = buyouts.shares;
+    assert_eq!(
+        get_balance(executor.ledger(), seller, shares),
+        Decimal::from(100u32)
+    );
+
+    //only the admin badge changes the reserve price
+    assert!(!call(
+        &mut executor,
+        buyouts.component,
+        "set_buyout_reserve_price",
+        vec!["2000".to_string(), format!("1,{}", shares)],
+        seller_key,
+        seller
+    ));
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "set_buyout_reserve_price",
+        vec!["2000".to_string(), format!("1,{}", buyouts.admin_badge)],
+        admin_key,
+        admin
+    ));
+    let bid_buyout = |amount: u32| {
+        vec![
+            "00000000000000000000000000000001".to_string(),
+            format!("{},{}", amount, xrd),
+        ]
+    };
+    assert!(!call(
+        &mut executor,
+        buyouts.component,
+        "bid_buyout",
+        bid_buyout(1500),
+        bidder_key,
+        bidder
+    ));
+
+    let before = get_balance(executor.ledger(), bidder, RADIX_TOKEN);
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "bid_buyout",
+        bid_buyout(2000),
+        bidder_key,
+        bidder
+    ));
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "vote_buyout",
+        vec![format!("100,{}", shares), "false".to_string()],
+        seller_key,
+        seller
+    ));
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "settle_buyout",
+        vec![],
+        admin_key,
+        admin
+    ));
+
+    //the bid is refunded and the shares given back
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "claim_buyout",
+        vec![format!("1,{}", buyouts.bidder_badge)],
+        bidder_key,
+        bidder
+    ));
+    assert_eq!(get_balance(executor.ledger(), bidder, RADIX_TOKEN), before);
+    assert_eq!(
+        get_balance(executor.ledger(), bidder, buyouts.collection),
+        Decimal::zero()
+    );
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "withdraw_vote",
+        vec![format!("1,{}", buyouts.vote_receipt)],
+        seller_key,
+        seller
+    ));
+    assert_eq!(
+        get_balance(executor.ledger(), seller, shares),
+        Decimal::from(100u32)
+    );
+
+    //a new buyout can be bid
+    assert!(call(
+        &mut executor,
+        buyouts.component,
+        "bid_buyout",
+        bid_buyout(2000),
+        bidder_key,
+        bidder
+    ));
+}
@@ -0,0 +1,113 @@
+use scrypto::prelude::*;
+
+use crate::interface::{PriceLevel, Side};
+use crate::tickets::OrderTickets;
+
+blueprint! {
+    /// Adapter for the `ChainBook` blueprint of ChainDEX.
+    ///
+    /// The base token is the `a` token of the pair and the quote token the `b` token. The order NFTs are kept by
+    /// the tickets, `withdraw` claims the filled part of each of them.
+    struct ChainBookAdapter {
+        book: Address,
+        base_def: ResourceDef,
+        quote_def: ResourceDef,
+        owner_badge: ResourceDef,
+        tickets: OrderTickets,
+    }
+
+    impl ChainBookAdapter {
+        /// Wrap a book created with `instantiate_chain_book(name, a_token_address, b_token_address)`.
+        /// Return the adapter and its owner badge.
+        pub fn instantiate(book: Address, a_token_address: Address, b_token_address: Address) -> (Component, Bucket) {
+            let owner_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "ChainBook adapter owner badge")
+                .initial_supply_fungible(1);
+
+            let component = Self {
+                book,
+                base_def: ResourceDef::from(a_token_address),
+                quote_def: ResourceDef::from(b_token_address),
+                owner_badge: owner_badge.resource_def(),
+                tickets: OrderTickets::new("ChainBook adapter"),
+            }
+            .instantiate();
+
+            (component, owner_badge)
+        }
+
+        pub fn base_token(&self) -> Address {
+            self.base_def.address()
+        }
+
+        pub fn quote_token(&self) -> Address {
+            self.quote_def.address()
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_limit(&mut self, side: Side, price: Decimal, tokens: Bucket) -> (Bucket, Vec<Bucket>) {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            let (filled, order): (Bucket, Bucket) =
+                Component::from(self.book).call("create_order", vec![scrypto_encode(&tokens), scrypto_encode(&price)]);
+
+            // the order is an empty bucket when filled in full
+            let ticket = if order.is_empty() {
+                self.tickets.none()
+            } else {
+                self.tickets.wrap(side, price, order)
+            };
+            (ticket, vec![filled])
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_market(&mut self, side: Side, tokens: Bucket) -> Vec<Bucket> {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            let (filled, remaining): (Bucket, Bucket) =
+                Component::from(self.book).call("market_order", vec![scrypto_encode(&tokens)]);
+            vec![filled, remaining]
+        }
+
+        #[auth(owner_badge)]
+        pub fn cancel(&mut self, ticket: Bucket) -> Vec<Bucket> {
+            let (_, order) = self.tickets.redeem(ticket);
+            let (remaining, filled): (Bucket, Bucket) =
+                Component::from(self.book).call("cancel_order", vec![scrypto_encode(&order)]);
+            vec![filled, remaining]
+        }
+
+        #[auth(owner_badge)]
+        pub fn withdraw(&mut self) -> Vec<Bucket> {
+            let book = Component::from(self.book);
+            let mut buckets: Vec<Bucket> = Vec::new();
+            for key in self.tickets.open_orders() {
+                let order = self.tickets.take_order(&key);
+                // the order comes back empty when it is completed
+                let (tokens, order): (Bucket, Bucket) = book.call("claim_tokens", vec![scrypto_encode(&order)]);
+                self.tickets.put_order(&key, order);
+                buckets.push(tokens);
+            }
+            buckets
+        }
+
+        pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            Component::from(self.book).call("get_best_bid_ask", vec![])
+        }
+
+        pub fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+            let (bids, asks): (Vec<(Decimal, Decimal, u32)>, Vec<(Decimal, Decimal, u32)>) =
+                Component::from(self.book).call("get_depth", vec![scrypto_encode(&levels)]);
+            (
+                bids.into_iter().map(PriceLevel::from).collect(),
+                asks.into_iter().map(PriceLevel::from).collect(),
+            )
+        }
+    }
+}
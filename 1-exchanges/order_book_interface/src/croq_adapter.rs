@@ -0,0 +1,123 @@
+use scrypto::prelude::*;
+
+use crate::interface::{PriceLevel, Side};
+use crate::tickets::OrderTickets;
+
+blueprint! {
+    /// Adapter for croq_order_book.
+    ///
+    /// All the offers are made with the user badge of the adapter, the book keeps the proceeds of the offers
+    /// resting in it in the user vaults so `cancel` and `withdraw` empty them.
+    struct CroqAdapter {
+        book: Address,
+        base_def: ResourceDef,
+        quote_def: ResourceDef,
+        user_badge: Vault,
+        owner_badge: ResourceDef,
+        tickets: OrderTickets,
+    }
+
+    impl CroqAdapter {
+        /// Wrap a book created with `instantiate(token, cash)`.
+        /// Return the adapter and its owner badge.
+        pub fn instantiate(book: Address, token: Address, cash: Address) -> (Component, Bucket) {
+            let user_badge: Bucket = Component::from(book).call("register", vec![]);
+            let owner_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "Croq adapter owner badge")
+                .initial_supply_fungible(1);
+
+            let component = Self {
+                book,
+                base_def: ResourceDef::from(token),
+                quote_def: ResourceDef::from(cash),
+                user_badge: Vault::with_bucket(user_badge),
+                owner_badge: owner_badge.resource_def(),
+                tickets: OrderTickets::new("Croq adapter"),
+            }
+            .instantiate();
+
+            (component, owner_badge)
+        }
+
+        pub fn base_token(&self) -> Address {
+            self.base_def.address()
+        }
+
+        pub fn quote_token(&self) -> Address {
+            self.quote_def.address()
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_limit(&mut self, side: Side, price: Decimal, tokens: Bucket) -> (Bucket, Vec<Bucket>) {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            let method = match side {
+                Side::Bid => "push_bid",
+                Side::Ask => "push_ask",
+            };
+            let book = Component::from(self.book);
+            let mut buckets: Vec<Bucket> = self.user_badge.authorize(|user| {
+                book.call(method, vec![scrypto_encode(&user), scrypto_encode(&price), scrypto_encode(&tokens)])
+            });
+
+            // an offer badge comes with the tokens when the offer stays in the book
+            let ticket = match buckets.iter().position(|bucket| bucket.resource_def() != self.base_def && bucket.resource_def() != self.quote_def) {
+                Some(i) => {
+                    let offer_badge = buckets.remove(i);
+                    self.tickets.wrap(side, price, offer_badge)
+                }
+                None => self.tickets.none(),
+            };
+            (ticket, buckets)
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_market(&mut self, side: Side, tokens: Bucket) -> Vec<Bucket> {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            Component::from(self.book).call("push_market", vec![scrypto_encode(&tokens)])
+        }
+
+        #[auth(owner_badge)]
+        pub fn cancel(&mut self, ticket: Bucket) -> Vec<Bucket> {
+            let (_, offer_badge) = self.tickets.redeem(ticket);
+            let (cash, token): (Bucket, Bucket) =
+                Component::from(self.book).call("cancel", vec![scrypto_encode(&offer_badge)]);
+
+            let mut buckets = vec![token, cash];
+            buckets.extend(self.withdraw_user());
+            buckets
+        }
+
+        #[auth(owner_badge)]
+        pub fn withdraw(&mut self) -> Vec<Bucket> {
+            self.withdraw_user()
+        }
+
+        pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            Component::from(self.book).call("best_bid_ask", vec![])
+        }
+
+        pub fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+            let (bids, asks): (Vec<(Decimal, Decimal, u32)>, Vec<(Decimal, Decimal, u32)>) =
+                Component::from(self.book).call("depth", vec![scrypto_encode(&levels)]);
+            (
+                bids.into_iter().map(PriceLevel::from).collect(),
+                asks.into_iter().map(PriceLevel::from).collect(),
+            )
+        }
+
+        // empty the user vaults of the adapter
+        fn withdraw_user(&mut self) -> Vec<Bucket> {
+            let book = Component::from(self.book);
+            self.user_badge
+                .authorize(|user| book.call("withdraw", vec![scrypto_encode(&user)]))
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! The [OrderBook] interface and the [OrderBookClient] calling an adapter component through it.
+//!
+//! Prices are always in quote tokens per base token and amounts in base tokens.
+//! An order of the [Side::Bid] side pays with quote tokens, an order of the [Side::Ask] side with base tokens.
+use sbor::*;
+use scrypto::prelude::*;
+
+/// Side of an order: a bid buys base tokens with quote tokens, an ask sells base tokens for quote tokens.
+#[derive(Debug, Clone, Copy, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+impl Side {
+    /// The resource an order of this side pays with.
+    pub fn paid_with(&self, base_def: &ResourceDef, quote_def: &ResourceDef) -> ResourceDef {
+        match self {
+            Side::Bid => quote_def.clone(),
+            Side::Ask => base_def.clone(),
+        }
+    }
+}
+
+/// The orders at one price of the book.
+///
+/// Same layout as the `DepthLevel` of orderbook so its depth decodes directly into it.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
+pub struct PriceLevel {
+    pub price: Decimal,
+    /// Amount of base tokens at this price
+    pub amount: Decimal,
+    pub order_count: u32,
+}
+
+impl PriceLevel {
+    /// Aggregate `(price, amount)` orders, sorted from the best price, into at most `levels` price levels.
+    pub fn aggregate(orders: Vec<(Decimal, Decimal)>, levels: u32) -> Vec<PriceLevel> {
+        let mut depth: Vec<PriceLevel> = Vec::new();
+        for (price, amount) in orders {
+            match depth.last_mut() {
+                Some(level) if level.price == price => {
+                    level.amount += amount;
+                    level.order_count += 1;
+                }
+                _ => {
+                    if depth.len() as u32 == levels {
+                        break;
+                    }
+                    depth.push(PriceLevel {
+                        price,
+                        amount,
+                        order_count: 1,
+                    });
+                }
+            }
+        }
+        depth
+    }
+}
+
+impl From<(Decimal, Decimal, u32)> for PriceLevel {
+    fn from((price, amount, order_count): (Decimal, Decimal, u32)) -> Self {
+        PriceLevel {
+            price,
+            amount,
+            order_count,
+        }
+    }
+}
+
+/// The methods every adapter exposes.
+///
+/// The methods taking an `auth` need the owner badge returned when the adapter is instantiated.
+pub trait OrderBook {
+    fn base_token(&self) -> Address;
+
+    fn quote_token(&self) -> Address;
+
+    /// Match the order against the book up to `price` and leave the rest in the book.
+    ///
+    /// Return the order ticket, empty if nothing stays in the book, and the tokens bought with the change.
+    fn place_limit(
+        &mut self,
+        side: Side,
+        price: Decimal,
+        tokens: Bucket,
+        auth: BucketRef,
+    ) -> (Bucket, Vec<Bucket>);
+
+    /// Match the order against the book whatever the price, nothing stays in the book.
+    ///
+    /// Return the tokens bought and the ones that could not be matched.
+    fn place_market(&mut self, side: Side, tokens: Bucket, auth: BucketRef) -> Vec<Bucket>;
+
+    /// Cancel the order of the ticket and return the tokens left in it.
+    ///
+    /// Books keeping the proceeds of all the orders in one account return them as well.
+    fn cancel(&mut self, ticket: Bucket, auth: BucketRef) -> Vec<Bucket>;
+
+    /// Collect the tokens bought by the orders resting in the book.
+    fn withdraw(&mut self, auth: BucketRef) -> Vec<Bucket>;
+
+    /// The highest bid price and the lowest ask price, `None` if there is no order on the side.
+    fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>);
+
+    /// The `levels` best price levels of each side, bids from the highest price and asks from the lowest price.
+    fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>);
+}
+
+/// Calls an adapter component through the [OrderBook] interface.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct OrderBookClient {
+    adapter: Address,
+}
+
+impl OrderBookClient {
+    pub fn new(adapter: Address) -> Self {
+        OrderBookClient { adapter }
+    }
+
+    pub fn address(&self) -> Address {
+        self.adapter
+    }
+
+    fn call<T: Decode>(&self, method: &str, args: Vec<Vec<u8>>) -> T {
+        Component::from(self.adapter).call::<T>(method, args)
+    }
+}
+
+impl OrderBook for OrderBookClient {
+    fn base_token(&self) -> Address {
+        self.call("base_token", vec![])
+    }
+
+    fn quote_token(&self) -> Address {
+        self.call("quote_token", vec![])
+    }
+
+    fn place_limit(
+        &mut self,
+        side: Side,
+        price: Decimal,
+        tokens: Bucket,
+        auth: BucketRef,
+    ) -> (Bucket, Vec<Bucket>) {
+        self.call(
+            "place_limit",
+            vec![
+                scrypto_encode(&side),
+                scrypto_encode(&price),
+                scrypto_encode(&tokens),
+                scrypto_encode(&auth),
+            ],
+        )
+    }
+
+    fn place_market(&mut self, side: Side, tokens: Bucket, auth: BucketRef) -> Vec<Bucket> {
+        self.call(
+            "place_market",
+            vec![
+                scrypto_encode(&side),
+                scrypto_encode(&tokens),
+                scrypto_encode(&auth),
+            ],
+        )
+    }
+
+    fn cancel(&mut self, ticket: Bucket, auth: BucketRef) -> Vec<Bucket> {
+        self.call(
+            "cancel",
+            vec![scrypto_encode(&ticket), scrypto_encode(&auth)],
+        )
+    }
+
+    fn withdraw(&mut self, auth: BucketRef) -> Vec<Bucket> {
+        self.call("withdraw", vec![scrypto_encode(&auth)])
+    }
+
+    fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+        self.call("best_bid_ask", vec![])
+    }
+
+    fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        self.call("depth", vec![scrypto_encode(&levels)])
+    }
+}
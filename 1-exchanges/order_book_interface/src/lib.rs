@@ -0,0 +1,18 @@
+//! A common interface over the order books of this directory.
+//!
+//! [OrderBook] is the interface, each adapter blueprint wraps one order book and exposes its methods:
+//! * `OrderbookAdapter` for the `Market` blueprint of orderbook
+//! * `CroqAdapter` for croq_order_book
+//! * `ChainBookAdapter` for the `ChainBook` blueprint of ChainDEX
+//! * `PetitcrocoAdapter` for the `OrderBook` blueprint of petitcroco-OrderBook
+//!
+//! An aggregator calls any adapter the same way with [OrderBookClient].
+mod chain_book_adapter;
+mod croq_adapter;
+mod interface;
+mod orderbook_adapter;
+mod petitcroco_adapter;
+mod tickets;
+
+pub use interface::{OrderBook, OrderBookClient, PriceLevel, Side};
+pub use tickets::{OrderTicket, OrderTickets};
@@ -0,0 +1,175 @@
+use sbor::*;
+use scrypto::prelude::*;
+
+use crate::interface::{PriceLevel, Side};
+use crate::tickets::OrderTickets;
+
+// order types of the Market blueprint
+const LIMIT: u8 = 0;
+const MARKET: u8 = 4;
+
+// same layout as the `OpenOrder` of orderbook
+#[derive(TypeId, Encode, Decode, Describe)]
+struct OpenOrder {
+    id: u32,
+    side: Side,
+    price: Decimal,
+    remaining_amount: Decimal,
+    locked_amount: Decimal,
+}
+
+// same layout as the `UserOrdersView` of orderbook
+#[derive(TypeId, Encode, Decode, Describe)]
+struct UserOrdersView {
+    quote_amount: Decimal,
+    locked_quote_amount: Decimal,
+    base_amount: Decimal,
+    locked_base_amount: Decimal,
+    orders: Vec<OpenOrder>,
+}
+
+blueprint! {
+    /// Adapter for the `Market` blueprint of orderbook.
+    ///
+    /// All the orders are placed with the open orders badge of the adapter, the market keeps their proceeds in a
+    /// single account so `place_limit`, `cancel` and `withdraw` empty it.
+    struct OrderbookAdapter {
+        market: Address,
+        base_def: ResourceDef,
+        quote_def: ResourceDef,
+        openorders_badge: Vault,
+        owner_badge: ResourceDef,
+        tickets: OrderTickets,
+    }
+
+    impl OrderbookAdapter {
+        /// Wrap a market created with `instantiate_market(quote_token, base_token, name)`.
+        /// Return the adapter and its owner badge.
+        pub fn instantiate(market: Address, quote_token: Address, base_token: Address) -> (Component, Bucket) {
+            let openorders_badge: Bucket = Component::from(market).call("create_openorders", vec![]);
+            let owner_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "Orderbook adapter owner badge")
+                .initial_supply_fungible(1);
+
+            let component = Self {
+                market,
+                base_def: ResourceDef::from(base_token),
+                quote_def: ResourceDef::from(quote_token),
+                openorders_badge: Vault::with_bucket(openorders_badge),
+                owner_badge: owner_badge.resource_def(),
+                tickets: OrderTickets::new("Orderbook adapter"),
+            }
+            .instantiate();
+
+            (component, owner_badge)
+        }
+
+        pub fn base_token(&self) -> Address {
+            self.base_def.address()
+        }
+
+        pub fn quote_token(&self) -> Address {
+            self.quote_def.address()
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_limit(&mut self, side: Side, price: Decimal, tokens: Bucket) -> (Bucket, Vec<Bucket>) {
+            assert!(price > Decimal::zero(), "Price must be positive");
+
+            let (order_id, change) = self.push(side, price, tokens, LIMIT);
+            let ticket = if self.rests_in_book(&order_id) {
+                self.tickets.wrap(side, price, order_id)
+            } else {
+                order_id.burn();
+                self.tickets.none()
+            };
+            let mut buckets = vec![change];
+            buckets.extend(self.withdraw_account());
+            (ticket, buckets)
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_market(&mut self, side: Side, tokens: Bucket) -> Vec<Bucket> {
+            // the market caps a bid by an amount of base, the best ask tells how much the quote can buy at most.
+            // Sweeping higher asks, the quote runs out first and the market unlocks the rest of the bid.
+            let price = match side {
+                Side::Bid => self.best_bid_ask().1.expect("No ask in the book"),
+                Side::Ask => Decimal::one(),
+            };
+
+            let (order_id, change) = self.push(side, price, tokens, MARKET);
+            order_id.burn();
+            let mut buckets = vec![change];
+            buckets.extend(self.withdraw_account());
+            buckets
+        }
+
+        #[auth(owner_badge)]
+        pub fn cancel(&mut self, ticket: Bucket) -> Vec<Bucket> {
+            let (_, order_id) = self.tickets.redeem(ticket);
+            let market = Component::from(self.market);
+            let order_id: Bucket = self.openorders_badge.authorize(|badge| {
+                market.call("cancel_order", vec![scrypto_encode(&order_id), scrypto_encode(&badge)])
+            });
+            order_id.burn();
+            self.withdraw_account()
+        }
+
+        #[auth(owner_badge)]
+        pub fn withdraw(&mut self) -> Vec<Bucket> {
+            self.withdraw_account()
+        }
+
+        pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            Component::from(self.market).call("get_best_bid_ask", vec![])
+        }
+
+        pub fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+            Component::from(self.market).call("get_depth", vec![scrypto_encode(&levels)])
+        }
+
+        // push an order to the market, return (order id badge, change)
+        fn push(&mut self, side: Side, price: Decimal, tokens: Bucket, order_type: u8) -> (Bucket, Bucket) {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            let (method, amount) = match side {
+                Side::Bid => ("bid_order", tokens.amount() / price),
+                Side::Ask => ("ask_order", tokens.amount()),
+            };
+            let market = Component::from(self.market);
+            self.openorders_badge.authorize(|badge| {
+                market.call(
+                    method,
+                    vec![
+                        scrypto_encode(&price),
+                        scrypto_encode(&amount),
+                        scrypto_encode(&order_type),
+                        scrypto_encode(&tokens),
+                        scrypto_encode(&badge),
+                    ],
+                )
+            })
+        }
+
+        // whether the order of the order id badge is still pending in the market
+        fn rests_in_book(&self, order_id: &Bucket) -> bool {
+            let id: u32 = order_id.resource_def().metadata()["id"].parse().unwrap();
+            let owner = self.openorders_badge.get_non_fungible_keys().remove(0);
+            let orders: UserOrdersView =
+                Component::from(self.market).call("get_user_orders", vec![scrypto_encode(&owner)]);
+            orders.orders.iter().any(|order| order.id == id)
+        }
+
+        // empty the open orders account, return (base, quote)
+        fn withdraw_account(&mut self) -> Vec<Bucket> {
+            let market = Component::from(self.market);
+            let (quote, base): (Bucket, Bucket) = self
+                .openorders_badge
+                .authorize(|badge| market.call("withdraw", vec![scrypto_encode(&badge)]));
+            vec![base, quote]
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use scrypto::prelude::*;
+
+use crate::interface::{PriceLevel, Side};
+use crate::tickets::OrderTickets;
+
+blueprint! {
+    /// Adapter for the `OrderBook` blueprint of petitcroco-OrderBook.
+    ///
+    /// Asks rest on the (base, quote) pair of the book at a ratio equal to the price, bids on the (quote, base)
+    /// pair at a ratio of one over the price, both pairs must be initialized by the admin of the book.
+    /// The book pays filled orders and refunds cancelled ones to the payout account directly, so `cancel` and
+    /// `withdraw` return nothing. Orders of the book can only be accepted in full, the ones the tokens can't pay
+    /// for are skipped.
+    struct PetitcrocoAdapter {
+        book: Address,
+        base_def: ResourceDef,
+        quote_def: ResourceDef,
+        member_badge: Vault,
+        payout_account: Address,
+        owner_badge: ResourceDef,
+        tickets: OrderTickets,
+        // (input, output, amount, ratio) of the order of each ticket, as stored in the book
+        orders: HashMap<NonFungibleKey, (Address, Address, Decimal, Decimal)>,
+    }
+
+    impl PetitcrocoAdapter {
+        /// Wrap a book created with `init()`, `payout_account` receives the tokens of the orders resting in it.
+        /// Return the adapter and its owner badge.
+        pub fn instantiate(book: Address, base_token: Address, quote_token: Address, payout_account: Address) -> (Component, Bucket) {
+            let member_badge: Bucket = Component::from(book).call(
+                "become_member",
+                vec![scrypto_encode(&"Order book adapter".to_string())],
+            );
+            let owner_badge = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+                .metadata("name", "Petitcroco adapter owner badge")
+                .initial_supply_fungible(1);
+
+            let component = Self {
+                book,
+                base_def: ResourceDef::from(base_token),
+                quote_def: ResourceDef::from(quote_token),
+                member_badge: Vault::with_bucket(member_badge),
+                payout_account,
+                owner_badge: owner_badge.resource_def(),
+                tickets: OrderTickets::new("Petitcroco adapter"),
+                orders: HashMap::new(),
+            }
+            .instantiate();
+
+            (component, owner_badge)
+        }
+
+        pub fn base_token(&self) -> Address {
+            self.base_def.address()
+        }
+
+        pub fn quote_token(&self) -> Address {
+            self.quote_def.address()
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_limit(&mut self, side: Side, price: Decimal, tokens: Bucket) -> (Bucket, Vec<Bucket>) {
+            assert!(price > Decimal::zero(), "Price must be positive");
+
+            let (bought, rest) = self.take_orders(side, Some(price), tokens);
+            if rest.is_empty() {
+                return (self.tickets.none(), vec![bought]);
+            }
+
+            let (input, output, ratio) = match side {
+                Side::Bid => (self.quote_def.address(), self.base_def.address(), Decimal::one() / price),
+                Side::Ask => (self.base_def.address(), self.quote_def.address(), price),
+            };
+            let amount = rest.amount();
+            let book = Component::from(self.book);
+            let payout_account = self.payout_account;
+            let change: Bucket = self.member_badge.authorize(|member| {
+                book.call(
+                    "add_order_orderbook",
+                    vec![
+                        scrypto_encode(&input),
+                        scrypto_encode(&output),
+                        scrypto_encode(&amount),
+                        scrypto_encode(&ratio),
+                        scrypto_encode(&rest),
+                        scrypto_encode(&payout_account),
+                        scrypto_encode(&member),
+                    ],
+                )
+            });
+
+            let (key, ticket) = self.tickets.mint(side, price);
+            self.orders.insert(key, (input, output, amount, ratio));
+            (ticket, vec![bought, change])
+        }
+
+        #[auth(owner_badge)]
+        pub fn place_market(&mut self, side: Side, tokens: Bucket) -> Vec<Bucket> {
+            let (bought, rest) = self.take_orders(side, None, tokens);
+            vec![bought, rest]
+        }
+
+        /// The tokens left in the order are sent back to the payout account.
+        #[auth(owner_badge)]
+        pub fn cancel(&mut self, ticket: Bucket) -> Vec<Bucket> {
+            let (key, _) = self.tickets.burn(ticket);
+            let (input, output, amount, ratio) = self.orders.remove(&key).unwrap();
+
+            // the book only logs when the order was accepted already
+            let book = Component::from(self.book);
+            let payout_account = self.payout_account;
+            self.member_badge.authorize(|member| {
+                book.call::<()>(
+                    "cancel_order",
+                    vec![
+                        scrypto_encode(&input),
+                        scrypto_encode(&output),
+                        scrypto_encode(&amount),
+                        scrypto_encode(&ratio),
+                        scrypto_encode(&payout_account),
+                        scrypto_encode(&member),
+                    ],
+                )
+            });
+            vec![]
+        }
+
+        /// Filled orders are paid to the payout account, there is nothing to withdraw.
+        #[auth(owner_badge)]
+        pub fn withdraw(&mut self) -> Vec<Bucket> {
+            vec![]
+        }
+
+        pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            let (bids, asks) = self.sorted_orders();
+            (
+                bids.first().map(|(price, _)| *price),
+                asks.first().map(|(price, _)| *price),
+            )
+        }
+
+        pub fn depth(&self, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+            let (bids, asks) = self.sorted_orders();
+            (PriceLevel::aggregate(bids, levels), PriceLevel::aggregate(asks, levels))
+        }
+
+        // (price, amount of base) of the orders of each side, from the best price
+        fn sorted_orders(&self) -> (Vec<(Decimal, Decimal)>, Vec<(Decimal, Decimal)>) {
+            // the lowest ratio is the best order on both pairs
+            let mut bids = self.book_orders(Side::Bid);
+            bids.sort_by(|a, b| a.1.cmp(&b.1));
+            let mut asks = self.book_orders(Side::Ask);
+            asks.sort_by(|a, b| a.1.cmp(&b.1));
+
+            (
+                bids.into_iter().map(|(amount, ratio)| (Decimal::one() / ratio, amount * ratio)).collect(),
+                asks.into_iter().map(|(amount, ratio)| (ratio, amount)).collect(),
+            )
+        }
+
+        // (amount, ratio) of the orders of a side, without the empty order put by `reset_pair_orderbook`
+        fn book_orders(&self, side: Side) -> Vec<(Decimal, Decimal)> {
+            let (input, output) = match side {
+                Side::Bid => (self.quote_def.address(), self.base_def.address()),
+                Side::Ask => (self.base_def.address(), self.quote_def.address()),
+            };
+            let orders: Vec<([Decimal; 2], Address)> = Component::from(self.book)
+                .call("get_orders", vec![scrypto_encode(&input), scrypto_encode(&output)]);
+            orders
+                .into_iter()
+                .map(|(order, _)| (order[0], order[1]))
+                .filter(|(amount, ratio)| *amount > Decimal::zero() && *ratio > Decimal::zero())
+                .collect()
+        }
+
+        // accept the orders of the opposite side from the best price while it doesn't pass `limit`
+        // return (bought, rest)
+        fn take_orders(&mut self, side: Side, limit: Option<Decimal>, mut tokens: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                tokens.resource_def() == side.paid_with(&self.base_def, &self.quote_def),
+                "Wrong tokens for this side"
+            );
+
+            let opposite = match side {
+                Side::Bid => Side::Ask,
+                Side::Ask => Side::Bid,
+            };
+            let (input, output) = match opposite {
+                Side::Bid => (self.quote_def.clone(), self.base_def.clone()),
+                Side::Ask => (self.base_def.clone(), self.quote_def.clone()),
+            };
+            let mut orders = self.book_orders(opposite);
+            orders.sort_by(|a, b| a.1.cmp(&b.1));
+
+            let book = Component::from(self.book);
+            let mut bought = Bucket::new(input.clone());
+            for (amount, ratio) in orders {
+                let price = match side {
+                    Side::Bid => ratio,
+                    Side::Ask => Decimal::one() / ratio,
+                };
+                let passed = match (side, limit) {
+                    (Side::Bid, Some(limit)) => price > limit,
+                    (Side::Ask, Some(limit)) => price < limit,
+                    (_, None) => false,
+                };
+                if passed {
+                    break;
+                }
+                if amount * ratio > tokens.amount() {
+                    continue;
+                }
+
+                let (change, purchased): (Bucket, Bucket) = self.member_badge.authorize(|member| {
+                    book.call(
+                        "accept_an_order",
+                        vec![
+                            scrypto_encode(&input.address()),
+                            scrypto_encode(&output.address()),
+                            scrypto_encode(&amount),
+                            scrypto_encode(&ratio),
+                            scrypto_encode(&tokens),
+                            scrypto_encode(&member),
+                        ],
+                    )
+                });
+                tokens = change;
+                bought.put(purchased);
+            }
+            (bought, tokens)
+        }
+    }
+}
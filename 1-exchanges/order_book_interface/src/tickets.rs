@@ -0,0 +1,118 @@
+//! The order tickets returned by every adapter for the orders resting in the book.
+use sbor::*;
+use scrypto::prelude::*;
+
+use crate::interface::Side;
+
+/// Data of an order ticket.
+#[derive(NonFungibleData)]
+pub struct OrderTicket {
+    pub side: Side,
+    pub price: Decimal,
+}
+
+/// Mints and burns the order tickets of an adapter.
+///
+/// When the wrapped order book gives a token for an order (order id badge, offer badge or order NFT), the ticket
+/// holds it while the order rests in the book.
+#[derive(Debug, TypeId, Encode, Decode, Describe)]
+pub struct OrderTickets {
+    minter: Vault,
+    ticket_def: ResourceDef,
+    count: u128,
+    // order tokens by ticket key, emptied vaults are kept since a vault can't be dropped
+    orders: HashMap<NonFungibleKey, Vault>,
+}
+
+impl OrderTickets {
+    pub fn new(name: &str) -> Self {
+        let minter = ResourceBuilder::new_fungible(DIVISIBILITY_NONE)
+            .metadata("name", format!("{} ticket minter", name))
+            .initial_supply_fungible(1);
+        let ticket_def = ResourceBuilder::new_non_fungible()
+            .metadata("name", format!("{} order ticket", name))
+            .flags(MINTABLE | BURNABLE)
+            .badge(minter.resource_def(), MAY_MINT | MAY_BURN)
+            .no_initial_supply();
+
+        OrderTickets {
+            minter: Vault::with_bucket(minter),
+            ticket_def,
+            count: 0,
+            orders: HashMap::new(),
+        }
+    }
+
+    pub fn ticket_def(&self) -> ResourceDef {
+        self.ticket_def.clone()
+    }
+
+    /// An empty bucket of tickets, returned when nothing stays in the book.
+    pub fn none(&self) -> Bucket {
+        Bucket::new(self.ticket_def.clone())
+    }
+
+    /// Mint the ticket of an order, return its key and the ticket.
+    pub fn mint(&mut self, side: Side, price: Decimal) -> (NonFungibleKey, Bucket) {
+        self.count += 1;
+        let key = NonFungibleKey::from(self.count);
+        let ticket_def = self.ticket_def.clone();
+        let ticket = self.minter.authorize(|auth| {
+            ticket_def.mint_non_fungible(&key, OrderTicket { side, price }, auth)
+        });
+        (key, ticket)
+    }
+
+    /// Burn a ticket, return its key and data.
+    pub fn burn(&mut self, ticket: Bucket) -> (NonFungibleKey, OrderTicket) {
+        assert!(
+            ticket.resource_def() == self.ticket_def,
+            "Not an order ticket of this adapter"
+        );
+        assert!(
+            ticket.amount() == Decimal::one(),
+            "Expecting one order ticket"
+        );
+
+        let key = ticket.get_non_fungible_key();
+        let data: OrderTicket = self.ticket_def.get_non_fungible_data(&key);
+        self.minter.authorize(|auth| ticket.burn_with_auth(auth));
+        (key, data)
+    }
+
+    /// Mint the ticket of an order and keep the order token of the wrapped order book.
+    pub fn wrap(&mut self, side: Side, price: Decimal, order: Bucket) -> Bucket {
+        let (key, ticket) = self.mint(side, price);
+        self.orders.insert(key, Vault::with_bucket(order));
+        ticket
+    }
+
+    /// Burn a ticket and return the order token it holds.
+    pub fn redeem(&mut self, ticket: Bucket) -> (OrderTicket, Bucket) {
+        let (key, data) = self.burn(ticket);
+        let order = self
+            .orders
+            .get_mut(&key)
+            .expect("No order token for this ticket")
+            .take_all();
+        (data, order)
+    }
+
+    /// Keys of the tickets still holding an order token.
+    pub fn open_orders(&self) -> Vec<NonFungibleKey> {
+        self.orders
+            .iter()
+            .filter(|(_, order)| !order.is_empty())
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Take the order token of a ticket, to be given back with `put_order`.
+    pub fn take_order(&mut self, key: &NonFungibleKey) -> Bucket {
+        self.orders.get_mut(key).unwrap().take_all()
+    }
+
+    pub fn put_order(&mut self, key: &NonFungibleKey, order: Bucket) {
+        self.orders.get_mut(key).unwrap().put(order);
+    }
+}
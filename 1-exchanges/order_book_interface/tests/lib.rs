@@ -0,0 +1,454 @@
+use order_book_interface::{PriceLevel, Side};
+use radix_engine::engine::*;
+use radix_engine::ledger::*;
+use radix_engine::model::{Instruction, Receipt};
+use radix_engine::transaction::*;
+use scrypto::prelude::*;
+
+#[test]
+fn test_aggregate_price_levels() {
+    let orders = vec![
+        (dec!("5"), dec!("10")),
+        (dec!("5"), dec!("2")),
+        (dec!("6"), dec!("20")),
+        (dec!("7"), dec!("1")),
+    ];
+
+    let depth = PriceLevel::aggregate(orders, 2);
+
+    assert_eq!(
+        depth,
+        vec![
+            PriceLevel {
+                price: dec!("5"),
+                amount: dec!("12"),
+                order_count: 2
+            },
+            PriceLevel {
+                price: dec!("6"),
+                amount: dec!("20"),
+                order_count: 1
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_aggregate_empty_book() {
+    assert!(PriceLevel::aggregate(vec![], 5).is_empty());
+}
+
+/// compiles the package at path, relative to this crate, the same way `scrypto build` does
+fn compile(path: &str, name: &str) -> Vec<u8> {
+    let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path);
+    std::process::Command::new("cargo")
+        .current_dir(&path)
+        .args(["build", "--target", "wasm32-unknown-unknown", "--release"])
+        .status()
+        .unwrap();
+    std::fs::read(format!(
+        "{}/target/wasm32-unknown-unknown/release/{}.wasm",
+        path,
+        name.replace("-", "_")
+    ))
+    .unwrap()
+}
+
+struct Adapter {
+    component: Address,
+    owner_badge: Address,
+}
+
+struct TestEnv<'a, L: SubstateStore> {
+    executor: TransactionExecutor<'a, L>,
+    key: EcdsaPublicKey,
+    account: Address,
+    package: Address,
+    base: Address,
+    quote: Address,
+}
+
+impl<'a, L: SubstateStore> TestEnv<'a, L> {
+    fn new(ledger: &'a mut L) -> Self {
+        let mut executor = TransactionExecutor::new(ledger, false);
+        let key = executor.new_public_key();
+        let account = executor.new_account(key);
+        let package = executor
+            .publish_package(include_code!("order_book_interface"))
+            .unwrap();
+
+        let mut create_token = || {
+            let transaction = TransactionBuilder::new(&executor)
+                .new_token_fixed(HashMap::new(), dec!("1000000"))
+                .call_method_with_all_resources(account, "deposit_batch")
+                .build(vec![key])
+                .unwrap();
+            let receipt = executor.run(transaction).unwrap();
+            assert!(receipt.result.is_ok());
+            receipt.resource_def(0).unwrap()
+        };
+        let base = create_token();
+        let quote = create_token();
+
+        TestEnv {
+            executor,
+            key,
+            account,
+            package,
+            base,
+            quote,
+        }
+    }
+
+    fn publish(&mut self, path: &str, name: &str) -> Address {
+        self.executor.publish_package(&compile(path, name)).unwrap()
+    }
+
+    fn call_function(
+        &mut self,
+        package: Address,
+        blueprint: &str,
+        function: &str,
+        args: Vec<String>,
+    ) -> Receipt {
+        let transaction = TransactionBuilder::new(&self.executor)
+            .call_function(package, blueprint, function, args, Some(self.account))
+            .call_method_with_all_resources(self.account, "deposit_batch")
+            .build(vec![self.key])
+            .unwrap();
+        let receipt = self.executor.run(transaction).unwrap();
+        assert!(receipt.result.is_ok());
+        receipt
+    }
+
+    fn call_method(&mut self, component: Address, method: &str, args: Vec<String>) -> Receipt {
+        let transaction = TransactionBuilder::new(&self.executor)
+            .call_method(component, method, args, Some(self.account))
+            .call_method_with_all_resources(self.account, "deposit_batch")
+            .build(vec![self.key])
+            .unwrap();
+        self.executor.run(transaction).unwrap()
+    }
+
+    /// call a method of the adapter with the tokens, if any, and the owner badge as the last arguments
+    fn call_adapter(
+        &mut self,
+        adapter: &Adapter,
+        method: &str,
+        mut args: Vec<Vec<u8>>,
+        tokens: Option<(Decimal, Address)>,
+    ) -> Receipt {
+        let mut builder = TransactionBuilder::new(&self.executor);
+        if let Some((amount, resource_address)) = tokens {
+            builder
+                .withdraw_from_account(
+                    &Resource::Fungible {
+                        amount,
+                        resource_address,
+                    },
+                    self.account,
+                )
+                .take_all_from_worktop(resource_address, |builder, bucket| {
+                    args.push(scrypto_encode(&bucket));
+                    builder
+                });
+        }
+        let transaction = builder
+            .withdraw_from_account(
+                &Resource::Fungible {
+                    amount: Decimal::one(),
+                    resource_address: adapter.owner_badge,
+                },
+                self.account,
+            )
+            .take_all_from_worktop(adapter.owner_badge, |builder, badge| {
+                builder.create_bucket_ref(badge, |builder, auth| {
+                    args.push(scrypto_encode(&auth));
+                    builder
+                        .add_instruction(Instruction::CallMethod {
+                            component_address: adapter.component,
+                            method: method.to_owned(),
+                            args,
+                        })
+                        .0
+                })
+            })
+            .call_method_with_all_resources(self.account, "deposit_batch")
+            .build(vec![self.key])
+            .unwrap();
+        self.executor.run(transaction).unwrap()
+    }
+
+    /// instantiate an adapter, its owner badge is the only new resource of the account
+    fn instantiate_adapter(&mut self, blueprint: &str, args: Vec<String>) -> Adapter {
+        let before = get_balances(self.executor.ledger(), self.account);
+        let package = self.package;
+        let receipt = self.call_function(package, blueprint, "instantiate", args);
+        Adapter {
+            component: receipt.component(0).unwrap(),
+            owner_badge: self.new_resource(&before),
+        }
+    }
+
+    /// the resource held by the account now and not in `before`
+    fn new_resource(&self, before: &HashMap<Address, Decimal>) -> Address {
+        let after = get_balances(self.executor.ledger(), self.account);
+        let mut new_resources = after
+            .into_iter()
+            .filter(|(address, amount)| !before.contains_key(address) && *amount > Decimal::zero())
+            .map(|(address, _)| address);
+        let resource = new_resources.next().expect("no new resource");
+        assert!(new_resources.next().is_none(), "more than one new resource");
+        resource
+    }
+
+    fn balance(&self, resource: Address) -> Decimal {
+        *get_balances(self.executor.ledger(), self.account)
+            .get(&resource)
+            .unwrap_or(&Decimal::zero())
+    }
+
+    fn place_limit(
+        &mut self,
+        adapter: &Adapter,
+        side: Side,
+        price: Decimal,
+        amount: Decimal,
+    ) -> Receipt {
+        let paid = match side {
+            Side::Bid => self.quote,
+            Side::Ask => self.base,
+        };
+        let args = vec![scrypto_encode(&side), scrypto_encode(&price)];
+        self.call_adapter(adapter, "place_limit", args, Some((amount, paid)))
+    }
+
+    fn place_market(&mut self, adapter: &Adapter, side: Side, amount: Decimal) -> Receipt {
+        let paid = match side {
+            Side::Bid => self.quote,
+            Side::Ask => self.base,
+        };
+        let args = vec![scrypto_encode(&side)];
+        self.call_adapter(adapter, "place_market", args, Some((amount, paid)))
+    }
+
+    fn cancel(&mut self, adapter: &Adapter, ticket_def: Address) -> Receipt {
+        self.call_adapter(
+            adapter,
+            "cancel",
+            vec![],
+            Some((Decimal::one(), ticket_def)),
+        )
+    }
+
+    fn best_bid_ask(&mut self, adapter: &Adapter) -> (Option<Decimal>, Option<Decimal>) {
+        let receipt = self.call_method(adapter.component, "best_bid_ask", vec![]);
+        assert!(receipt.result.is_ok());
+        scrypto_decode(&receipt.outputs[0].raw).unwrap()
+    }
+
+    fn depth(&mut self, adapter: &Adapter, levels: u32) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        let receipt = self.call_method(adapter.component, "depth", vec![levels.to_string()]);
+        assert!(receipt.result.is_ok());
+        scrypto_decode(&receipt.outputs[0].raw).unwrap()
+    }
+
+    /// A limit ask rests in the book behind a ticket and cancelling the ticket gives the tokens back.
+    /// Return the resource of the tickets.
+    fn check_limit_and_cancel(&mut self, adapter: &Adapter) -> Address {
+        let base_before = self.balance(self.base);
+        let before = get_balances(self.executor.ledger(), self.account);
+
+        let receipt = self.place_limit(adapter, Side::Ask, dec!("2"), dec!("10"));
+        assert!(receipt.result.is_ok());
+        let ticket_def = self.new_resource(&before);
+        assert_eq!(self.balance(ticket_def), Decimal::one());
+        assert_eq!(self.balance(self.base), base_before - dec!("10"));
+        assert_eq!(self.best_bid_ask(adapter), (None, Some(dec!("2"))));
+        assert_eq!(
+            self.depth(adapter, 5).1,
+            vec![PriceLevel {
+                price: dec!("2"),
+                amount: dec!("10"),
+                order_count: 1
+            }]
+        );
+
+        let receipt = self.cancel(adapter, ticket_def);
+        assert!(receipt.result.is_ok());
+        assert_eq!(self.balance(ticket_def), Decimal::zero());
+        assert_eq!(self.balance(self.base), base_before);
+        assert_eq!(self.best_bid_ask(adapter), (None, None));
+        ticket_def
+    }
+}
+
+fn get_balances<'a, L: SubstateStore>(
+    ledger: &'a L,
+    account: Address,
+) -> HashMap<Address, Decimal> {
+    let component = ledger.get_component(account).unwrap();
+    let validated_data = validate_data(component.state()).unwrap();
+    validated_data
+        .lazy_maps
+        .iter()
+        .flat_map(|mid| {
+            let lazy_map = ledger.get_lazy_map(&account, mid).unwrap();
+            lazy_map
+                .map()
+                .iter()
+                .flat_map(|(_, data)| validate_data(data).unwrap().vaults)
+                .map(|vid| {
+                    let vault = ledger.get_vault(&account, &vid).unwrap();
+                    (vault.resource_address(), vault.amount())
+                })
+                .collect::<Vec<(Address, Decimal)>>()
+        })
+        .collect()
+}
+
+#[test]
+fn test_orderbook_adapter() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = TestEnv::new(&mut ledger);
+    let (base, quote) = (env.base, env.quote);
+    let book_package = env.publish("../orderbook", "orderbook");
+    let receipt = env.call_function(
+        book_package,
+        "Market",
+        "instantiate_market",
+        vec![
+            quote.to_string(),
+            base.to_string(),
+            "TestMarket".to_string(),
+        ],
+    );
+    let market = receipt.component(0).unwrap();
+    let adapter = env.instantiate_adapter(
+        "OrderbookAdapter",
+        vec![market.to_string(), quote.to_string(), base.to_string()],
+    );
+
+    let ticket_def = env.check_limit_and_cancel(&adapter);
+
+    // the account trades on the market with its own open orders badge as the maker
+    let before = get_balances(env.executor.ledger(), env.account);
+    assert!(env
+        .call_method(market, "create_openorders", vec![])
+        .result
+        .is_ok());
+    let openorders_badge = env.new_resource(&before);
+    let push_ask = |env: &mut TestEnv<_>, price: &str, amount: &str| {
+        let receipt = env.call_method(
+            market,
+            "ask_order",
+            vec![
+                price.to_string(),
+                amount.to_string(),
+                "0".to_string(),
+                format!("{},{}", amount, base),
+                format!("1,{}", openorders_badge),
+            ],
+        );
+        assert!(receipt.result.is_ok());
+    };
+
+    // a limit bid filled in full leaves no ticket
+    push_ask(&mut env, "2", "10");
+    assert!(env
+        .place_limit(&adapter, Side::Bid, dec!("2"), dec!("20"))
+        .result
+        .is_ok());
+    assert_eq!(env.balance(ticket_def), Decimal::zero());
+    assert_eq!(env.best_bid_ask(&adapter), (None, None));
+
+    // a market bid sweeps the asks until its quote runs out
+    push_ask(&mut env, "2", "5");
+    push_ask(&mut env, "4", "5");
+    assert!(env
+        .place_market(&adapter, Side::Bid, dec!("15"))
+        .result
+        .is_ok());
+    assert_eq!(env.best_bid_ask(&adapter), (None, Some(dec!("4"))));
+    let asks = env.depth(&adapter, 5).1;
+    assert_eq!(asks.len(), 1);
+    assert!(asks[0].amount < dec!("5"));
+}
+
+#[test]
+fn test_croq_adapter() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = TestEnv::new(&mut ledger);
+    let (base, quote) = (env.base, env.quote);
+    let book_package = env.publish("../croq_order_book", "croq_order_book");
+    let receipt = env.call_function(
+        book_package,
+        "CroqOrderBook",
+        "instantiate",
+        vec![base.to_string(), quote.to_string()],
+    );
+    let book = receipt.component(0).unwrap();
+    let adapter = env.instantiate_adapter(
+        "CroqAdapter",
+        vec![book.to_string(), base.to_string(), quote.to_string()],
+    );
+
+    env.check_limit_and_cancel(&adapter);
+}
+
+#[test]
+fn test_chain_book_adapter() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = TestEnv::new(&mut ledger);
+    let (base, quote) = (env.base, env.quote);
+    let book_package = env.publish("../ChainDEX", "chain-dex");
+    let receipt = env.call_function(
+        book_package,
+        "ChainBook",
+        "instantiate_chain_book",
+        vec!["TestBook".to_string(), base.to_string(), quote.to_string()],
+    );
+    let book = receipt.component(0).unwrap();
+    let adapter = env.instantiate_adapter(
+        "ChainBookAdapter",
+        vec![book.to_string(), base.to_string(), quote.to_string()],
+    );
+
+    env.check_limit_and_cancel(&adapter);
+}
+
+#[test]
+fn test_petitcroco_adapter() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = TestEnv::new(&mut ledger);
+    let (base, quote, account) = (env.base, env.quote, env.account);
+    let book_package = env.publish("../petitcroco-OrderBook", "petitcroco-OrderBook");
+    let before = get_balances(env.executor.ledger(), account);
+    let receipt = env.call_function(book_package, "OrderBook", "init", vec![]);
+    let book = receipt.component(0).unwrap();
+    let admin_badge = env.new_resource(&before);
+    for (input, output) in [(base, quote), (quote, base)] {
+        let receipt = env.call_method(
+            book,
+            "init_pair_orderbook",
+            vec![
+                input.to_string(),
+                output.to_string(),
+                format!("1,{}", admin_badge),
+            ],
+        );
+        assert!(receipt.result.is_ok());
+    }
+    let adapter = env.instantiate_adapter(
+        "PetitcrocoAdapter",
+        vec![
+            book.to_string(),
+            base.to_string(),
+            quote.to_string(),
+            account.to_string(),
+        ],
+    );
+
+    // the book refunds the cancelled order to the payout account, the account of the test
+    env.check_limit_and_cancel(&adapter);
+}
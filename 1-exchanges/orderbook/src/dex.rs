@@ -8,11 +8,15 @@
 //! StateOrder is temporary struct to manage order logic using type state.   
 //!
 use crate::order::BTreeSetOrder;
+use crate::order::DepthLevel;
+use crate::order::OpenOrder;
 use crate::order::Order;
 use crate::order::OrderType;
 use crate::order::OrdersContainer;
 use crate::order::PriceOrder;
+use crate::order::Side;
 use crate::order::UserOrders;
+use crate::order::UserOrdersView;
 use sbor::*;
 use scrypto::prelude::*;
 use scrypto::rust::marker::PhantomData;
@@ -20,6 +24,8 @@ use scrypto::rust::marker::PhantomData;
 static MAKER_FEE: usize = 5;
 static TAKER_FEE: usize = 10;
 
+/// Fee are percentage of the transferred asset.
+/// Maker fee can be negative: it's a rebate paid to the maker from the taker fee.
 #[derive(Debug, TypeId, Encode, Decode, Describe)]
 pub struct DexParameters {
     pub quote_token: ResourceDef,
@@ -60,6 +66,7 @@ impl Dex {
     /// To manage a bid, user must provide enougth quote to buy the base at max limite price.
     /// Provided quote is put User quote vault. Quote vault amount but be greater that needed quote to match at limite price.
     /// Needed quote is locked in a vault for the whole duration of the buy order
+    /// For market order, the price is ignored and all the provided quote is locked. The order is matched until the amount of base is bought or the quote is used.
     // Pushed order are BidSide and TakerPos
     // they are matched against AskSide and MakerPos
     pub fn bid(
@@ -69,21 +76,42 @@ impl Dex {
         amount: Decimal,
         mut quote: Bucket,
         order_type: OrderType,
+        fee_discount: Decimal,
     ) -> (Bucket, Bucket) {
-        let (mut bid, mut bid_trader_orders) =
-            StateOrder::<BidSide, TakerPos>::init_match(owner, price, amount, &mut quote, self);
+        let market = order_type == OrderType::Market;
+        let (mut bid, mut bid_trader_orders) = StateOrder::<BidSide, TakerPos>::init_match(
+            owner,
+            price,
+            amount,
+            market,
+            fee_discount,
+            &mut quote,
+            self,
+        );
 
         let new_id = bid.order.id;
 
         //manage order type
         match order_type {
-            OrderType::Limit | OrderType::ImmediateOrCancel => {
+            OrderType::PostOnly => {
+                let cross = self
+                    .containers
+                    .asks
+                    .find_match(StateOrder::<BidSide, TakerPos>::find_order)
+                    .map_or(false, |ask| {
+                        StateOrder::<BidSide, TakerPos>::order_match(price, &ask)
+                    });
+                assert!(!cross, "Post only order would match an existing order.");
+                self.containers.bids.insert(bid.order);
+            }
+            _ => {
                 let (remain_bid, remain_ask) = loop {
                     match match_taker_order(
                         &bid,
                         &mut self.containers.asks,
                         StateOrder::<BidSide, TakerPos>::find_order,
                         StateOrder::<BidSide, TakerPos>::order_match,
+                        StateOrder::<BidSide, TakerPos>::max_base_amount,
                         &self.params,
                     ) {
                         None => break (Some(bid), None), //no match found
@@ -114,21 +142,35 @@ impl Dex {
                                     //continue to match, if ask exist
                                     bid = remain_bid.unwrap();
                                 }
-                                (Some(_), Some(_)) => {
-                                    unreachable!("Double match should not arrive")
+                                (Some(remain_bid), remain_ask) => {
+                                    //the bid has used all its locked quote before filling the ask (market bid).
+                                    //The bid is exhausted: unlock what remains, the ask remainder goes back to the book.
+                                    StateOrder::<BidSide, TakerPos>::cancel_order(
+                                        &remain_bid.order,
+                                        &remain_bid.order.owner,
+                                        self,
+                                    );
+                                    break (None, remain_ask);
                                 }
                             }
                         }
                     }
                 };
-                remain_ask.map(|ask| self.containers.asks.insert(ask.order));
-                if let OrderType::Limit = order_type {
-                    remain_bid.map(|bid| self.containers.bids.insert(bid.order));
+                //partially filled maker order keep its priority.
+                remain_ask.map(|ask| self.containers.asks.insert_front(ask.order));
+                if let Some(bid) = remain_bid {
+                    match order_type {
+                        OrderType::Limit => self.containers.bids.insert(bid.order),
+                        OrderType::FillOrKill => panic!("Fill or kill order not fully matched."),
+                        //IOC and market: unlock the quote of the not matched part.
+                        _ => StateOrder::<BidSide, TakerPos>::cancel_order(
+                            &bid.order,
+                            &bid.order.owner,
+                            self,
+                        ),
+                    }
                 }
             }
-            OrderType::PostOnly => {
-                self.containers.bids.insert(bid.order);
-            }
         };
         (create_order_id(new_id), quote)
     }
@@ -143,37 +185,59 @@ impl Dex {
         amount: Decimal,
         mut base: Bucket,
         order_type: OrderType,
+        fee_discount: Decimal,
     ) -> (Bucket, Bucket) {
-        let (mut ask, mut ask_trader_orders) =
-            StateOrder::<AskSide, TakerPos>::init_match(owner, price, amount, &mut base, self);
+        let market = order_type == OrderType::Market;
+        let (mut ask, mut ask_trader_orders) = StateOrder::<AskSide, TakerPos>::init_match(
+            owner,
+            price,
+            amount,
+            market,
+            fee_discount,
+            &mut base,
+            self,
+        );
         let new_id = ask.order.id;
 
         //manage order type
         match order_type {
-            OrderType::Limit | OrderType::ImmediateOrCancel => {
+            OrderType::PostOnly => {
+                let cross = self
+                    .containers
+                    .bids
+                    .find_match(StateOrder::<AskSide, TakerPos>::find_order)
+                    .map_or(false, |bid| {
+                        StateOrder::<AskSide, TakerPos>::order_match(price, &bid)
+                    });
+                assert!(!cross, "Post only order would match an existing order.");
+                self.containers.asks.insert(ask.order);
+            }
+            _ => {
                 let (remain_bid, remain_ask) = loop {
                     match match_taker_order(
                         &ask,
                         &mut self.containers.bids,
                         StateOrder::<AskSide, TakerPos>::find_order,
                         StateOrder::<AskSide, TakerPos>::order_match,
+                        StateOrder::<AskSide, TakerPos>::max_base_amount,
                         &self.params,
                     ) {
                         None => break (None, Some(ask)), //no match found
                         Some((matched, bid)) => {
                             let mut bid_trader_orders =
                                 UserOrders::get_user_orders(&bid.order.owner, &self);
-                            let remain_bid = bid.transfer_bid_match(
+                            //taker first, its fee pay the maker rebate.
+                            let remain_ask = ask.transfer_ask_match(
                                 &mut bid_trader_orders,
                                 &mut ask_trader_orders,
-                                matched.remainder_maker_base,
+                                matched.remainder_taker_base,
                                 &matched,
                                 self,
                             );
-                            let remain_ask = ask.transfer_ask_match(
+                            let remain_bid = bid.transfer_bid_match(
                                 &mut bid_trader_orders,
                                 &mut ask_trader_orders,
-                                matched.remainder_taker_base,
+                                matched.remainder_maker_base,
                                 &matched,
                                 self,
                             );
@@ -192,14 +256,21 @@ impl Dex {
                         }
                     }
                 };
-                remain_bid.map(|bid| self.containers.bids.insert(bid.order));
-                if let OrderType::Limit = order_type {
-                    remain_ask.map(|ask| self.containers.asks.insert(ask.order));
+                //partially filled maker order keep its priority.
+                remain_bid.map(|bid| self.containers.bids.insert_front(bid.order));
+                if let Some(ask) = remain_ask {
+                    match order_type {
+                        OrderType::Limit => self.containers.asks.insert(ask.order),
+                        OrderType::FillOrKill => panic!("Fill or kill order not fully matched."),
+                        //IOC and market: unlock the base of the not matched part.
+                        _ => StateOrder::<AskSide, TakerPos>::cancel_order(
+                            &ask.order,
+                            &ask.order.owner,
+                            self,
+                        ),
+                    }
                 }
             }
-            OrderType::PostOnly => {
-                self.containers.asks.insert(ask.order);
-            }
         };
         (create_order_id(new_id), base)
     }
@@ -225,6 +296,72 @@ impl Dex {
 
         order_id_badge
     }
+
+    /// Change the maker and taker fee. Maker fee can be negative (rebate) but can't be more than the taker fee.
+    pub fn set_fees(&mut self, maker_fee: Decimal, taker_fee: Decimal) {
+        let hundred: Decimal = Into::<Decimal>::into(100);
+        assert!(
+            taker_fee >= Decimal::zero() && taker_fee <= hundred,
+            "Taker fee must be between 0 and 100."
+        );
+        assert!(
+            maker_fee >= -taker_fee && maker_fee <= hundred,
+            "Maker rebate can't be more than the taker fee."
+        );
+        self.params.maker_fee = maker_fee;
+        self.params.taker_fee = taker_fee;
+    }
+
+    /// Take all the fee collected (quote, base).
+    pub fn withdraw_fees(&mut self) -> (Bucket, Bucket) {
+        (
+            self.fee_quote_vault.take_all(),
+            self.fee_base_vault.take_all(),
+        )
+    }
+
+    /// Return the L2 depth of the book for the `levels` best price levels: (bids, asks).
+    pub fn depth(&self, levels: usize) -> (Vec<DepthLevel>, Vec<DepthLevel>) {
+        (
+            self.containers.bids.depth(levels, true),
+            self.containers.asks.depth(levels, false),
+        )
+    }
+
+    /// Return the highest bid price and the lowest ask price if any.
+    pub fn best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+        (
+            self.containers.bids.best_price(true),
+            self.containers.asks.best_price(false),
+        )
+    }
+
+    /// Return the difference between the best ask and best bid price. None if one side is empty.
+    pub fn spread(&self) -> Option<Decimal> {
+        match self.best_bid_ask() {
+            (Some(bid), Some(ask)) => Some(ask - bid),
+            _ => None,
+        }
+    }
+
+    /// Return the user vaults amount and all its pending orders with the remaining amount.
+    pub fn user_orders_view(&self, owner: &NonFungibleKey) -> UserOrdersView {
+        let orders = self
+            .containers
+            .bids
+            .orders_of(owner)
+            .iter()
+            .map(|order| OpenOrder::new(order, Side::Bid))
+            .chain(
+                self.containers
+                    .asks
+                    .orders_of(owner)
+                    .iter()
+                    .map(|order| OpenOrder::new(order, Side::Ask)),
+            )
+            .collect();
+        UserOrders::get_user_orders(owner, self).view(orders)
+    }
 }
 
 ///Match indicate that a corresponding order has been found
@@ -235,6 +372,8 @@ struct Match {
     transfert_user_quote: Decimal,
     remainder_maker_base: Decimal,
     remainder_taker_base: Decimal,
+    //fee percentage of the taker, its fee on this match pays the maker rebate.
+    taker_fee: Decimal,
 }
 
 #[derive(Debug)]
@@ -251,14 +390,32 @@ struct TakerPos;
 struct StateOrder<SIDE, POSITION> {
     order: Order,
     order_fee: Decimal,
+    // market order are matched without price limit.
+    market: bool,
     state: PhantomData<(SIDE, POSITION)>,
 }
 
 impl<SIDE, POSITION> StateOrder<SIDE, POSITION> {
-    fn new_with_order(order: Order, order_fee: Decimal) -> StateOrder<SIDE, POSITION> {
+    /// The order fee tier discount is applied to the fee but the discounted fee doesn't go below `min_fee`.
+    /// A rebate (negative fee) isn't changed.
+    fn new_with_order(
+        order: Order,
+        order_fee: Decimal,
+        min_fee: Decimal,
+    ) -> StateOrder<SIDE, POSITION> {
+        let order_fee = if order_fee > Decimal::zero() {
+            std::cmp::max(
+                order_fee * (Into::<Decimal>::into(100) - order.fee_discount)
+                    / Into::<Decimal>::into(100),
+                std::cmp::min(order_fee, min_fee),
+            )
+        } else {
+            order_fee
+        };
         StateOrder {
             order,
             order_fee,
+            market: false,
             state: PhantomData,
         }
     }
@@ -270,18 +427,25 @@ impl StateOrder<BidSide, TakerPos> {
         owner: NonFungibleKey,
         price: Decimal,
         amount: Decimal,
+        market: bool,
+        fee_discount: Decimal,
         quote: &mut Bucket,
         dex: &mut Dex,
     ) -> (StateOrder<BidSide, TakerPos>, UserOrders) {
         let mut bid_trader_orders = UserOrders::get_user_orders(&owner, &dex);
 
+        //market order lock all the provided quote. Limit order the quote needed at limit price.
+        let locked_amount = if market {
+            quote.amount()
+        } else {
+            price * amount
+        };
         //put quote in user Vault.
         bid_trader_orders
             .quote_vault
             .put(quote.take(quote.amount()));
         //lock enought quote for the order
         let available_amount = bid_trader_orders.quote_vault.amount();
-        let locked_amount = price * amount;
         assert!(
             available_amount >= locked_amount,
             "Not enougth quote provided."
@@ -296,11 +460,16 @@ impl StateOrder<BidSide, TakerPos> {
             price,
             amount,
             locked_amount,
+            fee_discount,
         };
-        (
-            StateOrder::<BidSide, TakerPos>::new_with_order(order, dex.params.taker_fee),
-            bid_trader_orders,
-        )
+        //the discounted taker fee must still pay the maker rebate.
+        let mut bid = StateOrder::<BidSide, TakerPos>::new_with_order(
+            order,
+            dex.params.taker_fee,
+            -dex.params.maker_fee,
+        );
+        bid.market = market;
+        (bid, bid_trader_orders)
     }
 
     /// Code to cancel Bid order.
@@ -322,6 +491,11 @@ impl StateOrder<BidSide, TakerPos> {
     fn find_order(set: &BTreeSet<PriceOrder>) -> Option<PriceOrder> {
         set.iter().cloned().min()
     }
+
+    /// Return the max base that can be bought at match price with the locked quote.
+    fn max_base_amount(&self, match_price: Decimal) -> Decimal {
+        std::cmp::min(self.order.amount, self.order.locked_amount / match_price)
+    }
 }
 
 impl StateOrder<AskSide, TakerPos> {
@@ -330,6 +504,8 @@ impl StateOrder<AskSide, TakerPos> {
         owner: NonFungibleKey,
         price: Decimal,
         amount: Decimal,
+        market: bool,
+        fee_discount: Decimal,
         base: &mut Bucket,
         dex: &mut Dex,
     ) -> (StateOrder<AskSide, TakerPos>, UserOrders) {
@@ -347,11 +523,16 @@ impl StateOrder<AskSide, TakerPos> {
             price,
             amount,
             locked_amount: Decimal::zero(),
+            fee_discount,
         };
-        (
-            StateOrder::<AskSide, TakerPos>::new_with_order(order, dex.params.taker_fee),
-            ask_trader_orders,
-        )
+        //the discounted taker fee must still pay the maker rebate.
+        let mut ask = StateOrder::<AskSide, TakerPos>::new_with_order(
+            order,
+            dex.params.taker_fee,
+            -dex.params.maker_fee,
+        );
+        ask.market = market;
+        (ask, ask_trader_orders)
     }
 
     /// Code to cancel Ask order.
@@ -373,6 +554,11 @@ impl StateOrder<AskSide, TakerPos> {
     fn find_order(set: &BTreeSet<PriceOrder>) -> Option<PriceOrder> {
         set.iter().cloned().max()
     }
+
+    /// Return the max base that can be sold. All the order base is locked.
+    fn max_base_amount(&self, _match_price: Decimal) -> Decimal {
+        self.order.amount
+    }
 }
 
 impl<POSITION> StateOrder<BidSide, POSITION> {
@@ -387,16 +573,32 @@ impl<POSITION> StateOrder<BidSide, POSITION> {
     ) -> Option<StateOrder<BidSide, POSITION>> {
         //Calculate fee and transfert matched order in trader's Vaults.
         //for bid side take fee from base because bid receive base.
-        let bid_fee_base_amount =
-            matched.transfert_user_base * self.order_fee / Into::<Decimal>::into(100);
-        dex.fee_base_vault
-            .put(ask_trader.locked_base_vault.take(bid_fee_base_amount));
-
-        bid_trader.base_vault.put(
-            ask_trader
-                .locked_base_vault
-                .take(matched.transfert_user_base - bid_fee_base_amount),
-        );
+        if self.order_fee >= Decimal::zero() {
+            let bid_fee_base_amount =
+                matched.transfert_user_base * self.order_fee / Into::<Decimal>::into(100);
+            dex.fee_base_vault
+                .put(ask_trader.locked_base_vault.take(bid_fee_base_amount));
+
+            bid_trader.base_vault.put(
+                ask_trader
+                    .locked_base_vault
+                    .take(matched.transfert_user_base - bid_fee_base_amount),
+            );
+        } else {
+            //maker rebate: receive all the base and the rebate in quote from the taker fee.
+            bid_trader.base_vault.put(
+                ask_trader
+                    .locked_base_vault
+                    .take(matched.transfert_user_base),
+            );
+            let rebate_quote_amount = std::cmp::min(
+                matched.transfert_user_quote * -self.order_fee / Into::<Decimal>::into(100),
+                matched.transfert_user_quote * matched.taker_fee / Into::<Decimal>::into(100),
+            );
+            bid_trader
+                .quote_vault
+                .put(dex.fee_quote_vault.take(rebate_quote_amount));
+        }
 
         //update bid and ask order with remainding
         self.order.amount = remain_base;
@@ -428,15 +630,31 @@ impl<POSITION> StateOrder<AskSide, POSITION> {
     ) -> Option<StateOrder<AskSide, POSITION>> {
         //Calculate fee and transfert matched order in trader's Vaults.
         //for ask side take fee from quote because ask receive quote.
-        let ask_fee_base_amount =
-            matched.transfert_user_quote * self.order_fee / Into::<Decimal>::into(100);
-        dex.fee_quote_vault
-            .put(bid_trader.locked_quote_vault.take(ask_fee_base_amount));
-        ask_trader.quote_vault.put(
-            bid_trader
-                .locked_quote_vault
-                .take(matched.transfert_user_quote - ask_fee_base_amount),
-        );
+        if self.order_fee >= Decimal::zero() {
+            let ask_fee_base_amount =
+                matched.transfert_user_quote * self.order_fee / Into::<Decimal>::into(100);
+            dex.fee_quote_vault
+                .put(bid_trader.locked_quote_vault.take(ask_fee_base_amount));
+            ask_trader.quote_vault.put(
+                bid_trader
+                    .locked_quote_vault
+                    .take(matched.transfert_user_quote - ask_fee_base_amount),
+            );
+        } else {
+            //maker rebate: receive all the quote and the rebate in base from the taker fee.
+            ask_trader.quote_vault.put(
+                bid_trader
+                    .locked_quote_vault
+                    .take(matched.transfert_user_quote),
+            );
+            let rebate_base_amount = std::cmp::min(
+                matched.transfert_user_base * -self.order_fee / Into::<Decimal>::into(100),
+                matched.transfert_user_base * matched.taker_fee / Into::<Decimal>::into(100),
+            );
+            ask_trader
+                .base_vault
+                .put(dex.fee_base_vault.take(rebate_base_amount));
+        }
         //update bid and ask order with remainding
         self.order.amount = remain_base;
 
@@ -446,38 +664,55 @@ impl<POSITION> StateOrder<AskSide, POSITION> {
 
 /// Try to find a match order for the specified order of SIDE.
 /// If an order is found calculate the asset to transfer.
+/// The found order is removed from the set only if it's matched. If partially filled, it's put back by the caller.
+/// Market order match any price at the found order price.
 /// Transfer is done later one order after the other.
-fn match_taker_order<SIDE, SIDE2: std::fmt::Debug, F, M>(
+fn match_taker_order<SIDE, SIDE2: std::fmt::Debug, F, M, B>(
     taker: &StateOrder<SIDE, TakerPos>,
     set: &mut BTreeSetOrder,
     find_order: F,
     order_match: M,
+    max_base_amount: B,
     dex: &DexParameters,
 ) -> Option<(Match, StateOrder<SIDE2, MakerPos>)>
 where
     F: Fn(&BTreeSet<PriceOrder>) -> Option<PriceOrder>,
     M: Fn(Decimal, &Order) -> bool,
+    B: Fn(&StateOrder<SIDE, TakerPos>, Decimal) -> Decimal,
 {
     //BTreeSet min only in Nigthly.
-    let matched = set
-        .find_match_and_take_order(find_order)
-        .and_then(|found_order| {
-            if order_match(taker.order.price, &found_order) {
-                let match_price = std::cmp::min(taker.order.price, found_order.price);
-                let base_to_transfert = std::cmp::min(found_order.amount, taker.order.amount);
-                Some((
-                    Match {
-                        transfert_user_quote: base_to_transfert * match_price,
-                        transfert_user_base: base_to_transfert,
-                        remainder_maker_base: found_order.amount - base_to_transfert,
-                        remainder_taker_base: taker.order.amount - base_to_transfert,
-                    },
-                    StateOrder::<SIDE2, MakerPos>::new_with_order(found_order, dex.maker_fee),
-                ))
+    let matched = set.find_match(find_order).and_then(|found_order| {
+        if taker.market || order_match(taker.order.price, &found_order) {
+            let match_price = if taker.market {
+                found_order.price
             } else {
-                None
+                std::cmp::min(taker.order.price, found_order.price)
+            };
+            let base_to_transfert =
+                std::cmp::min(found_order.amount, max_base_amount(taker, match_price));
+            if base_to_transfert == Decimal::zero() {
+                //no more asset to match.
+                return None;
             }
-        });
+            let found_order = set.take_order_with_id(found_order.id).unwrap();
+            Some((
+                Match {
+                    transfert_user_quote: base_to_transfert * match_price,
+                    transfert_user_base: base_to_transfert,
+                    remainder_maker_base: found_order.amount - base_to_transfert,
+                    remainder_taker_base: taker.order.amount - base_to_transfert,
+                    taker_fee: taker.order_fee,
+                },
+                StateOrder::<SIDE2, MakerPos>::new_with_order(
+                    found_order,
+                    dex.maker_fee,
+                    Decimal::zero(),
+                ),
+            ))
+        } else {
+            None
+        }
+    });
     matched
 }
 
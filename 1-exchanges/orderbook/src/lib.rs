@@ -11,6 +11,10 @@ blueprint! {
         //manage open orders user access
         orders_badge_minter: Vault,
         orders_badge_def: ResourceDef,
+        //admin access to fee management
+        admin_badge_def: ResourceDef,
+        //fee tier NFT that give a discount on the order fee
+        fee_tier_badge_def: ResourceDef,
 
         // Define market data
         pub dex: dex::Dex,
@@ -24,7 +28,7 @@ blueprint! {
         /// Return the created market component address and market admin access badge.
         ///
         /// Market define 2 level of access:
-        ///  * admin: badge return by this function that can withdraw fee gain during asset transfert
+        ///  * admin: badge return by this function that can withdraw fee gain during asset transfert, change the fee and mint fee tier badge
         ///  * trader or user badge needed to push order to the book
         ///
         pub fn instantiate_market(
@@ -51,10 +55,19 @@ blueprint! {
                 .badge(orders_badge_minter.resource_def(), MAY_MINT)
                 .no_initial_supply();
 
+            // Fee tier NFT minted by the admin for professional traders
+            let fee_tier_badge_def = ResourceBuilder::new_non_fungible()
+                .metadata("name", &format!("Fee tier challenge market:{}", name))
+                .flags(MINTABLE )
+                .badge(orders_badge_minter.resource_def(), MAY_MINT)
+                .no_initial_supply();
+
             (
                 Self {
                     orders_badge_minter: Vault::with_bucket(orders_badge_minter),
                     orders_badge_def,
+                    admin_badge_def: admin_badge.resource_def(),
+                    fee_tier_badge_def,
                     name,
                     dex: dex::Dex::new(quote_token, base_token),
                 }
@@ -89,11 +102,18 @@ blueprint! {
         //use u8 for order type beacause I didn't find an example with an Option as Tx parameter.
         /// Push a bid order to the order book. Bid order are a max limit price, a amount of base to buy and a quote bucket containing all the quote
         /// needed to by the base at the limit price.
+        /// A market bid order use all the provided quote to buy up to the amount of base.
         /// Order type define how the order will be matched.
         ///  * limit: 0 when the order is push the maximum amount of quote is transferred depending on the available opposite order in the book.
         ///    If some amount can't be matched, the remaining is added to the order book.
         ///  * Immediate or Cancel: 1 same as limit but if a remaining amount can't be matched, it's cancelled.
         ///  * Post: 2 the order is not matched an immediately added to the order book. It can be useful to decrease the fee (see Fee management).
+        ///    The transaction fails if the order would match an order of the book.
+        ///  * Fill or Kill: 3 the order must be fully matched or the transaction fails.
+        ///  * Market: 4 the price is ignored and the order is matched at the book prices, sweeping several price levels if needed.
+        ///    The remaining that can't be matched is cancelled.
+        ///
+        /// When an order of the book is partially matched, its remaining stays in the book with the same priority.
         ///
         /// Return the order id address use to cancel it and the rest of the quote not used.
         /// Provided quote is locked while the order is still pending and it's free when the order is cancelled or transferred when it's matched.
@@ -112,6 +132,7 @@ blueprint! {
                 amount,
                 quote,
                 ordre_type.into(),
+                Decimal::zero(),
             )
         }
 
@@ -122,6 +143,12 @@ blueprint! {
         ///    If some amount can't be matched, the remaining is added to the order book.
         ///  * Immediate or Cancel: 1 same as limit but if a remaining amount can't be matched, it's cancelled.
         ///  * Post: 2 the order is not matched an immediately added to the order book. It can be useful to decrease the fee (see Fee management).
+        ///    The transaction fails if the order would match an order of the book.
+        ///  * Fill or Kill: 3 the order must be fully matched or the transaction fails.
+        ///  * Market: 4 the price is ignored and the order is matched at the book prices, sweeping several price levels if needed.
+        ///    The remaining that can't be matched is cancelled.
+        ///
+        /// When an order of the book is partially matched, its remaining stays in the book with the same priority.
         ///
         /// Return the order id address use to cancel it and the rest of the quote not used.
         /// Provided base is locked while the order is still pending and it's free when the order is cancelled or transferred when it's matched.
@@ -140,9 +167,60 @@ blueprint! {
                 amount,
                 base,
                 ordre_type.into(),
+                Decimal::zero(),
+            )
+        }
+
+        /// Same as bid_order but the fee tier badge proof give a discount on the order fee.
+        #[auth(orders_badge_def)]
+        pub fn bid_order_with_fee_tier(&mut self, price: Decimal, amount: Decimal, ordre_type: u8, quote: Bucket, fee_tier: BucketRef) -> (Bucket, Bucket) {
+            info!("buy_order with fee tier");
+            let owner_keys = auth.get_non_fungible_keys();
+            let data: BadgeData = auth
+                .resource_def()
+                .get_non_fungible_data(owner_keys.get(0).unwrap());
+            assert!(data.name == self.name, "Not current market open order badge");
+            let fee_discount = self.fee_tier_discount(fee_tier);
+            self.dex.bid(
+                owner_keys.get(0).unwrap().clone(),
+                price,
+                amount,
+                quote,
+                ordre_type.into(),
+                fee_discount,
+            )
+        }
+
+        /// Same as ask_order but the fee tier badge proof give a discount on the order fee.
+        #[auth(orders_badge_def)]
+        pub fn ask_order_with_fee_tier(&mut self, price: Decimal, amount: Decimal, ordre_type: u8, base: Bucket, fee_tier: BucketRef) -> (Bucket, Bucket) {
+            info!("sell order with fee tier");
+            let owner_keys = auth.get_non_fungible_keys();
+            let data: BadgeData = auth
+                .resource_def()
+                .get_non_fungible_data(owner_keys.get(0).unwrap());
+            assert!(data.name == self.name, "Not current market open order badge");
+            let fee_discount = self.fee_tier_discount(fee_tier);
+            self.dex.ask(
+                owner_keys.get(0).unwrap().clone(),
+                price,
+                amount,
+                base,
+                ordre_type.into(),
+                fee_discount,
             )
         }
 
+        // Read the discount of the provided fee tier badge.
+        fn fee_tier_discount(&self, fee_tier: BucketRef) -> Decimal {
+            assert!(fee_tier.resource_def().address() == self.fee_tier_badge_def.address(), "Not current market fee tier badge");
+            let data: FeeTierData = fee_tier
+                .resource_def()
+                .get_non_fungible_data(fee_tier.get_non_fungible_keys().get(0).unwrap());
+            fee_tier.drop();
+            data.discount
+        }
+
         ///
         /// Withdraw all the asset (quote, base) store in the provided badge openorders and return it.
         /// Locked quote or base can't be withdrawn until associated order are pending in the order book.
@@ -177,6 +255,59 @@ blueprint! {
             assert!(data.name == self.name, "Not current market open order badge");
             self.dex.cancel_order(order_id_badge,owner_keys.get(0).unwrap().clone())
         }
+
+        /// Mint a fee tier badge. Orders pushed with the badge have their fee decreased by the discount percentage.
+        /// Maker rebate isn't changed by the discount, a taker fee isn't discounted below the maker rebate.
+        #[auth(admin_badge_def)]
+        pub fn mint_fee_tier_badge(&mut self, discount: Decimal) -> Bucket {
+            assert!(discount >= Decimal::zero() && discount <= Into::<Decimal>::into(100), "Discount must be between 0 and 100.");
+            self.orders_badge_minter.authorize(|auth| {
+                self.fee_tier_badge_def.mint_non_fungible(
+                    &NonFungibleKey::from(Uuid::generate()),
+                    FeeTierData {
+                        name: self.name.clone(),
+                        discount,
+                    },
+                    auth,
+                )
+            })
+        }
+
+        /// Change the maker and taker fee in percent. A negative maker fee is a rebate paid with the taker fee.
+        #[auth(admin_badge_def)]
+        pub fn set_fees(&mut self, maker_fee: Decimal, taker_fee: Decimal) {
+            self.dex.set_fees(maker_fee, taker_fee);
+        }
+
+        /// Withdraw all the collected fee (quote, base).
+        /// Maker rebate is paid with the taker fee of the same match, withdraw doesn't change it.
+        #[auth(admin_badge_def)]
+        pub fn withdraw_fees(&mut self) -> (Bucket, Bucket) {
+            self.dex.withdraw_fees()
+        }
+
+        /// Return the L2 depth of the book for the `levels` best price levels of each side.
+        /// Each level contains the price, the total amount of base and the number of orders at this price.
+        /// Bids are sorted from the highest price and asks from the lowest price.
+        pub fn get_depth(&self, levels: u32) -> (Vec<order::DepthLevel>, Vec<order::DepthLevel>) {
+            self.dex.depth(levels as usize)
+        }
+
+        /// Return the best bid (highest) and best ask (lowest) price. None if there's no order on the side.
+        pub fn get_best_bid_ask(&self) -> (Option<Decimal>, Option<Decimal>) {
+            self.dex.best_bid_ask()
+        }
+
+        /// Return the spread between the best ask and the best bid. None if one side of the book is empty.
+        pub fn get_spread(&self) -> Option<Decimal> {
+            self.dex.spread()
+        }
+
+        /// Return the view of the user openorders identified by its badge key:
+        /// quote and base amount (available and locked) and the pending orders with their remaining amount of base.
+        pub fn get_user_orders(&self, owner: NonFungibleKey) -> order::UserOrdersView {
+            self.dex.user_orders_view(&owner)
+        }
     }
 
 }
@@ -185,3 +316,9 @@ blueprint! {
 pub struct BadgeData {
     name: String,
 }
+
+#[derive(Debug, Clone, NonFungibleData)]
+pub struct FeeTierData {
+    name: String,
+    discount: Decimal,
+}
@@ -15,32 +15,28 @@ pub struct BTreeSetOrder {
 }
 
 impl BTreeSetOrder {
-    pub fn find_match_and_take_order<F>(&mut self, find_order: F) -> Option<Order>
+    /// Return the first order of the price level selected by find_order without removing it from the set.
+    /// The order is only taken from the set once it's sure that it's matched (see take_order_with_id).
+    pub fn find_match<F>(&self, find_order: F) -> Option<Order>
     where
         F: Fn(&BTreeSet<PriceOrder>) -> Option<PriceOrder>,
     {
-        let found = find_order(&self.set);
-        found
-            .as_ref()
-            .and_then(|order| self.take_order_with_price(order))
+        find_order(&self.set).and_then(|price_order| price_order.order_list.first().cloned())
     }
 
-    fn take_order_with_price(&mut self, order: &PriceOrder) -> Option<Order> {
-        //rm not get_mut in BtreeSet
-        let found = self.set.take(order);
-        found.map(|mut found_order| {
-            let ret = found_order.take_next_order();
-            if found_order.is_empty() {
-                self.order_index.remove(&ret.id);
-                ret
-            } else {
-                self.set.insert(found_order);
-                ret
-            }
-        })
+    /// Add the order at the end of its price level.
+    pub fn insert(&mut self, order: Order) {
+        self.insert_in_price_order(order, false);
     }
 
-    pub fn insert(&mut self, order: Order) {
+    /// Add the order at the front of its price level.
+    /// Used to put back the remaining of a partially filled order without losing its time priority.
+    pub fn insert_front(&mut self, order: Order) {
+        self.insert_in_price_order(order, true);
+    }
+
+    fn insert_in_price_order(&mut self, order: Order, front: bool) {
+        //rm not get_mut in BtreeSet
         let empty = PriceOrder::new(order.price);
         let mut price_order = if self.set.contains(&empty) {
             self.set.take(&empty).unwrap()
@@ -48,7 +44,11 @@ impl BTreeSetOrder {
             empty
         };
         self.order_index.insert(order.id, order.price);
-        price_order.order_list.push(order);
+        if front {
+            price_order.order_list.insert(0, order);
+        } else {
+            price_order.order_list.push(order);
+        }
         self.set.insert(price_order);
     }
 
@@ -71,6 +71,45 @@ impl BTreeSetOrder {
                 .flatten()
         })
     }
+
+    /// Return the aggregated depth of the `levels` first price levels starting from the best price.
+    /// Best price is the highest for bids (descending) and the lowest for asks.
+    pub fn depth(&self, levels: usize, descending: bool) -> Vec<DepthLevel> {
+        if descending {
+            self.set
+                .iter()
+                .rev()
+                .take(levels)
+                .map(PriceOrder::depth_level)
+                .collect()
+        } else {
+            self.set
+                .iter()
+                .take(levels)
+                .map(PriceOrder::depth_level)
+                .collect()
+        }
+    }
+
+    /// Return the best price of the set. Highest for bids (descending) and lowest for asks.
+    pub fn best_price(&self, descending: bool) -> Option<Decimal> {
+        let best = if descending {
+            self.set.iter().next_back()
+        } else {
+            self.set.iter().next()
+        };
+        best.map(|price_order| price_order.price)
+    }
+
+    /// Return all the pending orders owned by the specified user.
+    pub fn orders_of(&self, owner: &NonFungibleKey) -> Vec<Order> {
+        self.set
+            .iter()
+            .flat_map(|price_order| price_order.order_list.iter())
+            .filter(|order| &order.owner == owner)
+            .cloned()
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
@@ -92,10 +131,6 @@ impl PriceOrder {
             order_list: vec![],
         }
     }
-    pub fn take_next_order(&mut self) -> Order {
-        self.order_list.remove(0)
-    }
-
     pub fn is_empty(&self) -> bool {
         return self.order_list.is_empty();
     }
@@ -106,6 +141,26 @@ impl PriceOrder {
             .position(|o| o.id == id)
             .map(|pos| self.order_list.remove(pos))
     }
+
+    /// Aggregate the orders of the price level.
+    pub fn depth_level(&self) -> DepthLevel {
+        DepthLevel {
+            price: self.price,
+            amount: self
+                .order_list
+                .iter()
+                .fold(Decimal::zero(), |total, order| total + order.amount),
+            order_count: self.order_list.len() as u32,
+        }
+    }
+}
+
+/// L2 view of a price level: total base amount and number of orders at this price.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub price: Decimal,
+    pub amount: Decimal,
+    pub order_count: u32,
 }
 
 impl Ord for PriceOrder {
@@ -151,6 +206,49 @@ impl UserOrders {
             panic!("Badge provided not declared call create_openorders to get one")
         })
     }
+
+    /// Read only view of the user vaults amount with its pending orders.
+    pub fn view(&self, orders: Vec<OpenOrder>) -> UserOrdersView {
+        UserOrdersView {
+            quote_amount: self.quote_vault.amount(),
+            locked_quote_amount: self.locked_quote_vault.amount(),
+            base_amount: self.base_vault.amount(),
+            locked_base_amount: self.locked_base_vault.amount(),
+            orders,
+        }
+    }
+}
+
+/// Pending order of a user with the remaining amount of base to trade.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
+pub struct OpenOrder {
+    pub id: u32,
+    pub side: Side,
+    pub price: Decimal,
+    pub remaining_amount: Decimal,
+    pub locked_amount: Decimal,
+}
+
+impl OpenOrder {
+    pub fn new(order: &Order, side: Side) -> Self {
+        OpenOrder {
+            id: order.id,
+            side,
+            price: order.price,
+            remaining_amount: order.amount,
+            locked_amount: order.locked_amount,
+        }
+    }
+}
+
+/// Return by the market to show the user assets and pending orders.
+#[derive(Debug, Clone, TypeId, Encode, Decode, Describe)]
+pub struct UserOrdersView {
+    pub quote_amount: Decimal,
+    pub locked_quote_amount: Decimal,
+    pub base_amount: Decimal,
+    pub locked_base_amount: Decimal,
+    pub orders: Vec<OpenOrder>,
 }
 #[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
 pub struct Order {
@@ -159,6 +257,7 @@ pub struct Order {
     pub price: Decimal,
     pub amount: Decimal, //amount in base to trade.
     pub locked_amount: Decimal,
+    pub fee_discount: Decimal, //percentage of the fee tier discount applied to the order fee.
 }
 
 impl Ord for Order {
@@ -179,11 +278,19 @@ pub enum Side {
     Ask,
 }
 
+/// Define how an order is matched and how long it stays in the book (time in force).
 #[derive(Debug, Clone, TypeId, Encode, Decode, Describe, PartialEq, Eq)]
 pub enum OrderType {
+    /// Good till cancelled: the remaining not matched is added to the book.
     Limit,
+    /// The remaining not matched is cancelled.
     ImmediateOrCancel,
+    /// The order is added to the book without being matched. Fail if it would match.
     PostOnly,
+    /// The order must be fully matched or the transaction fails.
+    FillOrKill,
+    /// No limit price, matched at the book prices until filled or the provided asset is used. Remaining is cancelled.
+    Market,
 }
 
 impl From<u8> for OrderType {
@@ -191,6 +298,8 @@ impl From<u8> for OrderType {
         match order_type {
             1 => OrderType::ImmediateOrCancel,
             2 => OrderType::PostOnly,
+            3 => OrderType::FillOrKill,
+            4 => OrderType::Market,
             _ => OrderType::Limit,
         }
     }
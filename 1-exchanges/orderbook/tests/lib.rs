@@ -1,6 +1,7 @@
 use radix_engine::engine::*;
 use radix_engine::ledger::*;
 use radix_engine::transaction::*;
+use sbor::*;
 use scrypto::prelude::*;
 
 fn create_account<'a, L: SubstateStore>(
@@ -81,7 +82,7 @@ fn create_market<'a, L: SubstateStore>(
     quote_token: Address,
     base_token: Address,
     key: EcdsaPublicKey,
-) -> Address {
+) -> (Address, Address, Address) {
     let receipt = executor
         .run(
             TransactionBuilder::new(executor)
@@ -101,7 +102,12 @@ fn create_market<'a, L: SubstateStore>(
                 .unwrap(),
         )
         .unwrap();
-    receipt.component(0).unwrap()
+    //resources are created in order: admin badge, badge minter, openorders badge, fee tier badge.
+    (
+        receipt.component(0).unwrap(),
+        receipt.resource_def(0).unwrap(),
+        receipt.resource_def(3).unwrap(),
+    )
 }
 
 fn transfer_token<'a, L: SubstateStore>(
@@ -138,9 +144,24 @@ struct Trader {
     base_token: Address,
 }
 
+//Market admin account holding the admin badge.
+struct Admin {
+    key: EcdsaPublicKey,
+    address: Address,
+    admin_badge: Address,
+    fee_tier_badge: Address,
+}
+
 fn init<'a, L: SubstateStore>(
     ledger: &'a mut L,
 ) -> (TransactionExecutor<'a, L>, Address, Vec<Trader>) {
+    let (executor, instance, traders, _) = init_with_admin(ledger);
+    (executor, instance, traders)
+}
+
+fn init_with_admin<'a, L: SubstateStore>(
+    ledger: &'a mut L,
+) -> (TransactionExecutor<'a, L>, Address, Vec<Trader>, Admin) {
     let mut executor = TransactionExecutor::new(ledger, false);
     let package = executor
         .publish_package(include_code!("orderbook"))
@@ -159,7 +180,7 @@ fn init<'a, L: SubstateStore>(
         market_hand.0,
     );
 
-    let instance = create_market(
+    let (instance, admin_badge, fee_tier_badge) = create_market(
         &mut executor,
         package,
         market_hand.1,
@@ -198,7 +219,13 @@ fn init<'a, L: SubstateStore>(
             }
         })
         .collect();
-    (executor, instance, traders)
+    let admin = Admin {
+        key: market_hand.0,
+        address: market_hand.1,
+        admin_badge,
+        fee_tier_badge,
+    };
+    (executor, instance, traders, admin)
 }
 
 fn create_trader_openorders<'a, L: radix_engine::ledger::SubstateStore>(
@@ -262,6 +289,39 @@ fn push_bid_order<'a, L: SubstateStore>(
     receipt.new_entities[0]
 }
 
+//Push a bid order that can fail. Return true if the transaction succeed.
+fn try_push_bid_order<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    instance: Address,
+    price: usize,
+    amount_base: usize,
+    amount_token: usize,
+    order_type: u8,
+    trader: &Trader,
+) -> bool {
+    let receipt = executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_method(
+                    instance,
+                    "bid_order",
+                    vec![
+                        format!("{}", price),
+                        format!("{}", amount_base),
+                        format!("{}", order_type),
+                        format!("{},{}", amount_token, trader.quote_token),
+                        format!("{},{}", 1, trader.access_badge_address),
+                    ],
+                    Some(trader.address),
+                )
+                .call_method_with_all_resources(trader.address, "deposit_batch")
+                .build(vec![trader.key])
+                .unwrap(),
+        )
+        .unwrap();
+    receipt.result.is_ok()
+}
+
 fn push_ask_order<'a, L: SubstateStore>(
     executor: &mut TransactionExecutor<'a, L>,
     instance: Address,
@@ -372,6 +432,88 @@ fn check_wallet<'a, L: SubstateStore>(
     );
 }
 
+//Call an admin method of the market, the returned assets go to the admin account.
+fn admin_call<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    instance: Address,
+    admin: &Admin,
+    method: &str,
+    mut args: Vec<String>,
+) {
+    args.push(format!("{},{}", 1, admin.admin_badge));
+    let receipt = executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_method(instance, method, args, Some(admin.address))
+                .call_method_with_all_resources(admin.address, "deposit_batch")
+                .build(vec![admin.key])
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(receipt.result.is_ok());
+}
+
+fn push_bid_order_with_fee_tier<'a, L: SubstateStore>(
+    executor: &mut TransactionExecutor<'a, L>,
+    instance: Address,
+    price: usize,
+    amount_base: usize,
+    amount_token: usize,
+    fee_tier_badge: Address,
+    trader: &Trader,
+) {
+    let receipt = executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_method(
+                    instance,
+                    "bid_order_with_fee_tier",
+                    vec![
+                        format!("{}", price),
+                        format!("{}", amount_base),
+                        format!("{}", 0),
+                        format!("{},{}", amount_token, trader.quote_token),
+                        format!("{},{}", 1, fee_tier_badge),
+                        format!("{},{}", 1, trader.access_badge_address),
+                    ],
+                    Some(trader.address),
+                )
+                .call_method_with_all_resources(trader.address, "deposit_batch")
+                .build(vec![trader.key])
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(receipt.result.is_ok());
+}
+
+//Call a read only method of the market and decode its result.
+fn call_view<'a, L: SubstateStore, T: Decode>(
+    executor: &mut TransactionExecutor<'a, L>,
+    instance: Address,
+    method: &str,
+    args: Vec<String>,
+    trader: &Trader,
+) -> T {
+    let receipt = executor
+        .run(
+            TransactionBuilder::new(executor)
+                .call_method(instance, method, args, Some(trader.address))
+                .build(vec![trader.key])
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(receipt.result.is_ok());
+    scrypto_decode(&receipt.outputs[0].raw).unwrap()
+}
+
+//Same layout as the market DepthLevel.
+#[derive(Debug, PartialEq, TypeId, Decode)]
+struct DepthLevel {
+    price: Decimal,
+    amount: Decimal,
+    order_count: u32,
+}
+
 #[test]
 fn test_trade_match_buy() {
     let mut ledger = InMemorySubstateStore::with_bootstrap();
@@ -594,3 +736,283 @@ fn test_cancel() {
         From::<u32>::from(99980),
     );
 }
+
+#[test]
+fn test_partial_fill_and_market() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut executor, instance, traders) = init(&mut ledger);
+    push_ask_order(&mut executor, instance, 20, 10, 0, &traders[1]);
+    //partial fill, the remaining 6 stay in the book.
+    push_bid_order(&mut executor, instance, 20, 4, 80, 0, &traders[0]);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99920),
+        From::<u32>::from(100000),
+    );
+    //market order limited by the provided quote: buy 3 base at 20.
+    push_bid_order(&mut executor, instance, 0, 100, 60, 4, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[1]);
+    let base_val: Decimal = From::<u32>::from(1000063);
+    let div: Decimal = From::<u32>::from(10);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99860),
+        base_val / div,
+    );
+    check_wallet(
+        &mut executor,
+        1,
+        &traders[1],
+        From::<u32>::from(100133),
+        From::<u32>::from(99990),
+    );
+
+    //immediate or cancel match the remaining 3 and unlock the quote of the not matched part.
+    push_bid_order(&mut executor, instance, 20, 5, 100, 1, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[1]);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99800),
+        From::<u32>::from(100009),
+    );
+    check_wallet(
+        &mut executor,
+        1,
+        &traders[1],
+        From::<u32>::from(100190),
+        From::<u32>::from(99990),
+    );
+}
+
+#[test]
+fn test_fill_or_kill_and_post_only() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut executor, instance, traders) = init(&mut ledger);
+    push_ask_order(&mut executor, instance, 20, 10, 0, &traders[1]);
+    //not enough ask to fill the order.
+    assert!(!try_push_bid_order(
+        &mut executor,
+        instance,
+        20,
+        20,
+        400,
+        3,
+        &traders[0]
+    ));
+    assert!(try_push_bid_order(
+        &mut executor,
+        instance,
+        20,
+        10,
+        200,
+        3,
+        &traders[0]
+    ));
+
+    push_ask_order(&mut executor, instance, 21, 10, 2, &traders[1]);
+    //post only bid would match the ask at 21.
+    assert!(!try_push_bid_order(
+        &mut executor,
+        instance,
+        21,
+        10,
+        210,
+        2,
+        &traders[0]
+    ));
+    assert!(try_push_bid_order(
+        &mut executor,
+        instance,
+        20,
+        10,
+        200,
+        2,
+        &traders[0]
+    ));
+
+    push_withdraw(&mut executor, instance, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[1]);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99600),
+        From::<u32>::from(100009),
+    );
+    check_wallet(
+        &mut executor,
+        1,
+        &traders[1],
+        From::<u32>::from(100190),
+        From::<u32>::from(99980),
+    );
+}
+
+#[test]
+fn test_book_views() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut executor, instance, traders) = init(&mut ledger);
+    let (best_bid, best_ask): (Option<Decimal>, Option<Decimal>) = call_view(
+        &mut executor,
+        instance,
+        "get_best_bid_ask",
+        vec![],
+        &traders[0],
+    );
+    assert_eq!((best_bid, best_ask), (None, None));
+    let spread: Option<Decimal> =
+        call_view(&mut executor, instance, "get_spread", vec![], &traders[0]);
+    assert_eq!(spread, None);
+
+    push_ask_order(&mut executor, instance, 20, 10, 0, &traders[1]);
+    push_ask_order(&mut executor, instance, 20, 5, 0, &traders[1]);
+    push_ask_order(&mut executor, instance, 22, 10, 0, &traders[1]);
+    push_bid_order(&mut executor, instance, 18, 4, 72, 0, &traders[0]);
+    push_bid_order(&mut executor, instance, 17, 6, 102, 0, &traders[0]);
+
+    let (best_bid, best_ask): (Option<Decimal>, Option<Decimal>) = call_view(
+        &mut executor,
+        instance,
+        "get_best_bid_ask",
+        vec![],
+        &traders[0],
+    );
+    assert_eq!((best_bid, best_ask), (Some(dec!("18")), Some(dec!("20"))));
+    let spread: Option<Decimal> =
+        call_view(&mut executor, instance, "get_spread", vec![], &traders[0]);
+    assert_eq!(spread, Some(dec!("2")));
+
+    //bids from the highest price, asks from the lowest, orders of a price are aggregated.
+    let (bids, asks): (Vec<DepthLevel>, Vec<DepthLevel>) = call_view(
+        &mut executor,
+        instance,
+        "get_depth",
+        vec!["2".to_string()],
+        &traders[0],
+    );
+    assert_eq!(
+        bids,
+        vec![
+            DepthLevel {
+                price: dec!("18"),
+                amount: dec!("4"),
+                order_count: 1
+            },
+            DepthLevel {
+                price: dec!("17"),
+                amount: dec!("6"),
+                order_count: 1
+            },
+        ]
+    );
+    assert_eq!(
+        asks,
+        vec![
+            DepthLevel {
+                price: dec!("20"),
+                amount: dec!("15"),
+                order_count: 2
+            },
+            DepthLevel {
+                price: dec!("22"),
+                amount: dec!("10"),
+                order_count: 1
+            },
+        ]
+    );
+    let (bids, asks): (Vec<DepthLevel>, Vec<DepthLevel>) = call_view(
+        &mut executor,
+        instance,
+        "get_depth",
+        vec!["1".to_string()],
+        &traders[0],
+    );
+    assert_eq!((bids.len(), asks.len()), (1, 1));
+}
+
+#[test]
+fn test_maker_rebate_and_fee_tier() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let (mut executor, instance, traders, admin) = init_with_admin(&mut ledger);
+    //2% maker rebate, 10% taker fee.
+    admin_call(
+        &mut executor,
+        instance,
+        &admin,
+        "set_fees",
+        vec!["-2".to_string(), "10".to_string()],
+    );
+
+    //taker pays 1 base of fee, the maker gets 0.2 base of rebate.
+    push_ask_order(&mut executor, instance, 20, 10, 0, &traders[1]);
+    push_bid_order(&mut executor, instance, 20, 10, 200, 0, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[1]);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99800),
+        From::<u32>::from(100009),
+    );
+    check_wallet(
+        &mut executor,
+        1,
+        &traders[1],
+        From::<u32>::from(100200),
+        dec!("99990.2"),
+    );
+
+    //the collected fee are withdrawn and the taker has a 100% discount:
+    //its fee is kept at the maker rebate which is still fully paid.
+    admin_call(&mut executor, instance, &admin, "withdraw_fees", vec![]);
+    admin_call(
+        &mut executor,
+        instance,
+        &admin,
+        "mint_fee_tier_badge",
+        vec!["100".to_string()],
+    );
+    transfer_token(
+        &mut executor,
+        admin.address,
+        traders[0].address,
+        Decimal::one(),
+        admin.fee_tier_badge,
+        admin.key,
+    );
+    push_ask_order(&mut executor, instance, 20, 10, 0, &traders[1]);
+    push_bid_order_with_fee_tier(
+        &mut executor,
+        instance,
+        20,
+        10,
+        200,
+        admin.fee_tier_badge,
+        &traders[0],
+    );
+    push_withdraw(&mut executor, instance, &traders[0]);
+    push_withdraw(&mut executor, instance, &traders[1]);
+    check_wallet(
+        &mut executor,
+        0,
+        &traders[0],
+        From::<u32>::from(99600),
+        dec!("100018.8"),
+    );
+    check_wallet(
+        &mut executor,
+        1,
+        &traders[1],
+        From::<u32>::from(100400),
+        dec!("99980.4"),
+    );
+}
@@ -22,6 +22,9 @@ blueprint! {
 	//Member badge for each member of this orderbook
         member_badge_bag: Vault,
         member_auth_badge: ResourceDef,
+
+	//Member badges having placed each order : HashMap< (Add1, Add2, [Nbr of Add1 tokens, Ratio of Add2 tokens], Address of the order maker), Member badge keys in order>
+        order_owners: HashMap<(Address, Address, [Decimal; 2], Address), Vec<NonFungibleKey>>,
     }
 
     impl OrderBook {
@@ -46,6 +49,7 @@ blueprint! {
 
                 member_badge_bag: Vault::with_bucket(member_badge_bag),
                 member_auth_badge: member_auth_badge,
+                order_owners: HashMap::new(),
             }
             .instantiate();
 
@@ -88,17 +92,27 @@ blueprint! {
             info!("{:?}",self.orderbook.get(&(input, ouput)));
         }
 
+        //Returns all orders of the pair: (input,output), empty if the pair doesn't exist
+        pub fn get_orders(&self, input: Address, ouput: Address) -> Vec<([Decimal; 2], Address)> {
+            match self.orderbook.get(&(input, ouput)) {
+                Some(orders) => orders.clone(),
+                None => vec![]
+            }
+        }
+
 	    /* --- Administrate the orderbook --- */
         //Destroy all orders present for the pair (input, output)
         #[auth(admin_auth_badge)]
         pub fn reset_pair_orderbook(&mut self, input: Address, ouput: Address) {
             self.orderbook.insert((input, ouput), vec![([Decimal::from(0),Decimal::from(0)], input)]);
+            self.order_owners.retain(|order, _| (order.0, order.1) != (input, ouput));
         }
 
         //Initialize the pair (input, output)
         #[auth(admin_auth_badge)]
         pub fn init_pair_orderbook(&mut self, input: Address, ouput: Address) {
             self.orderbook.insert((input, ouput), vec![]);
+            self.order_owners.retain(|order, _| (order.0, order.1) != (input, ouput));
             self.vault.insert(input, Vault::new(ResourceDef::from(input)));
             info!("Order book correctly initialized!");
         }
@@ -150,11 +164,38 @@ blueprint! {
             return (payment, Bucket::new(ResourceDef::from(input)));
         }
 
+        //Cancel an order of this pair : (input,output), its tokens are sent back to the user_address who placed it. Only the member who placed the order can cancel it
+        #[auth(member_auth_badge)]
+        pub fn cancel_order(&mut self, input: Address ,ouput: Address, amount: Decimal, xrd_price: Decimal, user_address: Address) {
+            let orders = self.orderbook.get_mut(&(input, ouput)).unwrap();
+            let index = orders.iter().position(|order| order.0 == [amount, xrd_price] && order.1 == user_address);
+            match index {
+                Some(i) => {
+                    let owners = self.order_owners.get_mut(&(input, ouput, [amount, xrd_price], user_address));
+                    let member_keys = auth.get_non_fungible_keys();
+                    let owner = owners.as_ref().and_then(|owners| owners.iter().position(|owner| member_keys.contains(owner)));
+                    match owner {
+                        Some(j) => { owners.unwrap().remove(j); }
+                        None => panic!("Only the member who placed the order can cancel it!")
+                    }
+                    orders.remove(i);
+                    let refund = self.vault.get_mut(&input).unwrap().take(amount);
+                    let args = vec![scrypto_encode(&refund)];
+                    let _rtn = call_method(user_address, "deposit", args);
+                    info!("Order cancelled, funds sent back!");
+                }
+                None => info!("No order like this! ")
+            }
+        }
+
         //Allows you to add a given order in the book of this pair
         #[auth(member_auth_badge)]
         pub fn update_register_orderbook(&mut self, input: Address, ouput: Address, amount: Decimal, xrd_price: Decimal, user_address: Address) {
             let orders = self.orderbook.get_mut(&(input, ouput)).unwrap();
             orders.push(([amount, xrd_price], user_address));
+            self.order_owners.entry((input, ouput, [amount, xrd_price], user_address))
+                .or_insert_with(Vec::new)
+                .push(auth.get_non_fungible_keys().remove(0));
             info!("New order in the book, All orders : {:?} ", orders);
         }
 
@@ -164,7 +205,12 @@ blueprint! {
             let orders = self.orderbook.get_mut(&(input, ouput)).unwrap();
             for (i, _v) in (0..(orders.len())).enumerate() {
                 if vec![amount, xrd_price]==orders[i].0 {
-                    orders.remove(i);
+                    let (_, user_address) = orders.remove(i);
+                    if let Some(owners) = self.order_owners.get_mut(&(input, ouput, [amount, xrd_price], user_address)) {
+                        if !owners.is_empty() {
+                            owners.remove(0);
+                        }
+                    }
                     break;
                 }
             }
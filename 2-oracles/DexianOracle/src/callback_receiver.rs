@@ -0,0 +1,25 @@
+use scrypto::prelude::*;
+
+blueprint! {
+    /// receives the price of a callback, used by the tests
+    struct CallbackReceiver {
+        /// last (price, epoch_at) received
+        price: Option<(String, u64)>
+    }
+
+    impl CallbackReceiver {
+        pub fn new() -> ComponentAddress {
+            Self {
+                price: None
+            }.instantiate().globalize()
+        }
+
+        pub fn on_price(&mut self, price: String, epoch_at: u64, _args: Vec<Vec<u8>>) {
+            self.price = Some((price, epoch_at));
+        }
+
+        pub fn get_price(&self) -> Option<(String, u64)> {
+            self.price.clone()
+        }
+    }
+}
@@ -1,23 +1,46 @@
 use scrypto::prelude::*;
 
+mod callback_receiver;
+
 blueprint! {
     struct DeXianOracle {
          /// This is just a reular admin badge, for register/remove DataProvider
          admin_badge: ResourceAddress,
         
-         // /// DataProvider badge ResourceDef
-         // dataprovider_badge_def: ResourceDef,
+         /// DataProvider badge resource
+         dataprovider_badge: ResourceAddress,
          // minter
          callback_minter: Vault,
         
-         // /// DataProvider badge ResourceDef
-         // dataprovider_badge_def: ResourceDef,
+         /// registered DataProviders, a removed DataProvider can't feed with its badge anymore
+         dataproviders: HashSet<NonFungibleId>,
+
+         /// latest (price, epoch_at) fed by each DataProvider, by pair
+         submissions: HashMap<String, HashMap<NonFungibleId, (Decimal, u64)>>,
+
+         /// only the submissions of the last `window_epochs` epochs are aggregated
+         window_epochs: u64,
+
+         /// min count of submissions in the window to update the price
+         min_submissions: u32,
+
+         /// max deviation of a submission from the median of the window, e.g. 0.1 for 10%
+         max_deviation: Decimal,
          
-         /// callbacks
-         callback_vaults: Vault,
+         /// callback resource, the callback NFT is the request of the requester
+         callback_resource: ResourceAddress,
 
-         /// callback that have not yet been triggered 
+         /// callback that have not yet been triggered, in request order
          unfilful_vec: Vec<NonFungibleId>,
+
+         /// callback that failed, the requester can cancel them and reclaim the fee
+         failed_vec: Vec<NonFungibleId>,
+
+         /// fees of the callbacks not yet triggered
+         pending_fees: Vault,
+
+         /// an unfulfilled callback can be canceled `request_timeout` epochs after the request
+         request_timeout: u64,
  
          /// fee
          fee: Decimal,
@@ -43,39 +66,127 @@ blueprint! {
                 .divisibility(DIVISIBILITY_NONE)
                 .initial_supply(Decimal::ONE);
             
-            let callback_bucket = ResourceBuilder::new_non_fungible()
+            let dataprovider_badge = ResourceBuilder::new_non_fungible()
+                .metadata("name", "DeXianOracle DataProvider Badge").metadata("symbol", "DXDP")
+                .mintable(rule!(require(minter_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(minter_badge.resource_address())), LOCKED)
+                .no_initial_supply();
+
+            let callback_resource = ResourceBuilder::new_non_fungible()
                 .metadata("name", "DeXianOracle Callback").metadata("symbol", "DXCB")
-                .mintable(rule!(require(admin_badge.resource_address())), LOCKED)
-                .burnable(rule!(require(admin_badge.resource_address())), LOCKED)
+                .mintable(rule!(require(minter_badge.resource_address())), LOCKED)
+                .burnable(rule!(require(minter_badge.resource_address())), LOCKED)
                 .no_initial_supply();
             
             let component = Self {
                 admin_badge: admin_badge.resource_address(),
+                dataprovider_badge,
+                dataproviders: HashSet::new(),
+                submissions: HashMap::new(),
+                window_epochs: 10,
+                min_submissions: 3,
+                max_deviation: dec!("0.1"),
                 price_map: HashMap::new(),
                 vault: Vault::new(RADIX_TOKEN),
-                callback_vaults: Vault::new(callback_bucket),
+                callback_resource,
                 unfilful_vec: Vec::new(),
+                failed_vec: Vec::new(),
+                pending_fees: Vault::new(RADIX_TOKEN),
+                request_timeout: 100,
                 callback_minter: Vault::with_bucket(minter_badge),
                 fee
             }.instantiate();
 
             let access_rules = AccessRules::new()
-                .method("feed_price", rule!(require(admin_badge.resource_address())))
+                .method("feed_price", rule!(allow_all))
                 .method("withdraw_fee", rule!(require(admin_badge.resource_address())))
                 .method("request_price", rule!(allow_all))
-                .method("get_price", rule!(allow_all));
-                // .method("register_dataprovider", rule(!require(admin_badge.resource_address())))
-                // .method("remove_dataprovider", rule(!require(admin_badge.resource_address())));
+                .method("process_callbacks", rule!(allow_all))
+                .method("cancel_request", rule!(allow_all))
+                .method("expire_callback", rule!(allow_all))
+                .method("get_unfulfilled_callbacks", rule!(allow_all))
+                .method("get_failed_callbacks", rule!(allow_all))
+                .method("record_failed_callback", rule!(require(admin_badge.resource_address())))
+                .method("set_request_timeout", rule!(require(admin_badge.resource_address())))
+                .method("get_price", rule!(allow_all))
+                .method("get_price_checked", rule!(allow_all))
+                .method("register_dataprovider", rule!(require(admin_badge.resource_address())))
+                .method("remove_dataprovider", rule!(require(admin_badge.resource_address())))
+                .method("set_aggregation", rule!(require(admin_badge.resource_address())));
 
             (component.add_access_check(access_rules).globalize(), admin_badge)
 
         }
 
-        pub fn feed_price(&mut self, pair: String, price: String) -> bool {
+        /// mint the badge of a new DataProvider
+        pub fn register_dataprovider(&mut self, name: String) -> Bucket {
+            let id = NonFungibleId::random();
+            let badge = self.callback_minter.authorize(|| {
+                let rm = borrow_resource_manager!(self.dataprovider_badge);
+                rm.mint_non_fungible(&id, DataProvider { name })
+            });
+            self.dataproviders.insert(id);
+            badge
+        }
+
+        /// the badge of a removed DataProvider can't feed anymore, its submissions are dropped
+        pub fn remove_dataprovider(&mut self, id: NonFungibleId) {
+            assert!(self.dataproviders.remove(&id), "the dataprovider not exists!");
+            for pair_submissions in self.submissions.values_mut() {
+                pair_submissions.remove(&id);
+            }
+        }
+
+        pub fn set_aggregation(&mut self, window_epochs: u64, min_submissions: u32, max_deviation: Decimal) {
+            assert!(window_epochs > 0, "invalid window value.");
+            assert!(min_submissions > 0, "invalid min submissions value.");
+            assert!(max_deviation > Decimal::zero(), "invalid max deviation value.");
+            self.window_epochs = window_epochs;
+            self.min_submissions = min_submissions;
+            self.max_deviation = max_deviation;
+        }
+
+        /// submit the price of a DataProvider, the price is updated to the median of the submissions
+        /// in the window once there are `min_submissions` of them, not counting the outliers.
+        /// return whether the price is updated
+        pub fn feed_price(&mut self, dataprovider: Proof, pair: String, price: String) -> bool {
+            assert_eq!(dataprovider.resource_address(), self.dataprovider_badge, "invalid dataprovider badge!");
+            assert_eq!(dataprovider.amount(), Decimal::ONE, "use only one dataprovider badge at a time!");
+            let id = dataprovider.non_fungible::<DataProvider>().id();
+            assert!(self.dataproviders.contains(&id), "the dataprovider was removed!");
+
             let epoch_at = Runtime::current_epoch();
             let dec_price = Decimal::from(price.clone());
-            self.price_map.insert(pair.clone(), (dec_price, epoch_at));
-            self.filfull_request(&pair, price.clone(), epoch_at);
+            assert!(dec_price > Decimal::zero(), "invalid price value.");
+
+            let window_epochs = self.window_epochs;
+            let pair_submissions = self.submissions.entry(pair.clone()).or_insert_with(HashMap::new);
+            pair_submissions.insert(id, (dec_price, epoch_at));
+
+            let mut prices: Vec<Decimal> = pair_submissions.values()
+                .filter(|(_, submitted_at)| epoch_at - submitted_at < window_epochs)
+                .map(|(submitted, _)| *submitted)
+                .collect();
+            if (prices.len() as u32) < self.min_submissions {
+                return false;
+            }
+
+            // drop the outliers against the median of the window, a real move is followed once most
+            // of the DataProviders submitted it
+            let window_median = median(&mut prices);
+            let max_deviation = self.max_deviation;
+            let mut prices: Vec<Decimal> = prices.into_iter()
+                .filter(|submitted| {
+                    let deviation = if *submitted > window_median { *submitted - window_median } else { window_median - *submitted };
+                    deviation / window_median <= max_deviation
+                })
+                .collect();
+            if (prices.len() as u32) < self.min_submissions {
+                return false;
+            }
+
+            // the callbacks are triggered by `process_callbacks`
+            self.price_map.insert(pair, (median(&mut prices), epoch_at));
             true
         }
 
@@ -84,47 +195,134 @@ blueprint! {
             *self.price_map.get(&pair).unwrap()
         }
 
-        pub fn request_price(&mut self,  fee: Bucket,  pair: String, component: ComponentAddress, 
-            method: String, arg: String) -> NonFungibleId {
+        /// same as `get_price`, panics if the price is older than `max_age_epochs`
+        pub fn get_price_checked(&self, pair: String, max_age_epochs: u64) -> (Decimal, u64) {
+            let (price, epoch_at) = self.get_price(pair);
+            assert!(Runtime::current_epoch() - epoch_at <= max_age_epochs, "the price is stale!");
+            (price, epoch_at)
+        }
+
+        /// request a price for a callback, the callback is triggered by `process_callbacks` with a price fed
+        /// from the epoch of the request on.
+        /// return the callback NFT, to cancel the request, and the change of the fee
+        pub fn request_price(&mut self, mut fee: Bucket, pair: String, component: ComponentAddress,
+            method: String, arg: String) -> (Bucket, Bucket) {
             assert!(fee.amount() >= self.fee, "Fees are lower than required!");
-            self.vault.put(fee);
+            self.pending_fees.put(fee.take(self.fee));
 
             let callback_id = NonFungibleId::random();
-            let callback_data = CallbackData::new_instance(callback_id.clone(), component, method, pair, args!(arg));
+            let callback_data = CallbackData::new_instance(callback_id.clone(), component, method, pair, args!(arg),
+                self.fee, Runtime::current_epoch());
 
             let callback = self.callback_minter.authorize(|| {
-                let rm = borrow_resource_manager!(self.callback_vaults.resource_address());
+                let rm = borrow_resource_manager!(self.callback_resource);
                 rm.mint_non_fungible(&callback_id, callback_data)
             });
-            // Store the Callback NFR inside this component
-            self.callback_vaults.put(callback);
-            self.unfilful_vec.push(callback_id.clone());
-            callback_id
+            self.unfilful_vec.push(callback_id);
+            (callback, fee)
         }
 
-        pub fn withdraw_fee(&mut self, amount: Decimal) -> Bucket{
-            assert!(self.vault.amount () < amount, "balance insufficient!");
+        /// trigger at most `max` callbacks whose price has been fed, in request order.
+        /// a failing callback aborts the whole transaction, so the keeper passes the ids of the failing ones in `skip`,
+        /// they stay unfulfilled until they are recorded as failed.
+        /// the fee of each triggered callback is earned.
+        /// return the count of triggered callbacks
+        pub fn process_callbacks(&mut self, max: u32, skip: Vec<NonFungibleId>) -> u32 {
+            let rm = borrow_resource_manager!(self.callback_resource);
+            let mut count: u32 = 0;
+            let mut unfilful_vec = Vec::new();
+            for id in std::mem::take(&mut self.unfilful_vec) {
+                if count == max || skip.contains(&id) {
+                    unfilful_vec.push(id);
+                    continue;
+                }
+                let callback_data = rm.get_non_fungible_data::<CallbackData>(&id);
+                match self.price_map.get(&callback_data.pair) {
+                    Some((price, epoch_at)) if *epoch_at >= callback_data.requested_at => {
+                        callback_data.call(&price.to_string(), *epoch_at);
+                        self.vault.put(self.pending_fees.take(callback_data.fee));
+                        count += 1;
+                    }
+                    _ => unfilful_vec.push(id),
+                }
+            }
+            self.unfilful_vec = unfilful_vec;
+            count
+        }
 
-            self.vault.take(amount)
+        /// the admin records a failing callback as failed, it's not processed anymore.
+        pub fn record_failed_callback(&mut self, id: NonFungibleId) {
+            let index = self.unfilful_vec.iter().position(|unfilful| *unfilful == id)
+                .expect("the callback is not unfulfilled!");
+            self.failed_vec.push(self.unfilful_vec.remove(index));
         }
 
-        fn filfull_request(&mut self, pair: &String, price: String, epoch_at: u64) {
-            let mut i = 0;
-            while i < self.unfilful_vec.len() {
-                if self.callback_vaults.non_fungible_ids().contains(&self.unfilful_vec[i]){
-                    let callback = self.callback_vaults.take_non_fungible(&self.unfilful_vec[i]);
-                    let callback_data = callback.non_fungible::<CallbackData>().data();
-                    if callback_data.pair.eq(pair) {
-                        callback_data.call(&price, epoch_at);
-                        self.unfilful_vec.remove(i);
-                    }
-                    i += 1;
-                }                
+        /// anybody records an unfulfilled callback as failed once the request timed out.
+        pub fn expire_callback(&mut self, id: NonFungibleId) {
+            let index = self.unfilful_vec.iter().position(|unfilful| *unfilful == id)
+                .expect("the callback is not unfulfilled!");
+            let rm = borrow_resource_manager!(self.callback_resource);
+            let callback_data = rm.get_non_fungible_data::<CallbackData>(&id);
+            assert!(Runtime::current_epoch() >= callback_data.requested_at + self.request_timeout,
+                "the request has not timed out yet!");
+            self.failed_vec.push(self.unfilful_vec.remove(index));
+        }
+
+        pub fn get_unfulfilled_callbacks(&self) -> Vec<NonFungibleId> {
+            self.unfilful_vec.clone()
+        }
+
+        pub fn get_failed_callbacks(&self) -> Vec<NonFungibleId> {
+            self.failed_vec.clone()
+        }
+
+        pub fn set_request_timeout(&mut self, request_timeout: u64) {
+            self.request_timeout = request_timeout;
+        }
+
+        /// cancel a failed callback, or an unfulfilled one after the request timeout, and reclaim its fee
+        pub fn cancel_request(&mut self, callback: Bucket) -> Bucket {
+            assert_eq!(callback.resource_address(), self.callback_resource, "invalid callback!");
+            assert_eq!(callback.amount(), Decimal::ONE, "cancel one callback at a time!");
+            let callback_data = callback.non_fungible::<CallbackData>().data();
+
+            if let Some(index) = self.failed_vec.iter().position(|failed| *failed == callback_data.id) {
+                self.failed_vec.remove(index);
+            } else {
+                let index = self.unfilful_vec.iter().position(|unfilful| *unfilful == callback_data.id)
+                    .expect("the callback was fulfilled already!");
+                assert!(Runtime::current_epoch() >= callback_data.requested_at + self.request_timeout,
+                    "the request has not timed out yet!");
+                self.unfilful_vec.remove(index);
             }
+
+            self.callback_minter.authorize(|| callback.burn());
+            self.pending_fees.take(callback_data.fee)
         }
+
+        pub fn withdraw_fee(&mut self, amount: Decimal) -> Bucket{
+            assert!(self.vault.amount() >= amount, "balance insufficient!");
+
+            self.vault.take(amount)
+        }
+    }
+}
+
+fn median(prices: &mut Vec<Decimal>) -> Decimal {
+    prices.sort();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 0 {
+        (prices[mid - 1] + prices[mid]) / dec!("2")
+    } else {
+        prices[mid]
     }
 }
 
+#[derive(NonFungibleData)]
+pub struct DataProvider {
+    pub name: String,
+}
+
 #[derive(NonFungibleData)]
 pub struct CallbackData {
     /// request id
@@ -140,12 +338,18 @@ pub struct CallbackData {
 
     /// The args that should be passed to the target method
     pub args: Vec<Vec<u8>>,
+
+    /// fee paid for the request, refunded when canceled
+    pub fee: Decimal,
+
+    /// epoch of the request
+    pub requested_at: u64,
 }
 
 impl CallbackData {
     
     pub fn new_instance(id: NonFungibleId, component: ComponentAddress,
-        method: String, pair: String, args: Vec<Vec<u8>>
+        method: String, pair: String, args: Vec<Vec<u8>>, fee: Decimal, requested_at: u64
         ) -> Self {
         
         Self {
@@ -153,7 +357,9 @@ impl CallbackData {
             pair,
             id,
             component,
-            method
+            method,
+            fee,
+            requested_at
         }
     }
 
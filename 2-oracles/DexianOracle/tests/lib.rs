@@ -1,4 +1,5 @@
 use radix_engine::ledger::*;
+use radix_engine::model::Receipt;
 use radix_engine::transaction::*;
 use scrypto::prelude::*;
 
@@ -30,3 +31,282 @@ fn test_hello() {
     println!("{:?}\n", receipt2);
     assert!(receipt2.result.is_ok());
 }
+
+type Signer = (EcdsaPublicKey, EcdsaPrivateKey, ComponentAddress);
+
+/// run the instructions signed by the account and deposit the resources left in it
+fn execute<L, F>(
+    executor: &mut TransactionExecutor<L>,
+    (pk, sk, account): (&EcdsaPublicKey, &EcdsaPrivateKey, ComponentAddress),
+    instructions: F,
+) -> Receipt
+where
+    L: SubstateStore,
+    F: FnOnce(&mut TransactionBuilder) -> &mut TransactionBuilder,
+{
+    let mut builder = TransactionBuilder::new();
+    instructions(&mut builder);
+    let transaction = builder
+        .call_method_with_all_resources(account, "deposit_batch")
+        .build(executor.get_nonce([*pk]))
+        .sign([sk]);
+    executor.validate_and_execute(&transaction).unwrap()
+}
+
+fn feed_instructions<'b>(
+    builder: &'b mut TransactionBuilder,
+    oracle: ComponentAddress,
+    account: ComponentAddress,
+    dataprovider_badge: ResourceAddress,
+    pair: &str,
+    price: &str,
+) -> &'b mut TransactionBuilder {
+    builder
+        .call_method(account, "create_proof", args![dataprovider_badge])
+        .pop_from_auth_zone(|builder, proof_id| {
+            builder.call_method(
+                oracle,
+                "feed_price",
+                args![Proof(proof_id), pair.to_string(), price.to_string()],
+            )
+        })
+}
+
+struct OracleEnv<'a, L: SubstateStore> {
+    executor: TransactionExecutor<'a, L>,
+    pk: EcdsaPublicKey,
+    sk: EcdsaPrivateKey,
+    account: ComponentAddress,
+    oracle: ComponentAddress,
+    receiver: ComponentAddress,
+    admin_badge: ResourceAddress,
+    dataprovider_badge: ResourceAddress,
+}
+
+impl<'a, L: SubstateStore> OracleEnv<'a, L> {
+    fn new(ledger: &'a mut L) -> Self {
+        let mut executor = TransactionExecutor::new(ledger, false);
+        let (pk, sk, account) = executor.new_account();
+        let package = executor.publish_package(compile_package!()).unwrap();
+
+        let transaction = TransactionBuilder::new()
+            .call_function(package, "DeXianOracle", "new", args![dec!("1")])
+            .call_function(package, "CallbackReceiver", "new", args![])
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(executor.get_nonce([pk]))
+            .sign([&sk]);
+        let receipt = executor.validate_and_execute(&transaction).unwrap();
+        assert!(receipt.result.is_ok());
+
+        let mut env = Self {
+            executor,
+            pk,
+            sk,
+            account,
+            oracle: receipt.new_component_addresses[0],
+            receiver: receipt.new_component_addresses[1],
+            admin_badge: receipt.new_resource_addresses[0],
+            dataprovider_badge: receipt.new_resource_addresses[2],
+        };
+
+        let receipt = env.as_admin("register_dataprovider", args!["provider".to_string()]);
+        assert!(receipt.result.is_ok());
+        let receipt = env.as_admin("set_aggregation", args![10u64, 1u32, dec!("0.1")]);
+        assert!(receipt.result.is_ok());
+        env
+    }
+
+    /// run the instructions and deposit the resources left in the account
+    fn run<F>(&mut self, instructions: F) -> Receipt
+    where
+        F: FnOnce(&mut TransactionBuilder) -> &mut TransactionBuilder,
+    {
+        execute(
+            &mut self.executor,
+            (&self.pk, &self.sk, self.account),
+            instructions,
+        )
+    }
+
+    /// same as `run`, signed by another account
+    fn run_as<F>(&mut self, signer: &Signer, instructions: F) -> Receipt
+    where
+        F: FnOnce(&mut TransactionBuilder) -> &mut TransactionBuilder,
+    {
+        let (pk, sk, account) = signer;
+        execute(&mut self.executor, (pk, sk, *account), instructions)
+    }
+
+    fn call(&mut self, method: &str, args: Vec<Vec<u8>>) -> Receipt {
+        let oracle = self.oracle;
+        self.run(|builder| builder.call_method(oracle, method, args))
+    }
+
+    fn as_admin(&mut self, method: &str, args: Vec<Vec<u8>>) -> Receipt {
+        let (oracle, account, admin_badge) = (self.oracle, self.account, self.admin_badge);
+        self.run(|builder| {
+            builder
+                .call_method(account, "create_proof", args![admin_badge])
+                .call_method(oracle, method, args)
+        })
+    }
+
+    /// register a DataProvider whose badge is held by a new account
+    fn add_dataprovider(&mut self) -> Signer {
+        let (pk, sk, account) = self.executor.new_account();
+        let (oracle, admin_badge) = (self.oracle, self.admin_badge);
+        let transaction = TransactionBuilder::new()
+            .call_method(self.account, "create_proof", args![admin_badge])
+            .call_method(
+                oracle,
+                "register_dataprovider",
+                args!["provider".to_string()],
+            )
+            .call_method_with_all_resources(account, "deposit_batch")
+            .build(self.executor.get_nonce([self.pk]))
+            .sign([&self.sk]);
+        let receipt = self.executor.validate_and_execute(&transaction).unwrap();
+        assert!(receipt.result.is_ok());
+        (pk, sk, account)
+    }
+
+    fn feed_price(&mut self, pair: &str, price: &str) -> Receipt {
+        let (oracle, account, dataprovider_badge) =
+            (self.oracle, self.account, self.dataprovider_badge);
+        self.run(|builder| {
+            feed_instructions(builder, oracle, account, dataprovider_badge, pair, price)
+        })
+    }
+
+    fn feed_price_as(&mut self, dataprovider: &Signer, pair: &str, price: &str) -> Receipt {
+        let (oracle, account, dataprovider_badge) =
+            (self.oracle, dataprovider.2, self.dataprovider_badge);
+        self.run_as(dataprovider, |builder| {
+            feed_instructions(builder, oracle, account, dataprovider_badge, pair, price)
+        })
+    }
+
+    fn request_price(&mut self, pair: &str, component: ComponentAddress, method: &str) -> Receipt {
+        let (oracle, account) = (self.oracle, self.account);
+        self.run(|builder| {
+            builder
+                .call_method(account, "withdraw_by_amount", args![dec!("1"), RADIX_TOKEN])
+                .take_from_worktop(RADIX_TOKEN, |builder, bucket_id| {
+                    builder.call_method(
+                        oracle,
+                        "request_price",
+                        args![
+                            Bucket(bucket_id),
+                            pair.to_string(),
+                            component,
+                            method.to_string(),
+                            "arg".to_string()
+                        ],
+                    )
+                })
+        })
+    }
+
+    fn unfulfilled_callbacks(&mut self) -> Vec<NonFungibleId> {
+        let receipt = self.call("get_unfulfilled_callbacks", args![]);
+        assert!(receipt.result.is_ok());
+        scrypto_decode(&receipt.outputs[0].raw).unwrap()
+    }
+}
+
+#[test]
+fn test_skip_and_expire_failing_callback() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = OracleEnv::new(&mut ledger);
+    let (account, receiver) = (env.account, env.receiver);
+
+    // the account has no such method, this callback always fails
+    assert!(env
+        .request_price("XRD/USD", account, "no_such_method")
+        .result
+        .is_ok());
+    assert!(env
+        .request_price("XRD/USD", receiver, "on_price")
+        .result
+        .is_ok());
+    assert!(env.feed_price("XRD/USD", "0.05").result.is_ok());
+
+    let callbacks = env.unfulfilled_callbacks();
+    assert_eq!(callbacks.len(), 2);
+    let failing = callbacks[0].clone();
+
+    // the failing callback at the head blocks the others unless it's skipped
+    let no_skip: Vec<NonFungibleId> = Vec::new();
+    assert!(env
+        .call("process_callbacks", args![10u32, no_skip])
+        .result
+        .is_err());
+    assert!(env
+        .call("process_callbacks", args![10u32, vec![failing.clone()]])
+        .result
+        .is_ok());
+    assert_eq!(env.unfulfilled_callbacks(), vec![failing.clone()]);
+
+    let receipt = env.run(|builder| builder.call_method(receiver, "get_price", args![]));
+    let received: Option<(String, u64)> = scrypto_decode(&receipt.outputs[0].raw).unwrap();
+    assert_eq!(received, Some(("0.05".to_string(), 0)));
+
+    // anybody records it as failed once the request timed out
+    assert!(env
+        .call("expire_callback", args![failing.clone()])
+        .result
+        .is_err());
+    assert!(env
+        .as_admin("set_request_timeout", args![0u64])
+        .result
+        .is_ok());
+    assert!(env
+        .call("expire_callback", args![failing.clone()])
+        .result
+        .is_ok());
+    assert!(env.unfulfilled_callbacks().is_empty());
+
+    let receipt = env.call("get_failed_callbacks", args![]);
+    let failed: Vec<NonFungibleId> = scrypto_decode(&receipt.outputs[0].raw).unwrap();
+    assert_eq!(failed, vec![failing]);
+}
+
+#[test]
+fn test_quorum_and_price_move() {
+    let mut ledger = InMemorySubstateStore::with_bootstrap();
+    let mut env = OracleEnv::new(&mut ledger);
+    assert!(env
+        .as_admin("set_aggregation", args![10u64, 3u32, dec!("0.1")])
+        .result
+        .is_ok());
+    let first = env.add_dataprovider();
+    let second = env.add_dataprovider();
+    let third = env.add_dataprovider();
+
+    // outputs: create_proof, pop_from_auth_zone, feed_price
+    fn feed<L: SubstateStore>(env: &mut OracleEnv<L>, dataprovider: &Signer, price: &str) -> bool {
+        let receipt = env.feed_price_as(dataprovider, "XRD/USD", price);
+        assert!(receipt.result.is_ok());
+        scrypto_decode(&receipt.outputs[2].raw).unwrap()
+    }
+    fn price<L: SubstateStore>(env: &mut OracleEnv<L>) -> Decimal {
+        let receipt = env.call("get_price", args!["XRD/USD".to_string()]);
+        let (price, _): (Decimal, u64) = scrypto_decode(&receipt.outputs[0].raw).unwrap();
+        price
+    }
+
+    // no single DataProvider sets the price
+    assert!(!feed(&mut env, &first, "1"));
+    assert!(!feed(&mut env, &second, "1"));
+    assert!(feed(&mut env, &third, "1.02"));
+    assert_eq!(price(&mut env), dec!("1"));
+
+    // an outlier is recorded but left out of the price
+    assert!(!feed(&mut env, &first, "1.5"));
+    assert_eq!(price(&mut env), dec!("1"));
+
+    // a real move is followed once the quorum submitted it
+    assert!(!feed(&mut env, &second, "1.5"));
+    assert!(feed(&mut env, &third, "1.48"));
+    assert_eq!(price(&mut env), dec!("1.5"));
+}
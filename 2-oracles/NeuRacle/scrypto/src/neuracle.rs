@@ -13,6 +13,7 @@ use scrypto::prelude::*;
 use crate::neura_stable_coin::NStableCoin;
 use crate::validator::Validator;
 use crate::utilities::*;
+use std::str::FromStr;
 
 #[derive(NonFungibleData)]
 pub struct ValidatorData {
@@ -25,6 +26,15 @@ pub struct ValidatorData {
 
 }
 
+/// How the datas of a data source are validated.
+#[derive(TypeId, Encode, Decode, Describe, Clone, Copy, PartialEq, Debug)]
+pub enum FeedType {
+    /// Numeric value (eg: price), validated by the stake-weighted median of validators datas.
+    Numeric,
+    /// Any other value, validated by >2/3 staked weight of validators providing the same data.
+    Text
+}
+
 #[derive(NonFungibleData)]
 pub struct UserData {
     #[scrypto(mutable)]
@@ -40,6 +50,8 @@ blueprint! {
 
         /// Store NeuRacle validated datas.
         datas: BTreeMap<String, String>,
+        /// Store the feed type of each data source.
+        feed_types: HashMap<String, FeedType>,
         /// Store L2 stable coins project and it's name
         stable_coins: LazyMap<ComponentAddress, String>,
         /// Store NeuRacle Validator Addresses and their vote weight (staked amount).
@@ -66,10 +78,22 @@ blueprint! {
         reward_rate: Decimal, 
         /// Untruthful validator behavior will be punished * times per reward rate. Eg: punishment = 5, reward rate = 0.0015 > punish 0.0075% per round.
         punishment: Decimal, 
+        /// Numeric datas deviating less than tolerance % from the median are deemed truthful. Eg: tolerance = 1 > within 1% of the median.
+        tolerance: Decimal, 
         /// NeuRacle system time, caculated by current epoch / round length.
         system_time: u64, 
-        /// Keep track of the round status.
+        /// Keep track of the round status. Validators commit the hash of their datas on round start.
         round_start: bool, 
+        /// Keep track of the reveal status. Validators reveal their datas after the commits closed.
+        reveal_start: bool, 
+        /// The commits can only be closed after an amount of time from the round start.
+        commit_length: u64, 
+        /// The epoch from which the commits can be closed.
+        commit_deadline: u64, 
+        /// The round can be ended without enough reveals after an amount of time from the commits close.
+        reveal_length: u64, 
+        /// The epoch from which the round can be ended without enough reveals.
+        reveal_deadline: u64, 
         /// Keep track of the active validators per round.
         active_validators: HashMap<ComponentAddress, Decimal>, 
         /// The badge to mint new NeuRacle ecosystem controller badge when new L2 project created.
@@ -90,11 +114,13 @@ blueprint! {
             fee_stablecoin: Decimal, 
             unstake_delay: u64, 
             reward_rate: Decimal, 
-            punishment: Decimal) -> ComponentAddress {
+            punishment: Decimal,
+            tolerance: Decimal) -> ComponentAddress {
 
             let system_time = Runtime::current_epoch() / round_length;
 
             assert_fee(fee_stablecoin);
+            assert_tolerance(tolerance);
 
             let controller_badge_new = mint_controller_badge.authorize(|| {
                 borrow_resource_manager!(controller_badge)
@@ -125,11 +151,14 @@ blueprint! {
                 .method("set_unstake_delay", rule!(require(admin_badge)))
                 .method("new_stable_coin_project", rule!(require(admin_badge)))
                 .method("set_round_length", rule!(require(admin_badge)))
+                .method("set_commit_length", rule!(require(admin_badge)))
+                .method("set_reveal_length", rule!(require(admin_badge)))
                 .method("new_api", rule!(require(controller_badge)))
                 .default(rule!(allow_all));
 
             let component = Self {
                 datas: BTreeMap::new(),
+                feed_types: HashMap::new(),
                 stable_coins: LazyMap::new(),
                 validators: Vec::new(),
                 validator_cap: validator_cap,
@@ -145,8 +174,14 @@ blueprint! {
                 round_length: round_length,
                 reward_rate: reward_rate / dec!("100"),
                 punishment: punishment,
+                tolerance: tolerance / dec!("100"),
                 system_time: system_time,
                 round_start: false,
+                reveal_start: false,
+                commit_length: 0,
+                commit_deadline: 0,
+                reveal_length: 1,
+                reveal_deadline: 0,
                 active_validators: HashMap::new(),
                 mint_controller_badge: Vault::with_bucket(mint_controller_badge)
                 }
@@ -178,7 +213,7 @@ blueprint! {
                 })
             });
 
-            let validator_address = Validator::new(self.neura, badge.resource_address(), self.controller_badge.resource_address(), name, fee, self.unstake_delay);
+            let validator_address = Validator::new(self.neura, badge.resource_address(), self.controller_badge.resource_address(), name, fee, self.unstake_delay, Runtime::actor().component_address().unwrap());
 
             let mut data: ValidatorData = badge.non_fungible().data();
 
@@ -210,7 +245,7 @@ blueprint! {
                 })
             });
 
-            let validator_address = Validator::new(self.neura, self.controller_badge.resource_address(), badge.resource_address(), name, fee, self.unstake_delay);
+            let validator_address = Validator::new(self.neura, self.controller_badge.resource_address(), badge.resource_address(), name, fee, self.unstake_delay, Runtime::actor().component_address().unwrap());
 
             let mut data: ValidatorData = badge.non_fungible().data();
 
@@ -226,7 +261,8 @@ blueprint! {
         }
 
         /// Anyone can become NeuRacle user with NAR token, the data source must be an accessible api or validators won't get the data
-        pub fn become_new_user(&mut self, mut payment: Bucket, api: String) -> (Bucket, Bucket) {
+        /// The feed type is only taken on the first registration of the data source.
+        pub fn become_new_user(&mut self, mut payment: Bucket, api: String, feed_type: FeedType) -> (Bucket, Bucket) {
 
             let amount = payment.amount();
 
@@ -257,6 +293,8 @@ blueprint! {
             if !self.datas.contains_key(&api) {
 
                 self.datas.insert(api.clone(), String::default());
+
+                self.feed_types.insert(api.clone(), feed_type);
             
             }
 
@@ -351,6 +389,8 @@ blueprint! {
 
             self.round_start = true;
 
+            self.commit_deadline = current + self.commit_length;
+
             if self.stage == 1 {
 
                 self.validators.sort_by_key(|a| a.1);
@@ -372,18 +412,68 @@ blueprint! {
             return reward
         }
 
-        /// This method will check on the active status of validators and can only advance if >2/3 validator is active.
-        /// After that, this method will get the datas with the most weight, check if it > 2/3 vote weight,
+        /// This method will close the commits, validators can only reveal their datas after that.
+        /// The method can only be called after the commit deadline and if >2/3 validator has committed.
+        /// The person who close the commits will be rewarded a payrate amount.
+        pub fn begin_reveal(&mut self) -> Bucket {
+
+            assert!(
+                self.round_start == true && self.reveal_start == false,
+                "New round hasn't started yet or the commits already closed!"
+            );
+
+            assert!(
+                Runtime::current_epoch() >= self.commit_deadline,
+                "Not time to close the commits yet!"
+            );
+
+            let committed = self.active_validators.keys().filter(|&&address| {
+                let validator: Validator = address.into();
+                validator.has_committed()
+            }).count();
+
+            assert!(
+                committed*3 > self.active_validators.len()*2,
+                "Not enough validator committed yet!"
+            );
+
+            self.controller_badge.authorize(|| {
+                self.active_validators.keys().for_each(|&address| {
+                    let validator: Validator = address.into();
+                    validator.reveal_start();
+                })
+            });
+
+            info!("Begin reveal round number {} of NeuRacle", self.system_time);
+
+            self.reveal_start = true;
+
+            self.reveal_deadline = Runtime::current_epoch() + self.reveal_length;
+
+            let reward = self.controller_badge.authorize(|| {
+                borrow_resource_manager!(self.neura).mint(self.pay_rate)
+            });
+
+            info!("You are rewarded {} NAR for close the commits of a NeuRacle round", reward.amount());
+
+            return reward
+        }
+
+        /// This method will check on the active status of validators and can only advance if >2/3 validator is active, or after the reveal deadline.
+        /// Validators that committed but haven't revealed their datas will be punished.
+        /// If >2/3 validator is active, this method will get the datas with the most weight, check if it > 2/3 vote weight,
         /// feed that on NeuRacle, reward the validators provided same datas and punish those didn't.
+        /// Otherwise no data is fed and the round is restarted, so withholding reveals can't block NeuRacle.
         /// The person who end a round will be rewarded 2 times payrate (Assuming call this method will more costly on xrd fee than start a round)
         pub fn end_round(&mut self) -> Bucket {
             
             assert!(
-                self.round_start == true,
-                "New round hasn't started yet!"
+                self.reveal_start == true,
+                "The reveals haven't started yet!"
             );
         
             let mut val: HashMap<ComponentAddress, Decimal> = HashMap::new();
+            let mut unrevealed: Vec<ComponentAddress> = Vec::new();
 
             self.active_validators.iter().for_each(|(&address, &weight)| {
     
@@ -394,49 +484,80 @@ blueprint! {
                     val.insert(address, weight);
                     
                 }
+
+                else if validator.has_committed() {
+
+                    unrevealed.push(address);
+
+                }
             });
             
+            let enough_active = val.len()*3 > self.active_validators.len()*2;
+
             assert!(
-                val.len()*3 > self.active_validators.len()*2,
+                enough_active || Runtime::current_epoch() >= self.reveal_deadline,
                 "Not enough validator active yet!"
             );
 
+            if !enough_active {
+                info!("Not enough validator revealed their datas before the reveal deadline, no data is fed this round")
+            }
+
             self.active_validators = val;
 
-            let mut all_datas: HashMap<BTreeMap<String, String>, Decimal> = HashMap::new();
+            let mut all_datas: HashMap<ComponentAddress, BTreeMap<String, String>> = HashMap::new();
             let mut total_weight = Decimal::zero();
 
             self.active_validators.iter().for_each(|(&address, &weight)| {
 
                 let validator: Validator = address.into();
-                let datas = validator.get_data();
-                if all_datas.contains_key(&datas) {
-                    if let Some(x) = all_datas.get_mut(&datas) {
-                        *x += weight;
-                    }
-                }
-                else {
-                    all_datas.insert(datas.clone(), weight);
-                }
+                all_datas.insert(address, validator.get_data());
                 total_weight += weight
             });
 
-            let result = all_datas.iter().max_by_key(|entry | entry.1).unwrap();
-            let data = result.0.clone();
-            let weight = result.1.clone();
+            // Validate each data source on it's own, validators must be truthful on every validated data source to be rewarded.
+            let mut truthful: HashMap<ComponentAddress, bool> = self.active_validators.keys().map(|&address| (address, true)).collect();
+            let mut validated = false;
 
-            if weight*dec!("3") >= total_weight*dec!("2") {
-                self.datas = data;
+            // Without enough reveals, the datas aren't validated.
+            let apis: Vec<String> = if enough_active { self.datas.keys().cloned().collect() } else { Vec::new() };
+
+            for api in apis {
+
+                let feed_type = *self.feed_types.get(&api).unwrap_or(&FeedType::Text);
+
+                let result = match feed_type {
+                    FeedType::Numeric => self.validate_numeric(&api, &all_datas, total_weight),
+                    FeedType::Text => self.validate_text(&api, &all_datas, total_weight)
+                };
+
+                if let Some((data, truthful_validators)) = result {
+                    self.datas.insert(api, data);
+                    truthful.iter_mut().for_each(|(address, is_truthful)| {
+                        *is_truthful = *is_truthful && truthful_validators.contains(address)
+                    });
+                    validated = true
+                }
+            }
+
+            if validated {
                 self.controller_badge.authorize(|| {
-                    self.active_validators.iter().for_each(|(&address, _weight)| {
+                    truthful.iter().for_each(|(&address, &is_truthful)| {
     
                         let validator: Validator = address.into();
-                        if validator.get_data() == self.datas.clone() {validator.mint(self.reward_rate)}
+                        if is_truthful {validator.mint(self.reward_rate)}
                             else {validator.burn(self.reward_rate * self.punishment)}
                     })  
                 })
             }
 
+            self.controller_badge.authorize(|| {
+                unrevealed.iter().for_each(|&address| {
+                    let validator: Validator = address.into();
+                    validator.burn(self.reward_rate * self.punishment)
+                })
+            });
+
             info!("End round {} of NeuRacle", self.system_time);
 
             let current = Runtime::current_epoch();
@@ -445,6 +566,8 @@ blueprint! {
 
             self.round_start = false;
 
+            self.reveal_start = false;
+
             let reward = self.controller_badge.authorize(|| {
                 borrow_resource_manager!(self.neura).mint(self.pay_rate*dec!("2"))
             });
@@ -461,6 +584,8 @@ blueprint! {
             if !self.datas.contains_key(&api) {
                 
                 self.datas.insert(api.clone(), String::default());
+
+                self.feed_types.insert(api.clone(), FeedType::Numeric);
             
             };
 
@@ -518,5 +643,71 @@ blueprint! {
         pub fn set_round_length(&mut self, new_round_length:u64) {
             self.round_length = new_round_length
         }
+
+        pub fn set_commit_length(&mut self, new_commit_length:u64) {
+            self.commit_length = new_commit_length
+        }
+
+        pub fn set_reveal_length(&mut self, new_reveal_length:u64) {
+            self.reveal_length = new_reveal_length
+        }
+
+        /// The validators of the current round.
+        pub fn get_active_validators(&self) -> Vec<ComponentAddress> {
+            self.active_validators.keys().cloned().collect()
+        }
+
+        /// Numeric data is validated when validators with >2/3 staked weight provided a number, the stake-weighted median is the validated data.
+        /// Validators within tolerance of the median are truthful.
+        fn validate_numeric(&self, api: &String, all_datas: &HashMap<ComponentAddress, BTreeMap<String, String>>, total_weight: Decimal) -> Option<(String, Vec<ComponentAddress>)> {
+
+            let values: Vec<(ComponentAddress, Decimal, Decimal)> = all_datas.iter()
+                .filter_map(|(address, datas)| {
+                    let value = Decimal::from_str(datas.get(api)?).ok()?;
+                    Some((*address, value, self.active_validators[address]))
+                })
+                .collect();
+
+            let weight = values.iter().fold(Decimal::zero(), |sum, (_, _, weight)| sum + *weight);
+
+            if values.is_empty() || weight*dec!("3") < total_weight*dec!("2") {
+                return None
+            }
+
+            let median = weighted_median(values.iter().map(|(_, value, weight)| (*value, *weight)).collect());
+            let band = tolerance_band(median, self.tolerance);
+
+            let truthful_validators = values.iter()
+                .filter(|(_, value, _)| (*value - median <= band) && (median - *value <= band))
+                .map(|(address, _, _)| *address)
+                .collect();
+
+            Some((median.to_string(), truthful_validators))
+        }
+
+        /// Text data is validated when validators with >2/3 staked weight provided the same data.
+        fn validate_text(&self, api: &String, all_datas: &HashMap<ComponentAddress, BTreeMap<String, String>>, total_weight: Decimal) -> Option<(String, Vec<ComponentAddress>)> {
+
+            let mut weights: HashMap<String, Decimal> = HashMap::new();
+
+            all_datas.iter().for_each(|(address, datas)| {
+                if let Some(data) = datas.get(api) {
+                    *weights.entry(data.clone()).or_insert(Decimal::zero()) += self.active_validators[address]
+                }
+            });
+
+            let (data, weight) = weights.into_iter().max_by_key(|entry| entry.1)?;
+
+            if weight*dec!("3") < total_weight*dec!("2") {
+                return None
+            }
+
+            let truthful_validators = all_datas.iter()
+                .filter(|(_, datas)| datas.get(api) == Some(&data))
+                .map(|(address, _)| *address)
+                .collect();
+
+            Some((data, truthful_validators))
+        }
     }
 }
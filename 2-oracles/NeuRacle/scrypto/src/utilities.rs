@@ -19,4 +19,91 @@ pub fn assert_fee(fee: Decimal) {
         (fee >= Decimal::zero()) && (fee <= dec!("100")),
         "Fee must be in the range of 0 to 100"
     );
-}
\ No newline at end of file
+}
+
+pub fn assert_tolerance(tolerance: Decimal) {
+    assert!(
+        (tolerance >= Decimal::zero()) && (tolerance <= dec!("100")),
+        "Tolerance must be in the range of 0 to 100 (%)"
+    );
+}
+
+/// Stake-weighted median of (value, weight) pairs, the lowest value at which the cumulative weight reaches half of the total weight.
+pub fn weighted_median(mut values: Vec<(Decimal, Decimal)>) -> Decimal {
+
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let total = values.iter().fold(Decimal::zero(), |sum, (_, weight)| sum + *weight);
+
+    let mut cumulative = Decimal::zero();
+
+    for (value, weight) in values.iter() {
+        cumulative += *weight;
+        if cumulative*dec!("2") >= total {
+            return *value
+        }
+    }
+
+    values.last().expect("No value to aggregate.").0
+}
+
+/// Half width of the band around the median in which numeric datas are truthful, the tolerance is a fraction of the median magnitude.
+/// A zero median has no magnitude, the tolerance is then the absolute half width.
+pub fn tolerance_band(median: Decimal, tolerance: Decimal) -> Decimal {
+
+    if median == Decimal::zero() {
+        return tolerance
+    }
+
+    let magnitude = if median < Decimal::zero() { -median } else { median };
+
+    magnitude * tolerance
+}
+
+/// Commitment of a validator datas, the SHA-256 of the validator component address, every key and value in key order, each followed by a new line, then the salt.
+/// The address binds the commitment to the validator, so another validator can't commit the same hash and reveal the same datas and salt.
+/// It's computed off-chain by the Gateway since a transaction computing it would publish the datas and the salt before the reveal.
+pub fn commitment(validator: ComponentAddress, datas: &BTreeMap<String, String>, salt: &str) -> Hash {
+
+    let mut text = validator.to_string();
+    text.push('\n');
+
+    for (key, value) in datas.iter() {
+        text.push_str(key);
+        text.push('\n');
+        text.push_str(value);
+        text.push('\n');
+    }
+
+    text.push_str(salt);
+
+    sha256(text)
+}
+
+/// The salt must have at least 128 random bits, written in hexadecimal, so the commitment can't be brute-forced.
+pub fn assert_salt(salt: &str) {
+    assert!(
+        salt.len() >= 32 && salt.chars().all(|c| c.is_ascii_hexdigit()),
+        "Salt must be at least 32 hexadecimal characters"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weighted_median_follows_the_stake() {
+        assert_eq!(weighted_median(vec![(dec!("3"), dec!("1")), (dec!("1"), dec!("1")), (dec!("2"), dec!("1"))]), dec!("2"));
+        assert_eq!(weighted_median(vec![(dec!("1"), dec!("1")), (dec!("2"), dec!("1"))]), dec!("1"));
+        assert_eq!(weighted_median(vec![(dec!("1"), dec!("1")), (dec!("2"), dec!("1")), (dec!("100"), dec!("5"))]), dec!("100"));
+        assert_eq!(weighted_median(vec![(dec!("-5"), dec!("2")), (dec!("-1"), dec!("1"))]), dec!("-5"));
+    }
+
+    #[test]
+    fn tolerance_band_is_never_negative() {
+        assert_eq!(tolerance_band(dec!("200"), dec!("0.01")), dec!("2"));
+        assert_eq!(tolerance_band(dec!("-200"), dec!("0.01")), dec!("2"));
+        assert_eq!(tolerance_band(Decimal::zero(), dec!("0.01")), dec!("0.01"));
+    }
+}
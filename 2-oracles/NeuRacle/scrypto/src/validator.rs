@@ -3,6 +3,7 @@
 
 use scrypto::prelude::*;
 use crate::utilities::*;
+use crate::neuracle::NeuRacle;
 
 #[derive(NonFungibleData)]
 pub struct Staker {
@@ -40,8 +41,14 @@ blueprint! {
         unstake_delay: u64,
         /// Store new datas on-chain
         datas: BTreeMap<String, String>,
-        /// Keep track of round status
+        /// Keep track of round status, validator can commit on round start
         round_start: bool,
+        /// Keep track of reveal status, validator can reveal after the commits closed
+        reveal_start: bool,
+        /// Hash commitment of the datas to reveal
+        commitment: Option<Hash>,
+        /// NeuRacle component the validator belongs to
+        neuracle: ComponentAddress,
         /// Keep track of validator status
         active: bool
 
@@ -49,7 +56,7 @@ blueprint! {
 
     impl Validator {
         
-        pub fn new(medium_token: ResourceAddress, badge: ResourceAddress, neura_controller_badge: ResourceAddress, name: String, fee: Decimal, unstake_delay: u64) -> ComponentAddress {
+        pub fn new(medium_token: ResourceAddress, badge: ResourceAddress, neura_controller_badge: ResourceAddress, name: String, fee: Decimal, unstake_delay: u64, neuracle: ComponentAddress) -> ComponentAddress {
 
             assert_fee(fee);
 
@@ -68,8 +75,10 @@ blueprint! {
             let rules = AccessRules::new()
                 .method("change_fee", rule!(require(badge)))
                 .method("withdraw_fee", rule!(require(badge)))
-                .method("update_data", rule!(require(badge)))
+                .method("commit_data", rule!(require(badge)))
+                .method("reveal_data", rule!(require(badge)))
                 .method("round_start", rule!(require(neura_controller_badge)))
+                .method("reveal_start", rule!(require(neura_controller_badge)))
                 .method("get_datas", rule!(require(neura_controller_badge)))
                 .method("mint", rule!(require(neura_controller_badge)))
                 .method("burn", rule!(require(neura_controller_badge)))
@@ -88,6 +97,9 @@ blueprint! {
                 unstake_delay: unstake_delay,
                 datas: BTreeMap::new(),
                 round_start: false,
+                reveal_start: false,
+                commitment: None,
+                neuracle: neuracle,
                 active: false
                 }
                 .instantiate()
@@ -289,25 +301,75 @@ blueprint! {
             
         }
 
-        /// Validator can only update data on round start.
-        pub fn update_data(&mut self, datas: BTreeMap<String, String>) {
+        /// Validator can only commit on round start, before the commits closed.
+        /// The commitment must be computed off-chain on the validator address, the datas and a secret salt, see `utilities::commitment`.
+        /// A commitment already made by another validator of the round is rejected.
+        pub fn commit_data(&mut self, commitment: Hash) {
 
             assert!(
                 self.round_start == true,
-                "The round haven't started, you can't update data yet"
+                "The round haven't started or the commits closed, you can't commit data"
+            );
+
+            let own_address = Runtime::actor().component_address().unwrap();
+            let neuracle: NeuRacle = self.neuracle.into();
+
+            assert!(
+                neuracle.get_active_validators().into_iter()
+                    .filter(|&address| address != own_address)
+                    .all(|address| {
+                        let validator: crate::validator::Validator = address.into();
+                        validator.get_commitment() != Some(commitment)
+                    }),
+                "Another validator already made this commitment"
+            );
+
+            self.commitment = Some(commitment)
+
+        }
+
+        /// Validator can only reveal the committed datas after the commits closed.
+        /// Unrevealed datas will be deemed absent and punished.
+        pub fn reveal_data(&mut self, datas: BTreeMap<String, String>, salt: String) {
+
+            assert!(
+                self.reveal_start == true,
+                "The reveals haven't started, you can't reveal data yet"
+            );
+
+            assert_salt(&salt);
+
+            assert!(
+                self.commitment == Some(commitment(Runtime::actor().component_address().unwrap(), &datas, &salt)),
+                "The datas don't match your commitment"
             );
             
             self.datas = datas;
             self.active = true;
-            self.round_start = false
+            self.reveal_start = false
 
         }
 
         pub fn round_start(&mut self) {
             self.active = false;
+            self.commitment = None;
+            self.reveal_start = false;
             self.round_start = true
         }
 
+        pub fn reveal_start(&mut self) {
+            self.round_start = false;
+            self.reveal_start = self.commitment.is_some()
+        }
+
+        pub fn has_committed(&self) -> bool {
+            self.commitment.is_some()
+        }
+
+        pub fn get_commitment(&self) -> Option<Hash> {
+            self.commitment
+        }
+
         pub fn get_status(&self) -> bool {
             self.active
         }
@@ -0,0 +1,46 @@
+#!/usr/bin/env bash
+
+# Datas of a validator intervening in the Gateway process
+export MALICIOUS_RESULTS='"https://api.bitfinex.com/v2/ticker/tXRDUSD", "111", "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies=usd", "222", "https://api.coingecko.com/api/v3/simple/price?ids=radix&vs_currencies=usd", "333", "https://www.timeapi.io/api/Time/current/zone?timeZone=Asia/Ho_Chi_Minh", "4444", "https://www.timeapi.io/api/Time/current/zone?timeZone=Europe/London", "555"'
+
+# The commitment is computed off-chain, a transaction would publish the datas and the salt before the reveal.
+# SHA-256 of the validator address, every key and value in key order, each followed by a new line, then the salt (see utilities::commitment)
+# Usage: make_commitment <validator address> <datas> <salt>
+make_commitment () {
+    echo "$2" | grep -o '"[^"]*"' | tr -d '"' | paste - - | LC_ALL=C sort -t "$(printf '\t')" -k1,1 \
+        | { printf '%s\n' "$1"; tr '\t' '\n'; printf '%s' "$3"; } | sha256sum | cut -d ' ' -f 1
+}
+
+# The validator commit the hash of it's datas with a secret 128 bits salt, the salt is kept for the reveal
+# Usage: commit_data <validator account> <private key> <validator address> <datas>
+commit_data () {
+    resim set-default-account $1 $2
+    export VALUP_ACC=$1
+    export VALUP_ADDRESS=$3
+    export DATAS="$4"
+    export SALT=`head -c 16 /dev/urandom | od -An -tx1 | tr -d ' \n'`
+    echo $SALT > /tmp/neuracle_salt_$3
+    export COMMITMENT=`make_commitment $3 "$DATAS" $SALT`
+    resim run ./transaction_manifest/commit_data
+}
+
+# The validator commit a given hash, eg: the commitment of another validator
+# Usage: commit_hash <validator account> <private key> <validator address> <commitment>
+commit_hash () {
+    resim set-default-account $1 $2
+    export VALUP_ACC=$1
+    export VALUP_ADDRESS=$3
+    export COMMITMENT=$4
+    resim run ./transaction_manifest/commit_data
+}
+
+# The validator reveal it's datas with the salt of the commitment
+# Usage: reveal_data <validator account> <private key> <validator address> <datas>
+reveal_data () {
+    resim set-default-account $1 $2
+    export VALUP_ACC=$1
+    export VALUP_ADDRESS=$3
+    export DATAS="$4"
+    export SALT=`cat /tmp/neuracle_salt_$3`
+    resim run ./transaction_manifest/reveal_data
+}
@@ -4,33 +4,33 @@
 set -e
 
 source ./log.sh
+source ./commit_reveal.sh
 
-logc "The Gateway get api list from NeuRacle component, fetch and feed data to the system"
+logc "The Gateway get api list from NeuRacle component, fetch and commit data to the system"
 
 logy "This will take a bit"
 
-resim set-default-account $VAL1_ACC $VAL1_PIV
-export VALUP_ADDRESS=$VAL1_ADDRESS
-export VALUP_ACC=$VAL1_ACC
-
 export RESULTS=`./neuracle_gateway_prototype.exe`
 
-resim run ./transaction_manifest/update_data
+commit_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
 
-resim set-default-account $VAL2_ACC $VAL2_PIV
-export VALUP_ADDRESS=$VAL2_ADDRESS
-export VALUP_ACC=$VAL2_ACC
-resim run ./transaction_manifest/update_data
+commit_data $VAL2_ACC $VAL2_PIV $VAL2_ADDRESS "$RESULTS"
 
 logy "Let one validator non-active and one validator to intervene in the Gateway process and behave malicious"
-resim set-default-account $VAL4_ACC $VAL4_PIV
-export VALUP_ADDRESS=$VAL4_ADDRESS
-export VALUP_ACC=$VAL4_ACC
-resim run ./transaction_manifest/update_data_malicious
-
-resim set-default-account $VAL5_ACC $VAL5_PIV
-export VALUP_ADDRESS=$VAL5_ADDRESS
-export VALUP_ACC=$VAL5_ACC
-resim run ./transaction_manifest/update_data
+commit_data $VAL4_ACC $VAL4_PIV $VAL4_ADDRESS "$MALICIOUS_RESULTS"
+
+commit_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$RESULTS"
+
+logc "A person close the commits, validators can't copy each other datas since they can only reveal what they committed"
+
+resim run ./transaction_manifest/start_reveal
+
+reveal_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
+
+reveal_data $VAL2_ACC $VAL2_PIV $VAL2_ADDRESS "$RESULTS"
+
+reveal_data $VAL4_ACC $VAL4_PIV $VAL4_ADDRESS "$MALICIOUS_RESULTS"
+
+reveal_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$RESULTS"
 
 completed
\ No newline at end of file
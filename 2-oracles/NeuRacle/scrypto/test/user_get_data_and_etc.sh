@@ -4,6 +4,7 @@
 set -e
 
 source ./log.sh
+source ./commit_reveal.sh
 
 logc "Users use badge to get data"
 
@@ -33,30 +34,38 @@ resim set-current-epoch $epoch
 logg "That person call new round again."
 resim run ./transaction_manifest/start_round
 
-resim set-default-account $VAL1_ACC $VAL1_PIV
-export VALUP_ADDRESS=$VAL1_ADDRESS
-export VALUP_ACC=$VAL1_ACC
-resim run ./transaction_manifest/update_data
+commit_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
+
+logr "A lazy validator try to commit the commitment of another validator. This should show error!"
+commit_hash $VAL3_ACC $VAL3_PIV $VAL3_ADDRESS $COMMITMENT || true
+
+logr "Only one validator committed, but someone try to close the commits. This should show error!"
+resim run ./transaction_manifest/start_reveal || true
+
+commit_data $VAL2_ACC $VAL2_PIV $VAL2_ADDRESS "$RESULTS"
+
+commit_data $VAL4_ACC $VAL4_PIV $VAL4_ADDRESS "$RESULTS"
+
+commit_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$MALICIOUS_RESULTS"
+
+logg "Now 4/5 validator committed, someone close the commits."
+resim run ./transaction_manifest/start_reveal
+
+reveal_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
 
 logr "Only one validator is active, but that validator try to end the round. This should show error!"
 resim run ./transaction_manifest/end_round || true
 
-resim set-default-account $VAL2_ACC $VAL2_PIV
-export VALUP_ADDRESS=$VAL2_ADDRESS
-export VALUP_ACC=$VAL2_ACC
-resim run ./transaction_manifest/update_data
+logr "A validator try to reveal other datas than it committed. This should show error!"
+reveal_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$RESULTS" || true
 
-resim set-default-account $VAL4_ACC $VAL4_PIV
-export VALUP_ADDRESS=$VAL4_ADDRESS
-export VALUP_ACC=$VAL4_ACC
-resim run ./transaction_manifest/update_data
+reveal_data $VAL2_ACC $VAL2_PIV $VAL2_ADDRESS "$RESULTS"
 
-resim set-default-account $VAL5_ACC $VAL5_PIV
-export VALUP_ADDRESS=$VAL5_ADDRESS
-export VALUP_ACC=$VAL5_ACC
-resim run ./transaction_manifest/update_data_malicious
+reveal_data $VAL4_ACC $VAL4_PIV $VAL4_ADDRESS "$RESULTS"
 
-logg "Now 4/5 validator voted, someone try to end the round."
+reveal_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$MALICIOUS_RESULTS"
+
+logg "Now 4/5 validator revealed, someone try to end the round."
 resim run ./transaction_manifest/end_round
 
 logr "User try to get data after out of time. This should show error!"
@@ -67,4 +76,25 @@ logg "User funding account again to get data."
 resim call-method $COMP refund_account 1,$USER_BADGE 100,$NEURA 
 resim call-method $COMP get_data 1,$USER_BADGE 
 
+logc "Advance epoch by 1 and start a new round."
+epoch=$(($epoch + 1))
+resim set-current-epoch $epoch
+resim run ./transaction_manifest/start_round
+
+commit_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
+commit_data $VAL2_ACC $VAL2_PIV $VAL2_ADDRESS "$RESULTS"
+commit_data $VAL4_ACC $VAL4_PIV $VAL4_ADDRESS "$RESULTS"
+commit_data $VAL5_ACC $VAL5_PIV $VAL5_ADDRESS "$RESULTS"
+resim run ./transaction_manifest/start_reveal
+
+reveal_data $VAL1_ACC $VAL1_PIV $VAL1_ADDRESS "$RESULTS"
+
+logr "The other validators withhold their reveals, someone try to end the round. This should show error!"
+resim run ./transaction_manifest/end_round || true
+
+logg "After the reveal deadline, the round can end without enough reveals, no data is fed and the withholding validators are punished."
+epoch=$(($epoch + 1))
+resim set-current-epoch $epoch
+resim run ./transaction_manifest/end_round
+
 completed
\ No newline at end of file
@@ -25,11 +25,17 @@ blueprint! {
         /// a callback.
         callback_handle_resource: ResourceAddress,
 
-        /// A vault for collecting fees
+        /// A vault for collecting the fees of the callbacks executed by the admin/operator
         fees: Vault,
 
+        /// The prepaid fees and the remaining executions of each scheduled callback
+        schedules: HashMap<NonFungibleId, Schedule>,
+
         /// The amount in XRD that is charged for scheduling a callback
-        fee_amount: Decimal
+        fee_amount: Decimal,
+
+        /// The oracles that are read to validate and enforce time and price based triggers
+        oracles: TriggerOracles
     }
 
     impl CallbackScheduler {
@@ -88,9 +94,12 @@ blueprint! {
                 .method("get_new_callback_admin_handles",
                     rule!(require(admin_badge.resource_address())))
                 .method("execute_callback", rule!(allow_all))
+                .method("execute_due_callback", rule!(allow_all))
                 .method("cancel_callbacks", rule!(allow_all))
                 .method("get_callback_handle_resource", rule!(allow_all))
-                .method("withdraw_fees", rule!(require(admin_badge.resource_address())));
+                .method("withdraw_fees", rule!(require(admin_badge.resource_address())))
+                .method("set_time_oracle", rule!(require(admin_badge.resource_address())))
+                .method("set_price_oracle", rule!(require(admin_badge.resource_address())));
 
             // Instantiate the component
             let component = Self {
@@ -99,7 +108,9 @@ blueprint! {
                 new_callback_admin_handles: Vault::new(callback_admin_handle_resource),
                 callback_handle_resource,
                 fees:Vault::new(RADIX_TOKEN),
-                fee_amount
+                schedules: HashMap::new(),
+                fee_amount,
+                oracles: TriggerOracles::default()
             }
             .instantiate()
             .add_access_check(rules)
@@ -113,7 +124,10 @@ blueprint! {
         /// # Arguments:
         ///
         /// * `callback_request` A struct that describes the callback that should be scheduled
-        /// * `fee` A bucket with the fee in XRD that is being payed for the scheduling service
+        /// * `fee` A bucket with the fee in XRD that is being payed for the scheduling service.
+        /// The fee is charged once for each execution of the callback and is held in escrow until
+        /// the execution. Fees of executions that do not happen are refunded when the user cancels
+        /// the callback.
         ///
         /// # Returns:
         /// * a bucket containing a CallbackHandle that may be used to cancel the callback
@@ -122,7 +136,7 @@ blueprint! {
             -> (Bucket, Bucket) {
 
             // First, validate the callback request
-            callback_request.assert_valid();
+            callback_request.assert_valid(&self.oracles);
 
             // Mint a new Callback NFR
             // The same callback ID is used for the Callback, the CallbackHandle and the
@@ -156,8 +170,11 @@ blueprint! {
             });
             utils::debug_log_non_fungible("Minted CallbackHandle", &callback_handle);
 
-            // Collect the fee
-            self.fees.put(fee.take(self.fee_amount));
+            // Put the fees of all executions in escrow
+            let executions = Decimal::from(callback_request.trigger.executions());
+            let escrow = fee.take(self.fee_amount * executions);
+            let schedule = Schedule::new(&callback_request.trigger, self.fee_amount, escrow);
+            self.schedules.insert(callback_id, schedule);
             // Return the callback handle as well as any change to the caller
             (callback_handle, fee)
         }
@@ -184,12 +201,16 @@ blueprint! {
         /// in a separate transaction.
         /// * The target component fails to authorize the callback. In this case the admin/operator
         /// should cancel the callback in a separate transaction.
-        /// * The callback may have been canceled by the user. Because recallable resources
-        /// are not implemented as of Scrypto 0.4.0, the CallbackAdminHandle NFR that is
-        /// associated with the canceled callback still remains in the admins/operators
+        /// * The callback may have been canceled by the user or executed by a keeper. Because
+        /// recallable resources are not implemented as of Scrypto 0.4.0, the CallbackAdminHandle
+        /// NFR that is associated with the callback still remains in the admins/operators
         /// account. If this happens, the admin/operator should cancel the callback in a
         /// separate transaction.
         ///
+        /// The fee of the execution is collected by the component. If the callback is recurring
+        /// and has executions left, the CallbackAdminHandle is put back with the new handles,
+        /// from where the admin/operator retrieves it again.
+        ///
         /// # Arguments:
         /// * `callback_admin_handle` - A bucket containing the CallbackAdminHandle that is
         /// associated with the callback that should be executed.
@@ -206,50 +227,56 @@ blueprint! {
             // Also see the documentation on method cancel_callbacks
             assert!(
                 self.scheduled_callbacks.non_fungible_ids().contains(&callback_id),
-                "Callback was canceled by the user"
+                "Callback was canceled by the user or executed by a keeper"
             );
 
-            // Retrieve the callback NFR from the internal vault
-            let callback = self.scheduled_callbacks.take_non_fungible(&callback_id);
-            let callback_data = callback.non_fungible::<Callback>().data();
-            debug!("Executing {}", callback_data);
-
-            // Assert that the callback can be executed now
-            callback_data.trigger.assert_valid_now();
+            // Execute the callback and collect its fee
+            let (fee, done) = self.execute(&callback_id, false);
+            self.fees.put(fee);
 
-            // Authorize the callback we want to perform with the callee component
-            // This will yield us 1) the callee's callback_handle which we must burn
-            // after executing the callback and 2) any proofs that are expected by
-            // the method that should be called. If no explicit auth_provider has been configured
-            // for the callback, assume that the callee component itself implements the
-            // `authorize_callback` method.
-            let auth_provider = callback_data.auth_provider.unwrap_or(callback_data.component);
-            let auth_provider = borrow_component!(auth_provider);
-            let (callback_handle, proofs) = auth_provider
-                .call::<(Bucket, Vec<Proof>)>("authorize_callback", args!(callback.create_proof()));
+            if done {
+                self.minter.authorize(|| callback_admin_handle.burn());
+            } else {
+                self.new_callback_admin_handles.put(callback_admin_handle);
+            }
+        }
 
-            // Assert that the callee component has given us the correct CallbackHandle
-            utils::assert_resource_eq!(callback_handle, self.callback_handle_resource);
-            assert_eq!(callback_id, callback_handle.non_fungible::<CallbackHandle>().id(),
-                "Invalid CallbackHandle: the id of the provided CallbackHandle does not \
-                match that of the Callback"
+        /// Execute a callback that is due and get paid its fee out of the escrow
+        ///
+        /// Anybody may call this method, e.g. keepers that watch the scheduled callbacks and
+        /// execute them as soon as they are due. It has the same failure modes as `execute_callback`.
+        /// Callbacks whose trigger can't be verified on-ledger (`OnCondition`) can only be executed by the
+        /// admin/operator with `execute_callback`.
+        ///
+        /// # Arguments:
+        /// * `callback_id` - The ID of the callback that should be executed
+        ///
+        /// # Returns:
+        /// A bucket with the fee in XRD of the execution
+        pub fn execute_due_callback(&mut self, callback_id: NonFungibleId) -> Bucket {
+            assert!(
+                self.scheduled_callbacks.non_fungible_ids().contains(&callback_id),
+                "Callback was canceled or executed already"
             );
 
-            // Execute the callback using the proofs we were given
-            callback_data.execute_call(proofs);
+            let (fee, done) = self.execute(&callback_id, true);
 
-            // Burn all three NFRs that are associated with the executed callback
-            self.minter.authorize(|| {
-                callback.burn();
-                callback_handle.burn();
-                callback_admin_handle.burn();
-            });
+            // Burn the CallbackAdminHandle if the admin/operator has not retrieved it yet
+            if done && self.new_callback_admin_handles.non_fungible_ids().contains(&callback_id) {
+                self.minter.authorize(|| {
+                    self.new_callback_admin_handles.take_non_fungible(&callback_id).burn();
+                });
+            }
+
+            fee
         }
 
         /// Cancels all of the callbacks that are referenced by the supplied callback handles.
         /// This method can be called from either party, by the user as well as by the
         /// SchedulerComponent admin/operator.
-        /// At the moment fees will not be reimbursed.
+        /// When the user cancels, the fees of the executions that did not happen are refunded.
+        /// When the admin/operator cancels, the fees remain in escrow until the user cancels with
+        /// their "orphan" CallbackHandle.
         ///
         /// Because resources are not yet recallable as of Scrypto 0.4.1,
         /// when one party cancels a callback, the Callback NFR will be burned but the corresponding
@@ -265,7 +292,10 @@ blueprint! {
         /// # Arguments
         /// * `callback_handles` - A bucket containing the CallbackHandle or CallbackAdminHandle
         /// NFRs of the callbacks that should be canceled
-        pub fn cancel_callbacks(&mut self, callback_handles: Bucket) {
+        ///
+        /// # Returns
+        /// A bucket with the refunded fees in XRD, empty if the admin/operator cancels
+        pub fn cancel_callbacks(&mut self, callback_handles: Bucket) -> Bucket {
             // Determine the actual resource that was given to us and
             // assert that it is either a CallbackAdminHandle or CallbackHandle
             let handle_resource = callback_handles.resource_address();
@@ -288,6 +318,16 @@ blueprint! {
                 .intersection(&self.new_callback_admin_handles.non_fungible_ids())
                 .cloned().collect();
 
+            // Refund the fees left in escrow if the user cancels
+            let mut refund = Bucket::new(RADIX_TOKEN);
+            if handle_resource == self.callback_handle_resource {
+                for callback_id in callback_handles.non_fungible_ids() {
+                    if let Some(schedule) = self.schedules.get_mut(&callback_id) {
+                        refund.put(schedule.refund());
+                    }
+                }
+            }
+
             // Burn the associated Callback, CallbackHandle and CallbackAdminHandle NFRs
             self.minter.authorize(|| {
                 callback_handles.burn();
@@ -297,6 +337,8 @@ blueprint! {
                 // TODO Recall and burn the corresponding CallbackHandle/CallbackAdminHandle
                 // of the other party once recallable resources are implemented
             });
+
+            refund
         }
 
         /// Returns the resource address of the CallbackHandle NFR
@@ -308,6 +350,101 @@ blueprint! {
         pub fn withdraw_fees(&mut self) -> Bucket {
             self.fees.take_all()
         }
+
+        /// Sets the time oracle that is used for `AtDateTime` triggers
+        ///
+        /// The current time is the UNIX time at the start of the anchor epoch plus the time elapsed
+        /// since then according to the oracle. An EpochDurationOracle only lets its owner tick, and
+        /// its elapsed time never decreases, so the admin/operator should connect one it owns.
+        ///
+        /// # Arguments
+        /// * `time_oracle` - A component with a `millis_since_epoch` method returning the milliseconds
+        /// elapsed since the start of an epoch, e.g. an EpochDurationOracle component
+        /// * `anchor_epoch` - The epoch the elapsed time is counted from
+        /// * `anchor_time` - The UNIX time in seconds at the start of the anchor epoch
+        pub fn set_time_oracle(
+            &mut self,
+            time_oracle: ComponentAddress,
+            anchor_epoch: u64,
+            anchor_time: u64,
+        ) {
+            self.oracles.time_oracle = Some((time_oracle, anchor_epoch, anchor_time));
+        }
+
+        /// Sets the price oracle that is used for `WhenPriceCrosses` triggers
+        ///
+        /// # Arguments
+        /// * `price_oracle` - A component with a `get_price` method that takes a pair and returns
+        /// the price and the epoch it was fed at, e.g. a DexianOracle component
+        /// * `max_age_epochs` - The number of epochs after which a price is stale, triggers can't be
+        /// scheduled or executed against a stale price
+        pub fn set_price_oracle(&mut self, price_oracle: ComponentAddress, max_age_epochs: u64) {
+            self.oracles.price_oracle = Some((price_oracle, max_age_epochs));
+        }
+
+        /// Executes the callback with the given ID
+        ///
+        /// # Arguments:
+        /// * `callback_id` - The ID of the callback that should be executed
+        /// * `by_keeper` - Whether a keeper executes the callback instead of the admin/operator
+        ///
+        /// # Returns:
+        /// * a bucket with the fee of the execution, taken out of the escrow
+        /// * whether all executions of the callback are done
+        fn execute(&mut self, callback_id: &NonFungibleId, by_keeper: bool) -> (Bucket, bool) {
+            // Retrieve the callback NFR from the internal vault
+            let callback = self.scheduled_callbacks.take_non_fungible(callback_id);
+            let callback_data = callback.non_fungible::<Callback>().data();
+            debug!("Executing {}", callback_data);
+
+            // Assert that the callback can be executed now
+            assert!(
+                !by_keeper || callback_data.trigger.is_verifiable_on_ledger(),
+                "Only the admin/operator can execute a callback with a {} trigger",
+                callback_data.trigger
+            );
+            callback_data.trigger.assert_valid_now(&self.oracles);
+            self.schedules.get_mut(callback_id).unwrap().record_execution(&callback_data.trigger);
+
+            // Authorize the callback we want to perform with the callee component
+            // This will yield us 1) the callee's callback_handle which we must burn
+            // after executing the callback and 2) any proofs that are expected by
+            // the method that should be called. If no explicit auth_provider has been configured
+            // for the callback, assume that the callee component itself implements the
+            // `authorize_callback` method.
+            let auth_provider = callback_data.auth_provider.unwrap_or(callback_data.component);
+            let auth_provider = borrow_component!(auth_provider);
+            let (callback_handle, proofs) = auth_provider
+                .call::<(Bucket, Vec<Proof>)>("authorize_callback", args!(callback.create_proof()));
+
+            // Assert that the callee component has given us the correct CallbackHandle
+            utils::assert_resource_eq!(callback_handle, self.callback_handle_resource);
+            assert_eq!(*callback_id, callback_handle.non_fungible::<CallbackHandle>().id(),
+                "Invalid CallbackHandle: the id of the provided CallbackHandle does not \
+                match that of the Callback"
+            );
+
+            // Execute the callback using the proofs we were given
+            callback_data.execute_call(proofs);
+
+            let schedule = self.schedules.get_mut(callback_id).unwrap();
+            let fee = schedule.take_fee();
+            if schedule.executions_left > 0 {
+                // The callback is recurring, give the CallbackHandle back to the auth provider
+                // and keep the Callback NFR for the next execution
+                auth_provider.call::<()>(
+                    "return_callback_handle", args!(callback.create_proof(), callback_handle));
+                self.scheduled_callbacks.put(callback);
+                (fee, false)
+            } else {
+                // Burn the Callback and CallbackHandle NFRs that are associated with the callback
+                self.minter.authorize(|| {
+                    callback.burn();
+                    callback_handle.burn();
+                });
+                (fee, true)
+            }
+        }
     }
 }
 
@@ -317,7 +454,22 @@ pub enum Trigger {
     /// Execute the callback at a specific epoch.
     AtEpoch(u64),
 
-    /// Execute the callback at a specific date and time
+    /// Execute the callback every `interval` epochs from `first_epoch` on, at most `times` times.
+    /// An execution that is missed is skipped, the callback is due again at the next interval.
+    /// The auth provider of a recurring callback must implement `return_callback_handle`.
+    EveryEpochs {
+        /// The epoch of the first execution
+        first_epoch: u64,
+
+        /// The number of epochs between two executions
+        interval: u64,
+
+        /// The maximum number of executions
+        times: u32,
+    },
+
+    /// Execute the callback at a specific date and time.
+    /// The time is read from the time oracle configured in the scheduler.
     AtDateTime {
         /// ISO time string
         date_time: String,
@@ -325,11 +477,22 @@ pub enum Trigger {
         /// On DLTs precise execution times cannot be guaranteed.
         /// This specifies the tolerated deviation from the given
         /// date_time when executing the callback.
-        /// Note that at this moment no date_time verification is
-        /// implemented. This is left for future work.
         tolerance_seconds: u8,
     },
 
+    /// Execute the callback once the price of a pair has crossed a threshold.
+    /// The price is read from the price oracle configured in the scheduler.
+    WhenPriceCrosses {
+        /// The pair as known by the price oracle, e.g. "XRD/USDT"
+        pair: String,
+
+        /// The threshold the price must reach
+        price: Decimal,
+
+        /// Whether the price must rise above or fall below the threshold
+        direction: PriceDirection,
+    },
+
     /// Execute the callback when a specific condition is met
     ///
     /// This is only in hear for demo purposes.
@@ -339,12 +502,26 @@ pub enum Trigger {
 }
 
 impl Trigger {
+    /// Returns the number of executions of a callback with this trigger
+    pub fn executions(&self) -> u32 {
+        match self {
+            Trigger::EveryEpochs { times, .. } => *times,
+            _ => 1,
+        }
+    }
+
+    /// Returns whether the scheduler can check on-ledger that the trigger is valid.
+    /// Callbacks with other triggers rely on the admin/operator and can't be executed by keepers.
+    pub fn is_verifiable_on_ledger(&self) -> bool {
+        !matches!(self, Trigger::OnCondition(_))
+    }
+
     /// Asserts that the trigger is valid now.
     /// This can for example be a check that we are in the right epoch or
-    /// it also could be a check against a time oracle (not implemented yet).
+    /// a check against the time or price oracle.
     ///
     /// Panics if the trigger is not valid now
-    fn assert_valid_now(&self) {
+    fn assert_valid_now(&self, oracles: &TriggerOracles) {
         match self {
             Trigger::AtEpoch(epoch) => {
                 let now = Runtime::current_epoch();
@@ -356,7 +533,40 @@ impl Trigger {
                     now
                 );
             }
-            Trigger::AtDateTime { .. } => (), // TODO - Future work: implement this check e.g. by calling a 3rd-party time oracle
+            // Checked against the Schedule of the callback
+            Trigger::EveryEpochs { .. } => (),
+            Trigger::AtDateTime {
+                date_time,
+                tolerance_seconds,
+            } => {
+                let time = parse_date_time(date_time);
+                let now = oracles.current_time();
+                let tolerance = *tolerance_seconds as u64;
+                assert!(
+                    now + tolerance >= time && now <= time + tolerance,
+                    "Invalid execution, trigger restriction violated: \
+                execution is only allowed at {} ±{}s. Current time: {}",
+                    date_time,
+                    tolerance_seconds,
+                    now
+                );
+            }
+            Trigger::WhenPriceCrosses {
+                pair,
+                price,
+                direction,
+            } => {
+                let now = oracles.current_price(pair);
+                assert!(
+                    direction.is_crossed(now, *price),
+                    "Invalid execution, trigger restriction violated: \
+                execution is only allowed once the price of {} is {} {}. Current price: {}",
+                    pair,
+                    direction,
+                    price,
+                    now
+                );
+            }
             Trigger::OnCondition(_) => (), // TODO - Future work: implement an OnCondition specification and this check
         }
     }
@@ -366,15 +576,176 @@ impl Display for Trigger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::AtEpoch(epoch) => write!(f, "AtEpoch({epoch})"),
+            Self::EveryEpochs {
+                first_epoch,
+                interval,
+                times,
+            } => write!(
+                f,
+                "EveryEpochs(from {first_epoch} every {interval}, {times} times)"
+            ),
             Self::AtDateTime {
                 date_time,
                 tolerance_seconds,
             } => write!(f, "AtDateTime({date_time} ±{tolerance_seconds}s)"),
+            Self::WhenPriceCrosses {
+                pair,
+                price,
+                direction,
+            } => write!(f, "WhenPriceCrosses({pair} {direction} {price})"),
             Self::OnCondition(condition) => write!(f, "OnCondition({condition})"),
         }
     }
 }
 
+/// The direction in which a price must cross the threshold of a `WhenPriceCrosses` trigger
+#[derive(scrypto::Encode, scrypto::Decode, scrypto::TypeId, scrypto::Describe, Clone, Copy)]
+pub enum PriceDirection {
+    /// The price must rise to the threshold or above
+    Above,
+
+    /// The price must fall to the threshold or below
+    Below,
+}
+
+impl PriceDirection {
+    /// Returns true if `price` has crossed `threshold` in this direction
+    fn is_crossed(&self, price: Decimal, threshold: Decimal) -> bool {
+        match self {
+            Self::Above => price >= threshold,
+            Self::Below => price <= threshold,
+        }
+    }
+}
+
+impl Display for PriceDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Above => write!(f, "above"),
+            Self::Below => write!(f, "below"),
+        }
+    }
+}
+
+/// Parses the date time of an `AtDateTime` trigger into a UNIX timestamp in seconds
+///
+/// Panics if the date time is not a valid ISO string
+fn parse_date_time(date_time: &str) -> u64 {
+    utils::parse_iso_date_time(date_time)
+        .unwrap_or_else(|| panic!("Invalid date time: {}", date_time))
+}
+
+/// The oracles a CallbackScheduler reads to validate and enforce time and price based triggers.
+/// Both are configured by the admin/operator of the scheduler.
+#[derive(scrypto::Encode, scrypto::Decode, scrypto::TypeId, scrypto::Describe, Default)]
+pub struct TriggerOracles {
+    /// A component with a `millis_since_epoch` method returning the milliseconds elapsed since the
+    /// start of an epoch, with the anchor epoch and the UNIX time in seconds at its start
+    time_oracle: Option<(ComponentAddress, u64, u64)>,
+
+    /// A component with a `get_price` method that takes a pair and returns
+    /// the price and the epoch it was fed at, with the maximum age of a price in epochs
+    price_oracle: Option<(ComponentAddress, u64)>,
+}
+
+impl TriggerOracles {
+    /// Returns the current UNIX time in seconds
+    ///
+    /// Panics if no time oracle is configured
+    fn current_time(&self) -> u64 {
+        let (time_oracle, anchor_epoch, anchor_time) =
+            self.time_oracle.expect("No time oracle configured");
+        let millis: u64 =
+            borrow_component!(time_oracle).call("millis_since_epoch", args!(anchor_epoch));
+        // Ignore any fraction of a second
+        anchor_time + millis / 1000
+    }
+
+    /// Returns the current price of the given pair
+    ///
+    /// Panics if no price oracle is configured or if the price is older than its maximum age
+    fn current_price(&self, pair: &str) -> Decimal {
+        let (price_oracle, max_age_epochs) = self.price_oracle.expect("No price oracle configured");
+        let (price, epoch): (Decimal, u64) =
+            borrow_component!(price_oracle).call("get_price", args!(pair.to_owned()));
+        let now = Runtime::current_epoch();
+        assert!(
+            now <= epoch + max_age_epochs,
+            "Stale price of {}: fed at epoch {}, current epoch: {}",
+            pair,
+            epoch,
+            now
+        );
+        price
+    }
+}
+
+/// The prepaid fees and the remaining executions of a scheduled callback
+#[derive(scrypto::Encode, scrypto::Decode, scrypto::TypeId, scrypto::Describe)]
+pub struct Schedule {
+    /// The fee that is paid out of the escrow for each execution
+    fee_per_execution: Decimal,
+
+    /// The number of executions left
+    executions_left: u32,
+
+    /// The epoch from which the next execution of an `EveryEpochs` callback is due
+    next_epoch: u64,
+
+    /// The fees of the executions left
+    escrow: Vault,
+}
+
+impl Schedule {
+    /// Create the schedule of a callback
+    ///
+    /// # Arguments:
+    /// * `trigger` - The trigger of the callback
+    /// * `fee_per_execution` - The fee that is paid for each execution
+    /// * `escrow` - The fees of all executions
+    fn new(trigger: &Trigger, fee_per_execution: Decimal, escrow: Bucket) -> Self {
+        let next_epoch = match trigger {
+            Trigger::EveryEpochs { first_epoch, .. } => *first_epoch,
+            _ => 0,
+        };
+        Self {
+            fee_per_execution,
+            executions_left: trigger.executions(),
+            next_epoch,
+            escrow: Vault::with_bucket(escrow),
+        }
+    }
+
+    /// Records an execution of the callback
+    ///
+    /// Panics if an `EveryEpochs` callback is not due yet
+    fn record_execution(&mut self, trigger: &Trigger) {
+        if let Trigger::EveryEpochs { interval, .. } = trigger {
+            let now = Runtime::current_epoch();
+            assert!(
+                now >= self.next_epoch,
+                "Invalid execution, trigger restriction violated: \
+            execution is only allowed from epoch {}. Current epoch: {}",
+                self.next_epoch,
+                now
+            );
+            // Skip the executions that were missed
+            self.next_epoch += interval * ((now - self.next_epoch) / interval + 1);
+        }
+        self.executions_left -= 1;
+    }
+
+    /// Takes the fee of one execution out of the escrow
+    fn take_fee(&mut self) -> Bucket {
+        self.escrow.take(self.fee_per_execution)
+    }
+
+    /// Takes the fees of the executions left out of the escrow
+    fn refund(&mut self) -> Bucket {
+        self.escrow.take_all()
+    }
+}
+
 /// Represents a request for a callback
 #[derive(scrypto::Encode, scrypto::Decode, scrypto::TypeId, scrypto::Describe)]
 pub struct CallbackRequest {
@@ -425,13 +796,43 @@ impl CallbackRequest {
 
     /// Validates the request, e.g. by checking that the trigger is not in the past
     /// Panics if the request is invalid
-    pub fn assert_valid(&self) {
-        match self.trigger {
+    ///
+    /// # Arguments:
+    /// * `oracles` - The oracles of the scheduler, used to check time and price based triggers
+    pub fn assert_valid(&self, oracles: &TriggerOracles) {
+        match &self.trigger {
             Trigger::AtEpoch(epoch) => assert!(
-                epoch > Runtime::current_epoch(),
+                *epoch > Runtime::current_epoch(),
                 "Callback execution must be in the future"
             ),
-            _ => (), // TODO - Future work: Implement validations for the other triggers
+            Trigger::EveryEpochs {
+                first_epoch,
+                interval,
+                times,
+            } => {
+                assert!(
+                    *first_epoch > Runtime::current_epoch(),
+                    "Callback execution must be in the future"
+                );
+                assert!(*interval > 0, "Interval must be at least one epoch");
+                assert!(*times > 0, "Callback must be executed at least once");
+            }
+            Trigger::AtDateTime { date_time, .. } => assert!(
+                parse_date_time(date_time) > oracles.current_time(),
+                "Callback execution must be in the future"
+            ),
+            Trigger::WhenPriceCrosses {
+                pair,
+                price,
+                direction,
+            } => {
+                assert!(*price > Decimal::zero(), "Price threshold must be positive");
+                assert!(
+                    !direction.is_crossed(oracles.current_price(pair), *price),
+                    "Price threshold is already crossed"
+                );
+            }
+            Trigger::OnCondition(_) => (), // TODO - Future work: Implement validations for OnCondition
         }
     }
 
@@ -538,14 +939,38 @@ impl Callback {
     where
         for<'a> F: FnOnce(&'a NonFungibleId) -> Bucket,
     {
+        // Load the CallbackHandle corresponding to this callback
+        let callback_id = callback.non_fungible::<Callback>().data().id;
+        let callback_handle = callback_handle_provider(&callback_id);
+        // This also ensures that the callback_handle_provider is correctly implemented by the user
+        Self::assert_authentic(callback, &callback_handle);
+
+        // Return the CallbackHandle NFR
+        callback_handle
+    }
+
+    /// Verify the validity of a Callback against the CallbackHandle that the CallbackScheduler
+    /// gives back after an execution of a recurring callback. This method must be called from the
+    /// `return_callback_handle` method of the auth provider before it stores the CallbackHandle.
+    ///
+    /// **It is vitally important that any auth provider of recurring callbacks calls this method!**
+    /// Otherwise an attacker could deposit CallbackHandles of arbitrary callbacks.
+    ///
+    /// # Arguments:
+    /// * `callback` - The callback to verify
+    /// * `callback_handle` - The CallbackHandle that was given back
+    pub fn verify_returned(callback: &Proof, callback_handle: &Bucket) {
+        Self::assert_authentic(callback, callback_handle);
+    }
+
+    /// Asserts that the callback is an authentic Callback NFR of the same CallbackScheduler
+    /// as the CallbackHandle, and that both have the same ID
+    fn assert_authentic(callback: &Proof, callback_handle: &Bucket) {
         // Determine the actual resource address of the supposed
         // Callback NFR that was given to this method
         let actual_callback_resource = callback.resource_address();
-        // Load the CallbackHandle corresponding to this callback
         let callback = callback.non_fungible::<Callback>().data();
-        let callback_handle = callback_handle_provider(&callback.id);
         // Assert that the IDs of the Callback NFR and CallbackHandle NFR match
-        // This ensures that the callback_handle_provider is correctly implemented by the user
         assert_eq!(
             callback.id,
             callback_handle.non_fungible::<CallbackHandle>().id(),
@@ -564,9 +989,6 @@ impl Callback {
             actual_callback_resource, expected_callback_resource,
             "Invalid Callback resource"
         );
-
-        // Return the CallbackHandle NFR
-        callback_handle
     }
 }
 
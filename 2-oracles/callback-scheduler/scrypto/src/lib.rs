@@ -2,3 +2,4 @@ mod utils;
 mod callback_scheduler;
 mod dummy_component;
 mod test_component;
+mod test_oracle;
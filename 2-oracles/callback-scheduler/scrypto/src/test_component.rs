@@ -69,7 +69,8 @@ blueprint! {
                     && require(protector_badge.resource_address())))
                 .method("schedule_example_callbacks", rule!(require(admin_badge.resource_address())))
                 .method("cancel_callback", rule!(require(admin_badge.resource_address())))
-                .method("authorize_callback", rule!(allow_all));
+                .method("authorize_callback", rule!(allow_all))
+                .method("return_callback_handle", rule!(allow_all));
 
             let component =  Self {
                 admin_badge: admin_badge.resource_address(),
@@ -92,7 +93,8 @@ blueprint! {
         /// callback) will be performed. It is used to authenticate the callback and to also
         /// authorize the method call by handing out any required proofs to the scheduler component.
         ///
-        /// This is the only method that must be implemented as part of the scheduler integration.
+        /// This is the only method that must be implemented as part of the scheduler integration,
+        /// unless recurring callbacks are scheduled (see `return_callback_handle`).
         ///
         /// # Arguments:
         /// * `callback`: A proof containing a single Callback NFR. This demonstrates that the caller
@@ -124,6 +126,22 @@ blueprint! {
             (callback_handle, proofs)
         }
 
+        /// This method is part of the SchedulerComponent's API and must be implemented if
+        /// recurring callbacks are scheduled. After each execution of a recurring callback that has
+        /// executions left, the scheduler component gives the CallbackHandle back through this
+        /// method so that it can be handed out again by `authorize_callback`.
+        ///
+        /// # Arguments:
+        /// * `callback`: A proof containing the Callback NFR of the executed callback
+        /// * `callback_handle`: The CallbackHandle NFR that is associated with the callback
+        pub fn return_callback_handle(&mut self, callback: Proof, callback_handle: Bucket) {
+            // Verify the callback and CallbackHandle are authentic. It is VERY IMPORTANT that this
+            // method is called here!
+            Callback::verify_returned(&callback, &callback_handle);
+
+            self.callback_handles.put(callback_handle);
+        }
+
 
         /// A dummy method for demo purposes
         ///
@@ -210,12 +228,38 @@ blueprint! {
                 Trigger::AtEpoch(10), current_component, "public_method", args!("Sam"), None)
                 .schedule_callback(self.scheduler_component, fee);
             self.callback_handles.put(callback_handle);
+            debug!("");
+
+            // Schedule a 6th example callback
+            let(callback_handle, fee) = CallbackRequest::new(
+                Trigger::WhenPriceCrosses {
+                    pair: "XRD/USD".to_owned(),
+                    price: dec!("2"),
+                    direction: PriceDirection::Above
+                },
+                current_component,
+                "public_method",
+                args!("Keeper"),
+                None)
+                .schedule_callback(self.scheduler_component, fee);
+            self.callback_handles.put(callback_handle);
+            debug!("");
+
+            // Schedule a 7th example callback that is executed twice
+            let(callback_handle, fee) = CallbackRequest::new(
+                Trigger::EveryEpochs { first_epoch: 2, interval: 2, times: 2 },
+                current_component,
+                "public_method",
+                args!("Hal"),
+                None)
+                .schedule_callback(self.scheduler_component, fee);
+            self.callback_handles.put(callback_handle);
 
             fee
         }
 
-        /// Cancels a callback with the given ID
-        pub fn cancel_callback(&mut self, callback_id: NonFungibleId) {
+        /// Cancels a callback with the given ID and returns the refunded fees
+        pub fn cancel_callback(&mut self, callback_id: NonFungibleId) -> Bucket {
             let callback_handle = self.callback_handles.take_non_fungible(&callback_id);
             borrow_component!(self.scheduler_component)
                 .call("cancel_callbacks", args!(callback_handle))
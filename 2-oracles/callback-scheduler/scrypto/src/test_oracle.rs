@@ -0,0 +1,48 @@
+use scrypto::prelude::*;
+
+blueprint! {
+
+    /// This is an example oracle that stands in for a time oracle and a price oracle in the
+    /// automated test. It exposes the same `millis_since_epoch` and `get_price` methods the
+    /// CallbackScheduler component expects, but anybody may set the values.
+    struct TestOracle {
+        /// The milliseconds ticked since the oracle was instantiated
+        millis: u64,
+
+        /// The price of each pair and the epoch it was set at
+        prices: HashMap<String, (Decimal, u64)>,
+    }
+
+    impl TestOracle {
+
+        /// Instantiates our TestOracle
+        pub fn instantiate_test_oracle() -> ComponentAddress {
+            Self {
+                millis: 0,
+                prices: HashMap::new(),
+            }
+            .instantiate()
+            .globalize()
+        }
+
+        /// Advances the time by the given milliseconds
+        pub fn tick(&mut self, millis_since_last_tick: u64) {
+            self.millis += millis_since_last_tick;
+        }
+
+        /// Returns the milliseconds ticked since the oracle was instantiated, whatever the epoch
+        pub fn millis_since_epoch(&self, _epoch: u64) -> u64 {
+            self.millis
+        }
+
+        /// Sets the price of a pair
+        pub fn set_price(&mut self, pair: String, price: Decimal) {
+            self.prices.insert(pair, (price, Runtime::current_epoch()));
+        }
+
+        /// Returns the price of a pair and the epoch it was set at
+        pub fn get_price(&self, pair: String) -> (Decimal, u64) {
+            *self.prices.get(&pair).expect("Unknown pair")
+        }
+    }
+}
@@ -63,4 +63,70 @@ pub(crate)  fn debug_log_non_fungible(message: &str, non_fungible: &Bucket) {
         non_fungible.non_fungible_ids().into_iter().next().unwrap(),
         non_fungible.resource_address()
     );
-}
\ No newline at end of file
+}
+/// Parses an ISO 8601 date time string into a UNIX timestamp in seconds.
+///
+/// Accepts `YYYY-MM-DDTHH:MM[:SS]` followed by an optional UTC offset (`Z`, `+HH`, `+HH:MM`, `-HH:MM`...).
+/// Fractions of seconds are ignored. Returns `None` if the string is not a valid date time.
+pub(crate) fn parse_iso_date_time(date_time: &str) -> Option<u64> {
+    let (date, time) = date_time.split_once('T')?;
+
+    let mut date = date.split('-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if date.next().is_some()
+        || year < 1970
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+    {
+        return None;
+    }
+
+    // Split the time of day from the UTC offset
+    let (time, offset_sign, offset) = match time.find(|c| c == 'Z' || c == '+' || c == '-') {
+        Some(i) if &time[i..] == "Z" => (&time[..i], 0i64, ""),
+        Some(i) => (
+            &time[..i],
+            if &time[i..i + 1] == "+" { 1 } else { -1 },
+            &time[i + 1..],
+        ),
+        None => (time, 0, ""),
+    };
+
+    let mut time = time.split(':');
+    let hours = time.next()?.parse::<u64>().ok()?;
+    let minutes = time.next()?.parse::<u64>().ok()?;
+    let seconds = match time.next() {
+        Some(seconds) => seconds.split('.').next()?.parse::<u64>().ok()?,
+        None => 0,
+    };
+    if time.next().is_some() || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    let offset_seconds = if offset.is_empty() {
+        0
+    } else {
+        let mut offset = offset.split(':');
+        let offset_hours = offset.next()?.parse::<i64>().ok()?;
+        let offset_minutes = match offset.next() {
+            Some(minutes) => minutes.parse::<i64>().ok()?,
+            None => 0,
+        };
+        offset_sign * (offset_hours * 3600 + offset_minutes * 60)
+    };
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let local_seconds = (days * 86400 + hours * 3600 + minutes * 60 + seconds) as i64;
+    u64::try_from(local_seconds - offset_seconds).ok()
+}
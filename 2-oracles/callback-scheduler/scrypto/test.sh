@@ -24,6 +24,16 @@ export callback_admin_handle_resource=$(echo "$result" | sed -nr "s/.*callback_a
 export callback_handle_resource=$(echo "$result" | sed -nr "s/.*callback_handle_resource=([[:alnum:]_]+)/\1/p")
 printf "$result\n\n"
 
+# Instantiate a TestOracle that stands in for the time and price oracles, the time is
+# anchored shortly before callback #3 is due (2022-10-01T12:42+00:00) and XRD is worth 1 USD
+result=$(resim call-function $package TestOracle instantiate_test_oracle)
+printf "$result\n\n"
+export oracle_component=$(echo "$result" | sed -nr "s/.*Component: ([[:alnum:]_]+)/\1/p")
+resim call-method $oracle_component set_price XRD/USD 1 ; echo
+
+# Connect the TestOracle to the CallbackScheduler component
+resim run transactions/test/admin/set_oracles.rtm ; echo
+
 # Create another account for a user of the CallbackScheduler component
 result=$(resim new-account)
 printf "$result\n\n"
@@ -40,7 +50,7 @@ printf "$result\n\n"
 export test_component=$(echo "$result" | sed -nr "s/.*Component: ([[:alnum:]_]+)/\1/p")
 export test_component_admin_badge=$(echo "$result" | sed -nr "s/.*admin_badge=([[:alnum:]_]+)/\1/p")
 
-# Schedule 5 example callbacks
+# Schedule 7 example callbacks, the 7th callback is executed twice so 8 fees are paid
 result=$(resim run transactions/test/user/schedule_example_callbacks.rtm)
 printf "$result\n\n"
 export callback_id_1=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '1!d')
@@ -48,9 +58,11 @@ export callback_id_2=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:a
 export callback_id_3=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '3!d')
 export callback_id_4=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '4!d')
 export callback_id_5=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '5!d')
+export callback_id_6=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '6!d')
+export callback_id_7=$(echo "$result" | sed -nr "s/.*CallbackAdminHandle: #([[:alnum:]_]+).*/\1/p" | sed '7!d')
 
 # Immediately cancel callback #5
-# At the moment, fees are not reimbursed.
+# The fee is refunded to the user
 resim run transactions/test/user/cancel_callback_#5.rtm ; echo
 
 # Take on the role of the CallbackScheduler admin/operator
@@ -59,7 +71,7 @@ resim set-default-account $admin_account $admin_private_key ; echo
 # Retrieve the admin handles of all newly scheduled callbacks
 resim run transactions/test/admin/get_new_callback_admin_handles.rtm ; echo
 
-# Take a look at the admin account and observe that it contains the CallbackAdminHandles for the first 4 callbacks.
+# Take a look at the admin account and observe that it contains the CallbackAdminHandles for callbacks #1 to #4, #6 and #7.
 # The 5th callback was canceled by the user before we could retrieve it from the scheduler component.
 resim show $admin_account ; echo
 
@@ -71,11 +83,39 @@ resim run transactions/test/admin/execute_callback_#1.rtm || : ; echo
 resim set-current-epoch 1
 resim run transactions/test/admin/execute_callback_#1.rtm ; echo
 
-# Also execute callback #2 and #3
+# Also execute callback #2
 resim run transactions/test/admin/execute_callback_#2.rtm ; echo
+
+# Callback #3 is due at 2022-10-01T12:42+00:00 with a tolerance of 60 seconds
+# Observe that it fails as the time oracle is still more than 60 seconds before that time
+resim run transactions/test/admin/execute_callback_#3.rtm || : ; echo
+
+# Advance the time oracle and try again
+resim call-method $oracle_component tick 28150000 ; echo
 resim run transactions/test/admin/execute_callback_#3.rtm ; echo
 
-# Switch to the user account and cancel the 4th callback
+# Callback #6 is due once XRD is worth 2 USD or more
+# Observe that it fails as the price is still 1 USD
+resim run transactions/test/admin/execute_callback_#6.rtm || : ; echo
+
+# Raise the price and try again
+resim call-method $oracle_component set_price XRD/USD 2.5 ; echo
+resim run transactions/test/admin/execute_callback_#6.rtm ; echo
+
+# Callback #7 is due every 2 epochs from epoch 2 on, two times. Any keeper may execute it
+# and is paid the fee of 10 XRD that the user prepaid. Take on the role of a keeper
+resim set-default-account $user_account $user_private_key ; echo
+resim set-current-epoch 2
+resim run transactions/test/user/execute_due_callback_#7.rtm ; echo
+
+# Observe that it fails as the callback is not due again before epoch 4
+resim run transactions/test/user/execute_due_callback_#7.rtm || : ; echo
+
+# Advance the epoch and try again
+resim set-current-epoch 4
+resim run transactions/test/user/execute_due_callback_#7.rtm ; echo
+
+# Switch to the user account and cancel the 4th callback, the fee is refunded
 # The admin/operator will still be in posession of their CallbackAdminHandle
 # but will no longer be able to execute the callback.
 resim set-default-account $user_account $user_private_key ; echo
@@ -90,8 +130,8 @@ resim run transactions/test/admin/execute_callback_#4.rtm || : ; echo
 resim run transactions/test/admin/cancel_callback_#4.rtm ; echo
 
 # Finally withdraw the earned fees and show the admin account.
-# Observe that all callback handles are now gone and the XRD
-# balance has increased by 50.
+# Observe that all callback handles are now gone, except the one of callback #7 that
+# was executed by a keeper, and the XRD balance has increased by 40.
 resim run transactions/test/admin/withdraw_fees.rtm ; echo
 resim show $admin_account
 